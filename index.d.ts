@@ -3,6 +3,41 @@
 
 /* auto-generated by NAPI-RS */
 
+/** クリップボードから読み取った 1 つのファイル */
+export interface ClipboardFileEntry {
+  /** URI (`file:///...`, `smb://...`, `sftp://...`, `trash:///...` など) */
+  uri: string
+  /**
+   * ローカルで開けるパス。GVFS の URI は `$XDG_RUNTIME_DIR/gvfs` の下の FUSE のパス。
+   * 分からない場合はnull。
+   */
+  path?: string
+  /** URI のスキーム (`"file"`, `"smb"`, `"sftp"` など) */
+  scheme: string
+  /** このマシンのファイル (`file://`) かどうか。GVFS の FUSE のパスでは false。 */
+  isLocal: boolean
+  /** `withMetadata` を指定した場合の `path` のメタデータ。`path` がない場合はnull。 */
+  metadata?: ClipboardFileMetadata
+}
+/**
+ * ファイルのメタデータ
+ *
+ * `kind` はシンボリックリンク自体、それ以外はリンク先の値。
+ */
+export interface ClipboardFileMetadata {
+  /** パス（シンボリックリンクの場合はリンク先）が存在するかどうか */
+  exists: boolean
+  /** 種類 (`"file"`, `"dir"`, `"symlink"`, `"other"`)。存在しない場合はnull。 */
+  kind?: string
+  /** サイズ（バイト） */
+  size?: number
+  /** 更新日時（UNIX エポックからのミリ秒。`new Date(mtimeMs)` で使える） */
+  mtimeMs?: number
+  /** パーミッションのビット（`0o644` など）。Windows ではnull。 */
+  mode?: number
+  /** 読み取り専用かどうか */
+  readonly?: boolean
+}
 /**
  * クリップボードから読み取ったデータを保持する構造体
  * `read_clipboard_file_paths` から成功した値を抽出して生成することを想定
@@ -10,8 +45,94 @@
 export interface ClipboardContent {
   /** ファイルパスのリスト。読み取りに失敗した場合は空の配列。 */
   filePaths: Array<string>
+  /**
+   * ファイルの URI とパスのリスト。パスにできない URI (`trash://` など) も含む。
+   * 読み取りに失敗した場合は空の配列。
+   */
+  entries: Array<ClipboardFileEntry>
   /** テキスト内容。読み取りに失敗した場合はnull。 */
   text?: string
+  /**
+   * `filePaths` の取得元。ファイル形式から読み取った場合は `"clipboard"`、
+   * テキストから抽出した場合は `"text"`。パスがない場合はnull。
+   */
+  source?: string
+}
+/** `read_clipboard_text` で読み取ったテキストと、その形式と文字コード */
+export interface ClipboardText {
+  /** テキスト内容 */
+  text: string
+  /** 読み取った形式（X11 のターゲット、ペーストボードタイプ、または `"CF_UNICODETEXT"`） */
+  sourceFormat: string
+  /** デコードに使った文字コード（`"UTF-8"`, `"ISO-8859-1"`, `"COMPOUND_TEXT"`, `"UTF-16LE"`） */
+  charset: string
+  /** 変換できないバイトを U+FFFD に置き換えたかどうか */
+  wasLossy: boolean
+}
+/**
+ * クリップボードがロックされている場合などの再試行のポリシー
+ *
+ * 省略した項目は `set_clipboard_retry_policy` で設定した全体のポリシーの値になる。
+ */
+export interface RetryOptions {
+  /** 最初の試行を含めた最大の試行回数。1 なら再試行しない。デフォルトは 5。 */
+  attempts?: number
+  /** 最初の再試行までの待ち時間（ミリ秒）。再試行のたびに 2 倍になる。デフォルトは 10。 */
+  backoffMs?: number
+  /** 最初の試行からの合計の制限時間（ミリ秒）。デフォルトは 1000。 */
+  deadlineMs?: number
+}
+/** `read_clipboard_file_paths` のオプション */
+export interface ReadClipboardOptions {
+  /**
+   * ファイル形式のパスがない場合に、テキストからファイルパスを抽出するかどうか。
+   * 存在するパスだけが `source: "text"` として返される。デフォルトは false。
+   */
+  parseTextAsPaths?: boolean
+  /**
+   * 各エントリにパスのメタデータ（サイズ、更新日時、種類、パーミッション、存在するか）を
+   * 含めるかどうか。デフォルトは false。
+   */
+  withMetadata?: boolean
+  /** この呼び出しの再試行のポリシー */
+  retry?: RetryOptions
+}
+/** `expand_clipboard_paths` のオプション */
+export interface ExpandClipboardPathsOptions {
+  /** サブフォルダーの中もたどるかどうか。false ならコピーしたフォルダーの直下だけ。デフォルトは true。 */
+  recursive?: boolean
+  /** シンボリックリンクをたどるかどうか。false ならリンク自体を返す。デフォルトは false。 */
+  followSymlinks?: boolean
+  /** 返すエントリの最大数。達した場合は `truncated: true` で止める。デフォルトは 10000。 */
+  maxEntries?: number
+  /** 含めるファイルのグロブ (`"*.png"`, `"docs/**"`)。省略時はすべてのファイル。 */
+  include?: Array<string>
+  /** 除くファイルとフォルダーのグロブ (`"node_modules"`, `".git"`) */
+  exclude?: Array<string>
+  /** クリップボードの読み取りの再試行のポリシー */
+  retry?: RetryOptions
+}
+/** `expand_clipboard_paths` で展開したファイル */
+export interface ExpandedClipboardPath {
+  /** パス */
+  path: string
+  /** このファイルを含む、コピーされたパス */
+  root: string
+  /** `root` からの相対パス (`/` 区切り)。コピーされたのがファイルの場合はファイル名。 */
+  relativePath: string
+  /** 種類 (`"file"`, `"symlink"`, `"other"`) */
+  kind: string
+  /** サイズ（バイト） */
+  size: number
+}
+/** `expand_clipboard_paths` の結果 */
+export interface ExpandedClipboardPaths {
+  /** 展開したファイル（フォルダーは含まない） */
+  entries: Array<ExpandedClipboardPath>
+  /** `maxEntries` に達して途中で止めたかどうか */
+  truncated: boolean
+  /** 読み取れなかったフォルダーなどのエラー */
+  errors: Array<string>
 }
 /** クリップボードのバイナリデータを読みやすい形式で表示するための構造体 */
 export interface ReadableClipboardContent {
@@ -26,6 +147,182 @@ export interface ReadableClipboardContent {
   /** 最初の数バイトのプレビュー */
   preview?: string
 }
+/** `clipboard_doctor` のオプション */
+export interface ClipboardDoctorOptions {
+  /**
+   * クリップボードへの書き込み・読み取りの自己テストを行うかどうか。
+   * クリップボードの内容が書き換わる。デフォルトは true。
+   */
+  roundTrip?: boolean
+}
+/** クリップボード環境の診断項目 */
+export interface ClipboardDoctorCheck {
+  /** 項目名 (`display`, `xConnection`, `xfixes`, `clipboardManager`, `helperTools`, `roundTrip`) */
+  name: string
+  /** `"ok"`, `"warning"`, `"error"`, `"skipped"` のいずれか */
+  status: string
+  /** 結果の詳細 */
+  message: string
+}
+/** クリップボードのヘルパーコマンドの検出結果 */
+export interface ClipboardDoctorTool {
+  /** コマンド名 */
+  name: string
+  /** 見つかった実行ファイルのパス。見つからない場合はnull。 */
+  path?: string
+  /** バージョン表示の1行目。取得できない場合はnull。 */
+  version?: string
+}
+/** クリップボード環境の診断結果 */
+export interface ClipboardDoctorReport {
+  /** OS名 (`linux`, `macos`, `windows`) */
+  platform: string
+  /** `DISPLAY` 環境変数の値 */
+  display?: string
+  /** `WAYLAND_DISPLAY` 環境変数の値 */
+  waylandDisplay?: string
+  /** エラーとなった項目がなければ true */
+  ok: boolean
+  /** 各診断項目の結果 */
+  checks: Array<ClipboardDoctorCheck>
+  /** ヘルパーコマンドの検出結果 (Linuxのみ) */
+  tools: Array<ClipboardDoctorTool>
+}
+/** `snapshot_clipboard` のオプション */
+export interface SnapshotClipboardOptions {
+  /** 読み取る形式 (`"filePaths"`, `"text"`, `"raw"`)。省略時はすべての形式。 */
+  formats?: Array<string>
+  /** 読み取り中にクリップボードの所有者が変わった場合に読み直す最大回数。デフォルトは 3。 */
+  maxAttempts?: number
+  /** この呼び出しの再試行のポリシー（ロックされている場合など、各形式の読み取りに使う） */
+  retry?: RetryOptions
+}
+/** 1回の所有期間内に読み取ったクリップボードの内容 */
+export interface ClipboardSnapshot {
+  /** ファイルパスのリスト。要求されていない、または読み取れなかった場合はnull。 */
+  filePaths?: Array<string>
+  /** テキスト内容。要求されていない、または読み取れなかった場合はnull。 */
+  text?: string
+  /** 生のデータ。要求されていない、または読み取れなかった場合はnull。 */
+  raw?: Buffer
+  /** 読み取りを行った回数 */
+  attempts: number
+  /**
+   * 読み取りの前後で所有者が変わっていないことを確認できたかどうか。
+   * 所有期間を取得できない環境 (X11 のない Wayland など) では false。
+   */
+  verified: boolean
+}
+/** `get_clipboard_change_count` / `has_clipboard_changed_since` のオプション */
+export interface ClipboardChangeOptions {
+  /**
+   * 対象のセレクション (`"clipboard"`, `"primary"`)。デフォルトは `"clipboard"`。
+   * `"primary"` は Linux (X11) でのみ利用できる。
+   */
+  selection?: string
+}
+/** パスの先頭部分の対応（`from` で始まるパスを `to` で始まるパスにする） */
+export interface PathMappingOptions {
+  /** 置き換える先頭部分（`"/mnt/server"`、`"C:\\"` など） */
+  from: string
+  /** 置き換えた後の先頭部分。パスのほか `"sftp://user@host/srv"` のような URI も書ける。 */
+  to: string
+}
+/** `write_clipboard_file_paths` のオプション */
+export interface WriteClipboardOptions {
+  /**
+   * 書き込み元を示す印 (`org.nspasteboard.source`) の内容。macOS の慣習ではバンドル ID。
+   * `getClipboardOwnerInfo()` の `source` として読み取れる。
+   */
+  sourceMarker?: string
+  /**
+   * パスと一緒に書き込むテキストの形式
+   * (`"none"`, `"paths"`, `"uris"`, `"shell-quoted"`, `"custom"`)。
+   * 省略時は各プラットフォームの従来の内容（Windows は file URI、Linux はパス）。
+   */
+  textRepresentation?: string
+  /**
+   * `textRepresentation: "custom"` のテンプレート。パスごとに `{path}`, `{uri}`, `{name}`,
+   * `{quoted}` を置き換え、改行区切りで並べる。
+   */
+  customTemplate?: string
+  /**
+   * テキストのパスをこのディレクトリからの相対パスで表す（URI は絶対パスのまま）。
+   * `textRepresentation` を省略した場合は `"paths"` になる。
+   */
+  relativeTo?: string
+  /** テキストと URI のリストに書くパスを変換する対応表。最も長く一致するものを使う。 */
+  pathMappings?: Array<PathMappingOptions>
+  /**
+   * WSL のディストリビューション名（`process.env.WSL_DISTRO_NAME` など）。
+   * 指定すると `/mnt/c/...` を `C:\...`、それ以外を `\\wsl$\<distro>\...` にする対応を
+   * `pathMappings` の後に追加する。
+   */
+  wslDistro?: string
+  /** この呼び出しの再試行のポリシー */
+  retry?: RetryOptions
+}
+/** `write_clipboard_text` のオプション */
+export interface WriteClipboardTextOptions {
+  /**
+   * 改行コード (`"lf"`, `"crlf"`, `"native"`)。省略時はテキストの改行をそのまま書き込む。
+   * `"native"` は Windows では `"crlf"`、それ以外では `"lf"`。
+   */
+  lineEndings?: string
+  /** テキスト中の存在するファイルパスを URI のリストとしても書き込むかどうか。デフォルトは false。 */
+  alsoAsUriList?: boolean
+  /** この呼び出しの再試行のポリシー */
+  retry?: RetryOptions
+}
+/** クリップボードの所有者の情報 */
+export interface ClipboardOwnerInfo {
+  /** 所有者のウィンドウ（X11 のウィンドウ ID、Windows の HWND）。macOS では常にnull。 */
+  window?: number
+  /** 所有者のプロセス ID。取得できない場合はnull。 */
+  pid?: number
+  /** 所有者の実行ファイルのパス。取得できない場合はnull。 */
+  executable?: string
+  /** 書き込み元が付けた `org.nspasteboard.source` の印。ない場合はnull。 */
+  source?: string
+}
+/** `clear_clipboard` のオプション */
+export interface ClearClipboardOptions {
+  /**
+   * 対象のセレクション (`"clipboard"`, `"primary"`)。デフォルトは `"clipboard"`。
+   * `"primary"` は Linux (X11) でのみ利用できる。
+   */
+  selection?: string
+  /**
+   * このプロセスが最後に書き込んだ内容がまだ残っている場合だけ空にするかどうか。
+   * デフォルトは false。
+   */
+  onlyIfOwner?: boolean
+  /** この呼び出しの再試行のポリシー */
+  retry?: RetryOptions
+}
+/** `write_clipboard_lazy` のオプション */
+export interface LazyClipboardOptions {
+  /**
+   * 提供するフォーマット名（Linux は MIME タイプ、macOS はペーストボードタイプ、
+   * Windows は登録済みのフォーマット名または数値の ID）
+   */
+  formats: Array<string>
+  /**
+   * 貼り付け先が要求したフォーマットのデータを返す関数。
+   * `null` / `undefined` を返すとそのフォーマットは提供されない。
+   */
+  provider: (format: string) => Buffer | string | null | undefined
+}
+/** `start_file_drag` の結果 */
+export interface FileDragResult {
+  /**
+   * `"dropped"`（ドロップされた）、`"rejected"`（ドロップ先がない、または受け付けられなかった）、
+   * `"cancelled"`（Escape キーなどで中止された）のいずれか
+   */
+  outcome: string
+  /** ドロップ先が実行した操作 (`"copy"`, `"move"`, `"link"` など)。ドロップされなかった場合は null */
+  action?: string
+}
 /** Hello World関数 - 動作確認用 */
 export declare function helloWorld(): string
 /**
@@ -34,22 +331,106 @@ export declare function helloWorld(): string
  * # Arguments
  * * `paths` - A list of absolute or relative file paths to copy.
  *   - The paths will be registered to the clipboard in the appropriate format for each OS.
- *   - Passing an empty list will result in an error.
+ *   - Passing an empty list clears the clipboard on every platform, exactly like
+ *     `clearClipboard()` (no source marker is written).
+ * * `options` - Optional write options.
+ *   - `sourceMarker`: Also write an `org.nspasteboard.source` marker (conventionally the
+ *     bundle id of the writing application) that `getClipboardOwnerInfo()` reports as `source`.
+ *     On Linux the marker needs an X server; with only Wayland the call fails.
+ *   - `textRepresentation`: The text written next to the file list, the same on every OS:
+ *     `"none"`, `"paths"` (one per line), `"uris"` (`file://` URIs, one per line),
+ *     `"shell-quoted"` (quoted for the platform shell, space-separated) or `"custom"`
+ *     (`customTemplate` expanded per path with `{path}`, `{uri}`, `{name}` and `{quoted}`).
+ *     Defaults to `file://` URIs on Windows and plain paths on Linux. On Linux a text
+ *     representation needs an X server, like the source marker (otherwise the call fails).
+ *   - `relativeTo`: Show the paths in the text relative to this directory (e.g. for
+ *     "Copy Relative Path"), using `..` where needed. The file list and `file://` URIs stay
+ *     absolute, and a path that cannot be made relative (another drive or UNC share) stays
+ *     absolute. Implies `textRepresentation: "paths"` when that is omitted.
+ *   - `pathMappings`: `{ from, to }` prefixes that translate the paths in the text and in
+ *     the `text/uri-list` (e.g. a sshfs mount to `sftp://user@host/dir`). The longest
+ *     matching prefix wins, after `.` and `..` are resolved; unmatched paths are unchanged.
+ *   - `wslDistro`: Appends the WSL mappings for this distribution after `pathMappings`, so
+ *     `/mnt/c/...` becomes `C:\...` and other paths `\\wsl$\<distro>\...`.
  *
  * # Returns
- * * Returns `Ok(())` if the operation succeeds.
- * * Returns `Err(napi::Error)` if an error occurs.
+ * * Returns `Ok(ClipboardOwnership)` if the operation succeeds. Use it to learn when another
+ *   application replaces the clipboard contents (`isOwner()`, `onLost(callback)`).
+ * * Returns `Err(napi::Error)` for an unknown `textRepresentation` (or `"custom"` without
+ *   `customTemplate`), a `relativeTo` directory that does not exist, or if an error occurs.
  *
  * # Note
  * * This function will actually change the contents of the system clipboard.
  * * Please be careful when running tests.
  */
-export declare function writeClipboardFilePaths(paths: Array<string>): void
+export declare function writeClipboardFilePaths(paths: Array<string>, options?: WriteClipboardOptions | undefined | null): ClipboardOwnership
+/**
+ * Writes plain text to the OS clipboard.
+ *
+ * # Arguments
+ * * `text` - The text to write.
+ * * `options` - Optional write options.
+ *   - `lineEndings`: Convert every line break (`\n`, `\r\n` or `\r`) to `"lf"`, `"crlf"` or
+ *     `"native"` (CRLF on Windows, LF elsewhere). By default the text is written unchanged.
+ *   - `alsoAsUriList`: Also publish the existing paths found in the text (recognized like
+ *     `parseTextAsPaths`) as a list of `file://` URIs, so file managers can paste them.
+ *
+ * # Returns
+ * * Returns `Ok(ClipboardOwnership)` if the operation succeeds.
+ * * Returns `Err(napi::Error)` for an unknown `lineEndings` value or if the text cannot be written.
+ *   On Linux `alsoAsUriList` with paths fails without an X server instead of writing the text
+ *   alone.
+ *
+ * # Note
+ * * Linux publishes `UTF8_STRING`, `text/plain;charset=utf-8` and `text/plain` (plus
+ *   `text/uri-list` with `alsoAsUriList`, which needs an X server), Windows publishes
+ *   `CF_UNICODETEXT` (plus a registered `text/uri-list` format) and macOS publishes
+ *   `public.utf8-plain-text` (plus `public.file-url` items).
+ */
+export declare function writeClipboardText(text: string, options?: WriteClipboardTextOptions | undefined | null): ClipboardOwnership
+/**
+ * Clears the OS clipboard.
+ *
+ * # Arguments
+ * * `options` - Optional clear options.
+ *   - `selection`: `"clipboard"` (default) or `"primary"` (Linux X11 only).
+ *   - `onlyIfOwner`: Only clear when the content most recently written by this process
+ *     (`writeClipboardFilePaths`, `writeClipboardText`, `writeClipboardLazy` or an earlier clear)
+ *     is still on the selection, so data copied by another application in the meantime is
+ *     left alone.
+ *
+ * # Returns
+ * * Returns `Ok(true)` if the selection was cleared, `Ok(false)` if it was left untouched because
+ *   of `onlyIfOwner`.
+ * * Returns `Err(napi::Error)` for an unknown selection, or if the clipboard cannot be cleared
+ *   (e.g. on Linux without an X server, `xsel` or `wl-copy`).
+ *
+ * # Note
+ * * `writeClipboardFilePaths([])` behaves like `clearClipboard()`.
+ * * On X11 the selection owner is removed; a clipboard manager may take it over again.
+ */
+export declare function clearClipboard(options?: ClearClipboardOptions | undefined | null): boolean
 /**
  * Reads content from the OS clipboard, trying to extract both file paths and text independently.
  *
+ * # Arguments
+ * * `options` - Optional read options.
+ *   - `parseTextAsPaths`: When no file paths are on the clipboard, recognize existing paths
+ *     in the text (absolute, `~/`, `file://`, quoted/escaped shell paths and Windows drive/UNC
+ *     paths) and return them as `filePaths` with `source: "text"`.
+ *   - `withMetadata`: Also return `metadata` for each entry that has a `path`: `exists`,
+ *     `kind` (`"file"`, `"dir"`, `"symlink"` or `"other"`), `size`, `mtimeMs`, `mode`
+ *     (Unix permission bits) and `readonly`. The paths are stat'ed in parallel in Rust,
+ *     which is much faster than calling `fs.stat` for each path of a large selection.
+ *
  * # Returns
  * * Returns `Ok(ClipboardContent)` containing results for both file paths and text reads.
+ *   - `entries` lists every copied URI as `{ uri, path, scheme, isLocal }`, including
+ *     non-`file://` URIs such as GVFS `smb://`, `sftp://`, `trash://` and `recent://`
+ *     locations copied from Nautilus. On Linux a GVFS URI gets the FUSE path under
+ *     `$XDG_RUNTIME_DIR/gvfs` as `path` when gvfsd-fuse exposes the mount (`isLocal` stays
+ *     `false`); otherwise `path` is null.
+ *   - `filePaths` holds the `path` of each entry that has one.
  * * Returns `Err(napi::Error)` if both file paths and text reads failed.
  *
  * # Note
@@ -57,4 +438,278 @@ export declare function writeClipboardFilePaths(paths: Array<string>): void
  * * If at least one of the reads succeeds, the function returns success with available data.
  * * Only returns an error if both file paths and text reads fail.
  */
-export declare function readClipboardFilePaths(): ClipboardContent
+export declare function readClipboardFilePaths(options?: ReadClipboardOptions | undefined | null): ClipboardContent
+/**
+ * Reads the clipboard text and reports which format and charset it was decoded from.
+ *
+ * # Returns
+ * * Returns `Ok(ClipboardText)` with `{ text, sourceFormat, charset, wasLossy }`.
+ *   - Linux: the targets are tried in the order `UTF8_STRING`, `text/plain;charset=utf-8`,
+ *     `STRING` (ISO-8859-1), `COMPOUND_TEXT` and `TEXT`, and each is decoded with its own
+ *     charset, so text from legacy X applications (e.g. EUC-JP inside `COMPOUND_TEXT`)
+ *     is not garbled.
+ *   - Windows: `CF_UNICODETEXT` decoded as UTF-16LE.
+ *   - macOS: the first pasteboard type that holds a string, as UTF-8.
+ *   - `wasLossy` is `true` when some bytes could not be decoded and became U+FFFD.
+ * * Returns `Err(napi::Error)` if there is no text on the clipboard or it cannot be accessed.
+ */
+export declare function readClipboardText(): ClipboardText
+/**
+ * Reads the file paths on the clipboard and lists the files inside the copied folders.
+ *
+ * # Arguments
+ * * `options` - Optional expansion options.
+ *   - `recursive`: Walk into subfolders (default `true`). With `false` only the files directly
+ *     inside each copied folder are listed.
+ *   - `followSymlinks`: Follow symbolic links (default `false`, the link itself is listed).
+ *     Each folder is walked at most once, so link loops end. Linked folders are walked after
+ *     all others, so a folder is listed under its real path, and files reached only through
+ *     a link come last.
+ *   - `maxEntries`: Stop after this many entries and set `truncated` (default 10000).
+ *   - `include` / `exclude`: Glob patterns (`*`, `**`, `?`, `[...]`) matched against the
+ *     `/`-separated path relative to the copied folder. A pattern without `/` matches the
+ *     file name at any depth. `exclude` also skips the contents of matching folders.
+ *
+ * # Returns
+ * * Returns `Ok(ClipboardPathExpansion)`. `result()` walks the folders on the libuv thread
+ *   pool and resolves with `{ entries, truncated, errors }`; it can only be called once. Each entry is
+ *   `{ path, root, relativePath, kind, size }`, where `relativePath` is relative to the
+ *   copied `root` (the file name when a file was copied). Folders themselves are not
+ *   listed, and folders that cannot be read are reported in `errors`.
+ * * `cancel()` stops the walk and makes `result()` reject.
+ * * Returns `Err(napi::Error)` for an invalid glob pattern or if the clipboard cannot be read.
+ */
+export declare function expandClipboardPaths(options?: ExpandClipboardPathsOptions | undefined | null): ClipboardPathExpansion
+/**
+ * Reads several clipboard formats as one consistent snapshot.
+ *
+ * # Arguments
+ * * `options` - Optional snapshot options.
+ *   - `formats`: Formats to read (`"filePaths"`, `"text"`, `"raw"`). Defaults to all of them.
+ *   - `maxAttempts`: How many times to re-read when the clipboard owner changes mid-read (default: 3).
+ *
+ * # Returns
+ * * Returns `Ok(ClipboardSnapshot)` whose formats all come from the same clipboard ownership,
+ *   checked with the X11 selection timestamp, `GetClipboardSequenceNumber` on Windows or
+ *   `NSPasteboard.changeCount` on macOS. A format that could not be read is `null`.
+ * * Returns `Err(napi::Error)` for an unknown format, or if the clipboard changed during every attempt.
+ */
+export declare function snapshotClipboard(options?: SnapshotClipboardOptions | undefined | null): ClipboardSnapshot
+/**
+ * Returns a value that increases every time the clipboard changes.
+ *
+ * # Arguments
+ * * `options` - Optional change options.
+ *   - `selection`: `"clipboard"` (default) or `"primary"` (Linux only).
+ *
+ * # Returns
+ * * Returns `Ok(number)` without reading the clipboard contents. The value comes from
+ *   XFIXES selection events on Linux (counted from the first call in this process),
+ *   `GetClipboardSequenceNumber` on Windows and `NSPasteboard.changeCount` on macOS.
+ * * Returns `Err(napi::Error)` for an unknown selection, or if the change counter is unavailable
+ *   (e.g. no X server on Linux).
+ */
+export declare function getClipboardChangeCount(options?: ClipboardChangeOptions | undefined | null): number
+/**
+ * Checks whether the clipboard changed since `token` was obtained.
+ *
+ * # Arguments
+ * * `token` - A value previously returned by `getClipboardChangeCount` for the same selection.
+ * * `options` - Optional change options (see `getClipboardChangeCount`).
+ *
+ * # Returns
+ * * Returns `Ok(true)` if the current change count differs from `token`.
+ */
+export declare function hasClipboardChangedSince(token: number, options?: ClipboardChangeOptions | undefined | null): boolean
+/**
+ * Identifies the application that put the current data on the clipboard.
+ *
+ * # Returns
+ * * Returns `Ok(ClipboardOwnerInfo)`. Fields that cannot be derived on this platform are null:
+ *   - Linux (X11): the `XGetSelectionOwner` window, its `_NET_WM_PID` and `/proc/<pid>/exe` (null
+ *     when the owner's `WM_CLIENT_MACHINE` is another host).
+ *   - Windows: the `GetClipboardOwner` window, its process ID and executable.
+ *   - macOS: there is no owner API, so `pid` / `executable` come from the running application
+ *     whose bundle id matches the `org.nspasteboard.source` marker.
+ *   - `source` is the `org.nspasteboard.source` marker on every platform
+ *     (see `writeClipboardFilePaths(paths, { sourceMarker })`).
+ * * Returns `Err(napi::Error)` if the clipboard cannot be accessed (e.g. no X server and
+ *   no clipboard helper on Linux).
+ */
+export declare function getClipboardOwnerInfo(): ClipboardOwnerInfo
+/**
+ * Reads one clipboard format and writes it to a file without holding the whole payload in memory.
+ *
+ * # Arguments
+ * * `format` - The format to read: a MIME type / X11 target on Linux (`"image/png"`),
+ *   a pasteboard type on macOS (`"public.png"`), or a registered format name or numeric
+ *   format ID on Windows (`"PNG"`, `"13"`).
+ * * `dest_path` - The file to write. It is replaced only after the whole payload was read.
+ *
+ * # Returns
+ * * Returns `Ok(number)` with the number of bytes written.
+ * * Returns `Err(napi::Error)` if the format is not available or reading/writing fails.
+ */
+export declare function readClipboardFormatToFile(format: string, destPath: string): number
+/**
+ * Reads one clipboard format as a Node.js `Readable` stream of `Buffer` chunks.
+ *
+ * # Arguments
+ * * `format` - The format to read (see `readClipboardFormatToFile`).
+ *
+ * # Returns
+ * * A `Readable` fed from a background thread: directly from the helper process output or
+ *   X11 `INCR` chunks on Linux, and in 64 KiB slices of the native buffer elsewhere.
+ *   Read errors (e.g. the format is not available) are emitted as an `error` event.
+ * * The thread only reads ahead while the consumer asks for data, so a slow consumer does not
+ *   buffer the whole payload, and `destroy()` stops the read. A stream that is neither read to
+ *   the end nor destroyed keeps the process alive, like a paused socket.
+ * * Requires `process.getBuiltinModule` (Node.js 20.16 or later).
+ */
+export declare function readClipboardFormatStream(format: string): import('stream').Readable
+/**
+ * Registers formats on the clipboard and renders their data only when a paste target asks for it.
+ *
+ * # Arguments
+ * * `options` - Lazy write options.
+ *   - `formats`: Format names to offer (MIME types on Linux, pasteboard types on macOS and
+ *     registered format names on Windows).
+ *   - `provider`: Called with a format name when that format is requested. Returns a `Buffer`
+ *     or a string, or `null`/`undefined` to refuse the format.
+ *
+ * # Returns
+ * * A `ClipboardOwnership` handle, like `writeClipboardFilePaths`.
+ *
+ * # Note
+ * * Linux serves the X11 `CLIPBOARD` selection directly (`SelectionRequest`) and needs an
+ *   X server. Each format is rendered once and kept for later pastes.
+ * * Windows uses delayed rendering (`WM_RENDERFORMAT`) and macOS uses
+ *   `NSPasteboardItemDataProvider`, which needs a running main run loop (e.g. Electron).
+ * * The data is only available while this process is running, and a provider that does not
+ *   return within the clipboard timeout is treated as a failure. A format whose provider fails
+ *   is refused, so the paste target sees it as unavailable.
+ * * On Linux and Windows the provider is called on the JavaScript thread through the event loop.
+ *   Reading a lazily written format synchronously from the same process (for example
+ *   `readClipboardFormatToFile` right after `writeClipboardLazy`) blocks that thread, so the
+ *   provider cannot run and the read fails after the clipboard timeout. Read such formats from
+ *   another process.
+ */
+export declare function writeClipboardLazy(options: LazyClipboardOptions): ClipboardOwnership
+/**
+ * Starts dragging the given files out of a window and resolves when the drag ends.
+ *
+ * # Arguments
+ * * `window_handle` - The window the drag starts from, as returned by Electron's
+ *   `BrowserWindow.getNativeWindowHandle()`.
+ * * `paths` - The files to drag. The drop target receives the same formats as
+ *   `writeClipboardFilePaths` (`text/uri-list`, `x-special/gnome-copied-files` and text).
+ *
+ * # Returns
+ * * A promise of `{ outcome, action }`.
+ *
+ * # Note
+ * * Linux (X11) only. Call it while the mouse button is held (e.g. from a `dragstart` handler);
+ *   the drop happens when the button is released, and Escape cancels the drag.
+ * * The pointer is grabbed from a separate X connection, which fails while another client holds
+ *   a grab. Cancel Chromium's own drag (`event.preventDefault()` in `dragstart`) before calling
+ *   it; a grab that is not released within the clipboard timeout rejects the call.
+ * * If the drop target does not answer within the clipboard timeout after the button is
+ *   released, the drag is cancelled.
+ */
+export declare function startFileDrag(windowHandle: Buffer, paths: Array<string>): Promise<FileDragResult>
+/**
+ * Sets the timeout for clipboard operations that wait on another process.
+ *
+ * # Arguments
+ * * `timeout_ms` - Timeout in milliseconds (default: 5000). Must be greater than 0.
+ *   - On Linux, a helper command (`xclip`, `xsel`, `wl-clipboard`) or X11 request that does not
+ *     finish in time is killed and the call fails with an error whose `code` is `"ETIMEDOUT"`,
+ *     instead of blocking the calling thread while the selection owner does not respond.
+ *
+ * # Returns
+ * * Returns `Err(napi::Error)` with `InvalidArg` for `0`.
+ */
+export declare function setClipboardTimeout(timeoutMs: number): void
+/** Returns the current timeout for clipboard operations in milliseconds. */
+export declare function getClipboardTimeout(): number
+/**
+ * Sets the default retry policy for clipboard operations that fail transiently.
+ *
+ * # Arguments
+ * * `policy` - The fields to change; omitted fields keep their current value.
+ *   - `attempts`: Maximum number of attempts including the first one (default: 5, minimum 1).
+ *   - `backoffMs`: Wait before the first retry in milliseconds, doubled after every retry
+ *     (default: 10).
+ *   - `deadlineMs`: Total time budget in milliseconds; no retry starts after it (default: 1000).
+ *
+ * # Note
+ * * Retries cover failures that go away on their own: `OpenClipboard` failing while another
+ *   process holds the clipboard on Windows, losing an X11 selection race or a conversion that
+ *   fails while the owner changes on Linux, and a failed pasteboard write on macOS.
+ * * When retries run out, the error message reports how many attempts were made.
+ * * Individual calls can override the policy with their `retry` option.
+ */
+export declare function setClipboardRetryPolicy(policy: RetryOptions): void
+/** Returns the default retry policy for clipboard operations. */
+export declare function getClipboardRetryPolicy(): RetryOptions
+/**
+ * Returns the helper command used by the last clipboard operation.
+ *
+ * # Returns
+ * * On Linux, `"xclip"`, `"xsel"` or `"wl-clipboard"`; the first installed helper that supports
+ *   the requested operation is used.
+ * * `null` if no helper has run yet, or on platforms that use native APIs.
+ */
+export declare function getClipboardHelper(): string | null
+/**
+ * Checks the prerequisites for clipboard access in the current environment.
+ *
+ * # Arguments
+ * * `options` - Optional doctor options.
+ *   - `roundTrip`: Whether to write and read back a file path as a self-test (default: `true`).
+ *     This changes the contents of the system clipboard.
+ *
+ * # Returns
+ * * A structured report. On Linux it covers `DISPLAY`/`WAYLAND_DISPLAY`, the X connection,
+ *   XFIXES availability, installed helper tools and clipboard manager presence.
+ * * The same report is available from the command line via `clip-filepaths-doctor`.
+ */
+export declare function clipboardDoctor(options?: ClipboardDoctorOptions | undefined | null): ClipboardDoctorReport
+/**
+ * クリップボードに書き込んだ内容の所有状態
+ *
+ * 書き込み API の戻り値。別のアプリがクリップボードを書き換えると所有を失う。
+ */
+export class ClipboardOwnership {
+  /**
+   * Returns whether the content written by this call is still on the clipboard.
+   *
+   * Uses the X11 selection owner or the `wl-copy` process on Linux, and the clipboard
+   * sequence number (`GetClipboardSequenceNumber` / `changeCount`) on Windows and macOS.
+   */
+  isOwner(): boolean
+  /**
+   * Calls `callback` once when another application replaces the clipboard contents.
+   *
+   * The watcher runs on a background thread and does not keep the Node.js process alive.
+   * It keeps running after this handle is garbage-collected, so
+   * `writeClipboardFilePaths(paths).onLost(callback)` works without keeping the handle.
+   * Call `dispose()` to stop watching.
+   *
+   * Throws if the ownership cannot be tracked (e.g. on Linux without an X server or
+   * `wl-copy`). If watching fails later, `callback` is called once with the error.
+   */
+  onLost(callback: (error?: Error) => void): void
+  /** Stops all `onLost` watchers of this handle. */
+  dispose(): void
+}
+/** `expand_clipboard_paths` で開始した展開 */
+export class ClipboardPathExpansion {
+  /** Stops the walk. `result()` then rejects with a cancellation error. */
+  cancel(): void
+  /**
+   * Walks the copied folders on the libuv thread pool and resolves with the expanded files.
+   * Can only be called once.
+   */
+  result(): Promise<ExpandedClipboardPaths>
+}
@@ -310,8 +310,28 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { helloWorld, writeClipboardFilePaths, readClipboardFilePaths } = nativeBinding
+const { ClipboardOwnership, helloWorld, writeClipboardFilePaths, writeClipboardText, clearClipboard, readClipboardFilePaths, readClipboardText, expandClipboardPaths, ClipboardPathExpansion, snapshotClipboard, getClipboardChangeCount, hasClipboardChangedSince, getClipboardOwnerInfo, readClipboardFormatToFile, readClipboardFormatStream, writeClipboardLazy, startFileDrag, setClipboardTimeout, getClipboardTimeout, setClipboardRetryPolicy, getClipboardRetryPolicy, getClipboardHelper, clipboardDoctor } = nativeBinding
 
+module.exports.ClipboardOwnership = ClipboardOwnership
 module.exports.helloWorld = helloWorld
 module.exports.writeClipboardFilePaths = writeClipboardFilePaths
+module.exports.writeClipboardText = writeClipboardText
+module.exports.clearClipboard = clearClipboard
 module.exports.readClipboardFilePaths = readClipboardFilePaths
+module.exports.readClipboardText = readClipboardText
+module.exports.expandClipboardPaths = expandClipboardPaths
+module.exports.ClipboardPathExpansion = ClipboardPathExpansion
+module.exports.snapshotClipboard = snapshotClipboard
+module.exports.getClipboardChangeCount = getClipboardChangeCount
+module.exports.hasClipboardChangedSince = hasClipboardChangedSince
+module.exports.getClipboardOwnerInfo = getClipboardOwnerInfo
+module.exports.readClipboardFormatToFile = readClipboardFormatToFile
+module.exports.readClipboardFormatStream = readClipboardFormatStream
+module.exports.writeClipboardLazy = writeClipboardLazy
+module.exports.startFileDrag = startFileDrag
+module.exports.setClipboardTimeout = setClipboardTimeout
+module.exports.getClipboardTimeout = getClipboardTimeout
+module.exports.setClipboardRetryPolicy = setClipboardRetryPolicy
+module.exports.getClipboardRetryPolicy = getClipboardRetryPolicy
+module.exports.getClipboardHelper = getClipboardHelper
+module.exports.clipboardDoctor = clipboardDoctor
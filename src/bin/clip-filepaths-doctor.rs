@@ -0,0 +1,59 @@
+// クリップボード環境を診断するコマンドラインツール
+//
+// 使い方: clip-filepaths-doctor [--no-round-trip]
+
+// ライブラリ本体は napi の cdylib のため、診断に必要なモジュールを直接取り込む
+#[allow(dead_code)]
+#[path = "../doctor.rs"]
+mod doctor;
+
+#[cfg(target_os = "macos")]
+#[allow(unexpected_cfgs, dead_code, unused_imports)]
+#[path = "../platforms/mod.rs"]
+mod platforms;
+
+#[cfg(not(target_os = "macos"))]
+#[allow(dead_code, unused_imports)]
+#[path = "../platforms/mod.rs"]
+mod platforms;
+
+#[cfg(target_os = "windows")]
+use platforms::windows as current_platform;
+
+#[cfg(target_os = "macos")]
+use platforms::macos as current_platform;
+
+#[cfg(target_os = "linux")]
+use platforms::linux as current_platform;
+
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+  let mut options = doctor::DoctorOptions::default();
+
+  for arg in std::env::args().skip(1) {
+    match arg.as_str() {
+      "--no-round-trip" => options.round_trip = false,
+      "-h" | "--help" => {
+        println!("Usage: clip-filepaths-doctor [--no-round-trip]");
+        println!();
+        println!("Checks the prerequisites for clipboard access.");
+        println!("  --no-round-trip  Skip the write/read self-test (keeps the clipboard intact)");
+        return ExitCode::SUCCESS;
+      }
+      other => {
+        eprintln!("Unknown argument: {}", other);
+        return ExitCode::from(2);
+      }
+    }
+  }
+
+  let report = doctor::run(&options);
+  println!("{}", report);
+
+  if report.ok() {
+    ExitCode::SUCCESS
+  } else {
+    ExitCode::FAILURE
+  }
+}
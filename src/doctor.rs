@@ -0,0 +1,374 @@
+// クリップボード操作の前提条件を診断する実装
+//
+// `clipboardDoctor()` と `clip-filepaths-doctor` バイナリの両方から使われるため、
+// napi に依存しない形で実装する。
+
+#[cfg(target_os = "linux")]
+use crate::platforms::linux::helper::{find_in_path, run_command, HelperTool};
+#[cfg(target_os = "linux")]
+use crate::platforms::timeout;
+use std::env;
+use std::fmt;
+use std::path::Path;
+
+/// 診断項目の結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+  /// 問題なし
+  Ok,
+  /// 動作はするが注意が必要
+  Warning,
+  /// クリップボード操作が失敗する原因になる
+  Error,
+  /// この環境では確認しない
+  Skipped,
+}
+
+impl CheckStatus {
+  pub fn as_str(&self) -> &'static str {
+    match self {
+      CheckStatus::Ok => "ok",
+      CheckStatus::Warning => "warning",
+      CheckStatus::Error => "error",
+      CheckStatus::Skipped => "skipped",
+    }
+  }
+}
+
+/// 1 つの診断項目
+#[derive(Debug, Clone)]
+pub struct DoctorCheck {
+  pub name: String,
+  pub status: CheckStatus,
+  pub message: String,
+}
+
+impl DoctorCheck {
+  fn new(name: &str, status: CheckStatus, message: impl Into<String>) -> Self {
+    Self {
+      name: name.to_string(),
+      status,
+      message: message.into(),
+    }
+  }
+}
+
+/// ヘルパーコマンドの検出結果
+#[derive(Debug, Clone)]
+pub struct ToolInfo {
+  pub name: String,
+  /// 見つかった実行ファイルのパス。見つからない場合は None
+  pub path: Option<String>,
+  /// バージョン出力の 1 行目。取得できない場合は None
+  pub version: Option<String>,
+}
+
+/// 診断結果
+#[derive(Debug, Clone)]
+pub struct DoctorReport {
+  pub platform: String,
+  pub display: Option<String>,
+  pub wayland_display: Option<String>,
+  pub checks: Vec<DoctorCheck>,
+  pub tools: Vec<ToolInfo>,
+}
+
+impl DoctorReport {
+  /// エラーとなった項目がなければ true
+  pub fn ok(&self) -> bool {
+    self.checks.iter().all(|c| c.status != CheckStatus::Error)
+  }
+}
+
+impl fmt::Display for DoctorReport {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "platform: {}", self.platform)?;
+    if self.platform == "linux" {
+      writeln!(
+        f,
+        "DISPLAY: {}",
+        self.display.as_deref().unwrap_or("(not set)")
+      )?;
+      writeln!(
+        f,
+        "WAYLAND_DISPLAY: {}",
+        self.wayland_display.as_deref().unwrap_or("(not set)")
+      )?;
+    }
+    for tool in &self.tools {
+      match (&tool.path, &tool.version) {
+        (Some(path), Some(version)) => writeln!(f, "tool {}: {} ({})", tool.name, path, version)?,
+        (Some(path), None) => writeln!(f, "tool {}: {}", tool.name, path)?,
+        (None, _) => writeln!(f, "tool {}: not found", tool.name)?,
+      }
+    }
+    for check in &self.checks {
+      writeln!(
+        f,
+        "[{}] {}: {}",
+        check.status.as_str(),
+        check.name,
+        check.message
+      )?;
+    }
+    write!(f, "result: {}", if self.ok() { "ok" } else { "error" })
+  }
+}
+
+/// 診断のオプション
+#[derive(Debug, Clone)]
+pub struct DoctorOptions {
+  /// クリップボードへの書き込み・読み取りの自己テストを行うかどうか
+  /// （クリップボードの内容が書き換わる）
+  pub round_trip: bool,
+}
+
+impl Default for DoctorOptions {
+  fn default() -> Self {
+    Self { round_trip: true }
+  }
+}
+
+/// クリップボード環境を診断する
+pub fn run(options: &DoctorOptions) -> DoctorReport {
+  let mut report = DoctorReport {
+    platform: env::consts::OS.to_string(),
+    display: non_empty_env("DISPLAY"),
+    wayland_display: non_empty_env("WAYLAND_DISPLAY"),
+    checks: Vec::new(),
+    tools: Vec::new(),
+  };
+
+  #[cfg(target_os = "linux")]
+  check_linux_environment(&mut report);
+
+  report.checks.push(if options.round_trip {
+    check_round_trip()
+  } else {
+    DoctorCheck::new("roundTrip", CheckStatus::Skipped, "Disabled by options")
+  });
+
+  report
+}
+
+#[cfg(target_os = "linux")]
+fn check_linux_environment(report: &mut DoctorReport) {
+  report
+    .checks
+    .push(match (&report.display, &report.wayland_display) {
+      (None, None) => DoctorCheck::new(
+        "display",
+        CheckStatus::Error,
+        "Neither DISPLAY nor WAYLAND_DISPLAY is set",
+      ),
+      (Some(display), _) => DoctorCheck::new("display", CheckStatus::Ok, display.clone()),
+      (None, Some(wayland)) => DoctorCheck::new(
+        "display",
+        CheckStatus::Warning,
+        format!(
+          "Only WAYLAND_DISPLAY is set ({}); X11 features are unavailable",
+          wayland
+        ),
+      ),
+    });
+
+  let mut x_connected = false;
+  if report.display.is_some() {
+    match crate::platforms::linux::probe_x11() {
+      Ok(probe) => {
+        x_connected = true;
+        report.checks.push(DoctorCheck::new(
+          "xConnection",
+          CheckStatus::Ok,
+          "Connected to X server",
+        ));
+        report.checks.push(if probe.xfixes {
+          DoctorCheck::new("xfixes", CheckStatus::Ok, "XFIXES extension is available")
+        } else {
+          DoctorCheck::new(
+            "xfixes",
+            CheckStatus::Warning,
+            "XFIXES extension is not available; clipboard change tracking is limited",
+          )
+        });
+        report.checks.push(if probe.clipboard_manager {
+          DoctorCheck::new(
+            "clipboardManager",
+            CheckStatus::Ok,
+            "A clipboard manager owns CLIPBOARD_MANAGER",
+          )
+        } else {
+          DoctorCheck::new(
+            "clipboardManager",
+            CheckStatus::Warning,
+            "No clipboard manager; copied data is lost when the owning process exits",
+          )
+        });
+      }
+      Err(e) => {
+        report.checks.push(DoctorCheck::new(
+          "xConnection",
+          CheckStatus::Error,
+          format!("Failed to connect to X server: {}", e),
+        ));
+      }
+    }
+  } else {
+    for name in ["xConnection", "xfixes", "clipboardManager"] {
+      report.checks.push(DoctorCheck::new(
+        name,
+        CheckStatus::Skipped,
+        "DISPLAY is not set",
+      ));
+    }
+  }
+
+  for tool in HelperTool::ALL {
+    let path = find_in_path(tool.write_program());
+    let version = path
+      .as_ref()
+      .and_then(|p| tool_version(p, &tool.version_args()));
+    report.tools.push(ToolInfo {
+      name: tool.name().to_string(),
+      path: path.map(|p| p.to_string_lossy().into_owned()),
+      version,
+    });
+  }
+
+  report
+    .checks
+    .push(if report.tools.iter().any(|t| t.path.is_some()) {
+      DoctorCheck::new(
+        "helperTools",
+        CheckStatus::Ok,
+        "A clipboard helper is installed",
+      )
+    } else if x_connected {
+      // ファイルパスの読み書きは X11 のセレクションを直接扱って行える
+      DoctorCheck::new(
+        "helperTools",
+        CheckStatus::Warning,
+        "None of xclip, xsel or wl-clipboard is installed; file paths use the native X11 backend and text cannot be read",
+      )
+    } else {
+      DoctorCheck::new(
+        "helperTools",
+        CheckStatus::Error,
+        "None of xclip, xsel or wl-clipboard is installed",
+      )
+    });
+}
+
+// 一時ファイルのパスを書き込み、読み戻して一致するか確認する
+fn check_round_trip() -> DoctorCheck {
+  let path = env::temp_dir().join(format!("clip-filepaths-doctor-{}.txt", std::process::id()));
+  if let Err(e) = std::fs::write(&path, "clip-filepaths doctor") {
+    return DoctorCheck::new(
+      "roundTrip",
+      CheckStatus::Error,
+      format!("Failed to create temporary file: {}", e),
+    );
+  }
+
+  let check = match round_trip_path(&path) {
+    Ok(()) => DoctorCheck::new(
+      "roundTrip",
+      CheckStatus::Ok,
+      "Wrote and read back a file path",
+    ),
+    Err(message) => DoctorCheck::new("roundTrip", CheckStatus::Error, message),
+  };
+
+  let _ = std::fs::remove_file(&path);
+  check
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+fn round_trip_path(path: &Path) -> Result<(), String> {
+  let expected = path
+    .canonicalize()
+    .map_err(|e| format!("Failed to canonicalize temporary file: {}", e))?;
+
+  crate::current_platform::write_clipboard_file_paths(
+    &[path.to_string_lossy().into_owned()],
+    None,
+    None,
+  )
+  .map_err(|e| format!("Write failed: {}", e))?;
+
+  let paths = crate::current_platform::read_clipboard_file_paths()
+    .map_err(|e| format!("Read failed: {}", e))?;
+
+  if paths.iter().any(|p| Path::new(p) == expected) {
+    Ok(())
+  } else {
+    Err(format!(
+      "Read back {:?}, expected {}",
+      paths,
+      expected.display()
+    ))
+  }
+}
+
+#[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+fn round_trip_path(_path: &Path) -> Result<(), String> {
+  Err("Unsupported operating system".to_string())
+}
+
+fn non_empty_env(name: &str) -> Option<String> {
+  env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+// バージョン表示の 1 行目を取得する（xclip は stderr に出力する）
+#[cfg(target_os = "linux")]
+fn tool_version(path: &Path, version_args: &[String]) -> Option<String> {
+  let output = run_command(path, version_args, None, true, timeout::timeout()).ok()?;
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  let stderr = String::from_utf8_lossy(&output.stderr);
+  stdout
+    .lines()
+    .chain(stderr.lines())
+    .map(str::trim)
+    .find(|line| !line.is_empty())
+    .map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // エラー項目があるときだけ ok が false になる
+  #[test]
+  fn test_report_ok() {
+    let mut report = DoctorReport {
+      platform: "linux".to_string(),
+      display: None,
+      wayland_display: None,
+      checks: vec![
+        DoctorCheck::new("a", CheckStatus::Ok, ""),
+        DoctorCheck::new("b", CheckStatus::Warning, ""),
+        DoctorCheck::new("c", CheckStatus::Skipped, ""),
+      ],
+      tools: Vec::new(),
+    };
+    assert!(report.ok());
+
+    report
+      .checks
+      .push(DoctorCheck::new("d", CheckStatus::Error, "broken"));
+    assert!(!report.ok());
+    assert!(report.to_string().contains("[error] d: broken"));
+  }
+
+  // 自己テストを無効にした場合はスキップとして報告される
+  #[test]
+  fn test_run_without_round_trip() {
+    let report = run(&DoctorOptions { round_trip: false });
+    let round_trip = report
+      .checks
+      .iter()
+      .find(|c| c.name == "roundTrip")
+      .unwrap();
+    assert_eq!(round_trip.status, CheckStatus::Skipped);
+  }
+}
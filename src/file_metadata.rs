@@ -0,0 +1,237 @@
+// クリップボードから読み取ったファイルのメタデータの取得
+//
+// 読み取ったパスを JavaScript 側で 1 つずつ stat すると、選択したファイルが多い場合に
+// N-API の呼び出しが遅くなるため、Rust 側で複数のスレッドに分けてまとめて取得する。
+
+use std::fs::{self, Metadata};
+use std::thread;
+use std::time::UNIX_EPOCH;
+
+// 1 つのスレッドで取得するパスの最小の数（少ない場合はスレッドを作らない）
+const MIN_PATHS_PER_THREAD: usize = 16;
+
+// 使うスレッドの最大の数（ネットワークのファイルシステムでも待ちすぎないように）
+const MAX_THREADS: usize = 8;
+
+/// ファイルの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileKind {
+  File,
+  Directory,
+  Symlink,
+  Other,
+}
+
+impl FileKind {
+  /// JavaScript 側に返す名前
+  pub fn as_str(self) -> &'static str {
+    match self {
+      FileKind::File => "file",
+      FileKind::Directory => "dir",
+      FileKind::Symlink => "symlink",
+      FileKind::Other => "other",
+    }
+  }
+}
+
+/// 1 つのパスのメタデータ
+///
+/// 種類はリンク自体（lstat）、それ以外はリンク先（stat）の値。
+/// リンク先がない場合はリンク自体の値で、`exists` は false になる。
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileMetadata {
+  /// パス（シンボリックリンクの場合はリンク先）が存在するかどうか
+  pub exists: bool,
+  /// 種類。パスが存在しない場合は None
+  pub kind: Option<FileKind>,
+  /// サイズ（バイト）
+  pub size: Option<u64>,
+  /// 更新日時（UNIX エポックからのミリ秒）
+  pub modified_ms: Option<f64>,
+  /// パーミッションのビット（`0o755` など）。Unix 以外では None
+  pub mode: Option<u32>,
+  /// 読み取り専用かどうか
+  pub readonly: Option<bool>,
+}
+
+impl FileMetadata {
+  fn missing() -> Self {
+    Self {
+      exists: false,
+      kind: None,
+      size: None,
+      modified_ms: None,
+      mode: None,
+      readonly: None,
+    }
+  }
+}
+
+/// `path` のメタデータを取得する
+pub fn read(path: &str) -> FileMetadata {
+  let Ok(link) = fs::symlink_metadata(path) else {
+    return FileMetadata::missing();
+  };
+  let kind = if link.file_type().is_symlink() {
+    FileKind::Symlink
+  } else if link.is_dir() {
+    FileKind::Directory
+  } else if link.is_file() {
+    FileKind::File
+  } else {
+    FileKind::Other
+  };
+  let target = if kind == FileKind::Symlink {
+    fs::metadata(path).ok()
+  } else {
+    None
+  };
+  let exists = kind != FileKind::Symlink || target.is_some();
+  let metadata = target.as_ref().unwrap_or(&link);
+
+  FileMetadata {
+    exists,
+    kind: Some(kind),
+    size: Some(metadata.len()),
+    modified_ms: modified_ms(metadata),
+    mode: mode(metadata),
+    readonly: Some(metadata.permissions().readonly()),
+  }
+}
+
+/// 各パスのメタデータを、複数のスレッドで取得する（結果は `paths` と同じ順番）
+pub fn read_all(paths: &[&str]) -> Vec<FileMetadata> {
+  let threads = thread::available_parallelism()
+    .map_or(1, |n| n.get())
+    .min(MAX_THREADS)
+    .min(paths.len() / MIN_PATHS_PER_THREAD)
+    .max(1);
+  if threads == 1 {
+    return paths.iter().map(|path| read(path)).collect();
+  }
+
+  let chunk_size = paths.len().div_ceil(threads);
+  thread::scope(|scope| {
+    let workers: Vec<_> = paths
+      .chunks(chunk_size)
+      .map(|chunk| {
+        let worker = scope.spawn(move || chunk.iter().map(|path| read(path)).collect::<Vec<_>>());
+        (chunk.len(), worker)
+      })
+      .collect();
+    // スレッドが失敗しても、順番がずれないように存在しないものとして埋める
+    workers
+      .into_iter()
+      .flat_map(|(len, worker)| {
+        worker
+          .join()
+          .unwrap_or_else(|_| vec![FileMetadata::missing(); len])
+      })
+      .collect()
+  })
+}
+
+fn modified_ms(metadata: &Metadata) -> Option<f64> {
+  let modified = metadata.modified().ok()?;
+  match modified.duration_since(UNIX_EPOCH) {
+    Ok(duration) => Some(duration.as_secs_f64() * 1000.0),
+    // 1970 年より前の日時
+    Err(e) => Some(-e.duration().as_secs_f64() * 1000.0),
+  }
+}
+
+#[cfg(unix)]
+fn mode(metadata: &Metadata) -> Option<u32> {
+  use std::os::unix::fs::PermissionsExt;
+  Some(metadata.permissions().mode() & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn mode(_metadata: &Metadata) -> Option<u32> {
+  None
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "clip-filepaths-metadata-{}-{}",
+      name,
+      std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  // ファイル・ディレクトリ・存在しないパスの種類とサイズ
+  #[test]
+  fn test_read() {
+    let dir = temp_dir("read");
+    let file = dir.join("a.txt");
+    fs::write(&file, b"hello").unwrap();
+
+    let metadata = read(file.to_str().unwrap());
+    assert!(metadata.exists);
+    assert_eq!(metadata.kind, Some(FileKind::File));
+    assert_eq!(metadata.size, Some(5));
+    assert!(metadata.modified_ms.unwrap() > 0.0);
+    assert_eq!(metadata.readonly, Some(false));
+    #[cfg(unix)]
+    assert!(metadata.mode.unwrap() & 0o600 == 0o600);
+
+    assert_eq!(read(dir.to_str().unwrap()).kind, Some(FileKind::Directory));
+    assert_eq!(
+      read(dir.join("missing").to_str().unwrap()),
+      FileMetadata::missing()
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  // シンボリックリンクの種類はリンク自体、サイズはリンク先の値
+  #[cfg(unix)]
+  #[test]
+  fn test_read_symlink() {
+    let dir = temp_dir("symlink");
+    let file = dir.join("target.txt");
+    fs::write(&file, b"0123456789").unwrap();
+    std::os::unix::fs::symlink(&file, dir.join("link")).unwrap();
+    std::os::unix::fs::symlink(dir.join("missing"), dir.join("broken")).unwrap();
+
+    let link = read(dir.join("link").to_str().unwrap());
+    assert!(link.exists);
+    assert_eq!(link.kind, Some(FileKind::Symlink));
+    assert_eq!(link.size, Some(10));
+
+    let broken = read(dir.join("broken").to_str().unwrap());
+    assert!(!broken.exists);
+    assert_eq!(broken.kind, Some(FileKind::Symlink));
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  // 複数のスレッドに分けても `paths` と同じ順番で返る
+  #[test]
+  fn test_read_all_keeps_order() {
+    let dir = temp_dir("order");
+    let paths: Vec<String> = (0..100)
+      .map(|i| {
+        let path = dir.join(format!("{}.txt", i));
+        fs::write(&path, vec![b'x'; i]).unwrap();
+        path.to_string_lossy().into_owned()
+      })
+      .collect();
+    let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+
+    let sizes: Vec<Option<u64>> = read_all(&paths).iter().map(|m| m.size).collect();
+    let expected: Vec<Option<u64>> = (0..100).map(|i| Some(i as u64)).collect();
+    assert_eq!(sizes, expected);
+    assert!(read_all(&[]).is_empty());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,223 @@
+// include / exclude に使うグロブのパターン
+//
+// `*`（`/` 以外の 0 文字以上）、`**`（`/` を含む 0 文字以上）、`?`（`/` 以外の 1 文字）、
+// `[abc]` / `[a-z]` / `[!a]`（文字クラス）、`\`（次の文字をそのまま）を使える。
+// `/` を含まないパターンは、どの深さでもファイル名と比べる（`*.png`、`node_modules`）。
+
+/// 相対パス（`/` 区切り）と比べるグロブのパターン
+#[derive(Debug, Clone)]
+pub struct Glob {
+  pattern: Vec<char>,
+  // `/` を含まないパターンはファイル名と比べる
+  name_only: bool,
+}
+
+impl Glob {
+  /// パターンを解析する。文字クラスが閉じていない場合はエラー
+  pub fn new(pattern: &str) -> Result<Self, String> {
+    let chars: Vec<char> = pattern.chars().collect();
+    let mut i = 0;
+    while i < chars.len() {
+      match chars[i] {
+        '\\' => i += 2,
+        '[' => {
+          let end = class_end(&chars, i)
+            .ok_or_else(|| format!("Unclosed '[' in glob pattern: {}", pattern))?;
+          i = end + 1;
+        }
+        _ => i += 1,
+      }
+    }
+
+    // 先頭の `/` はルートからのパスを表す
+    let anchored = pattern.strip_prefix('/');
+    let pattern = anchored.unwrap_or(pattern);
+    Ok(Self {
+      name_only: anchored.is_none() && !pattern.contains('/'),
+      pattern: pattern.chars().collect(),
+    })
+  }
+
+  /// `relative_path`（`/` 区切り）と一致するかどうか
+  pub fn matches(&self, relative_path: &str) -> bool {
+    let target = if self.name_only {
+      relative_path.rsplit('/').next().unwrap_or(relative_path)
+    } else {
+      relative_path
+    };
+    let target: Vec<char> = target.chars().collect();
+    Matcher::new(&self.pattern, &target).match_from(0, 0)
+  }
+}
+
+// パターンの位置とテキストの位置の組ごとに結果を覚えておき、`*a*a*a*b` のような
+// パターンでも同じ組を繰り返し調べない（パターンの長さ × テキストの長さの 2 乗で収まる）
+struct Matcher<'a> {
+  pattern: &'a [char],
+  text: &'a [char],
+  memo: Vec<Option<bool>>,
+}
+
+impl<'a> Matcher<'a> {
+  fn new(pattern: &'a [char], text: &'a [char]) -> Self {
+    Self {
+      pattern,
+      text,
+      memo: vec![None; (pattern.len() + 1) * (text.len() + 1)],
+    }
+  }
+
+  // `pattern[p..]` と `text[t..]` が一致するかどうか
+  fn match_from(&mut self, p: usize, t: usize) -> bool {
+    let key = p * (self.text.len() + 1) + t;
+    if let Some(matched) = self.memo[key] {
+      return matched;
+    }
+    let matched = self.match_uncached(p, t);
+    self.memo[key] = Some(matched);
+    matched
+  }
+
+  fn match_uncached(&mut self, p: usize, t: usize) -> bool {
+    let (pattern, text) = (self.pattern, self.text);
+    match pattern.get(p) {
+      None => t == text.len(),
+      Some('*') if pattern.get(p + 1) == Some(&'*') => {
+        match pattern.get(p + 2) {
+          // `**/` は 0 個以上のディレクトリ
+          Some('/') => {
+            self.match_from(p + 3, t)
+              || (t..text.len()).any(|i| text[i] == '/' && self.match_from(p + 3, i + 1))
+          }
+          _ => (t..=text.len()).any(|i| self.match_from(p + 2, i)),
+        }
+      }
+      Some('*') => {
+        for i in t..=text.len() {
+          if self.match_from(p + 1, i) {
+            return true;
+          }
+          if i < text.len() && text[i] == '/' {
+            return false;
+          }
+        }
+        false
+      }
+      Some('?') => text.get(t).is_some_and(|c| *c != '/') && self.match_from(p + 1, t + 1),
+      Some('[') => {
+        let Some(end) = class_end(pattern, p) else {
+          return false;
+        };
+        text
+          .get(t)
+          .is_some_and(|c| *c != '/' && class_matches(&pattern[p + 1..end], *c))
+          && self.match_from(end + 1, t + 1)
+      }
+      Some('\\') if p + 1 < pattern.len() => {
+        text.get(t) == Some(&pattern[p + 1]) && self.match_from(p + 2, t + 1)
+      }
+      Some(c) => text.get(t) == Some(c) && self.match_from(p + 1, t + 1),
+    }
+  }
+}
+
+// `pattern[start]` の `[` に対応する `]` の位置（先頭の `]` は文字として扱う）
+fn class_end(pattern: &[char], start: usize) -> Option<usize> {
+  let mut i = start + 1;
+  if matches!(pattern.get(i), Some('!') | Some('^')) {
+    i += 1;
+  }
+  if pattern.get(i) == Some(&']') {
+    i += 1;
+  }
+  (i..pattern.len()).find(|&j| pattern[j] == ']')
+}
+
+// 文字クラスの中身（`[` と `]` の間）に `c` が含まれるかどうか
+fn class_matches(class: &[char], c: char) -> bool {
+  let (negated, class) = match class.first() {
+    Some('!') | Some('^') => (true, &class[1..]),
+    _ => (false, class),
+  };
+  let mut found = false;
+  let mut i = 0;
+  while i < class.len() {
+    if i + 2 < class.len() && class[i + 1] == '-' {
+      found |= class[i] <= c && c <= class[i + 2];
+      i += 3;
+    } else {
+      found |= class[i] == c;
+      i += 1;
+    }
+  }
+  found != negated
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // パターンと相対パスの一致
+  #[test]
+  fn test_matches() {
+    let cases = [
+      // ファイル名と比べる
+      ("*.png", "a.png", true),
+      ("*.png", "photos/2024/a.png", true),
+      ("*.png", "a.png.txt", false),
+      ("node_modules", "app/node_modules", true),
+      ("?.txt", "a.txt", true),
+      ("?.txt", "ab.txt", false),
+      // 相対パスと比べる
+      ("src/*.rs", "src/main.rs", true),
+      ("src/*.rs", "src/bin/main.rs", false),
+      ("src/**/*.rs", "src/main.rs", true),
+      ("src/**/*.rs", "src/bin/x/main.rs", true),
+      ("**/.git", ".git", true),
+      ("**/.git", "a/b/.git", true),
+      ("build/**", "build/out/a.o", true),
+      ("build/**", "src/build/a.o", false),
+      ("/a.txt", "a.txt", true),
+      ("/a.txt", "dir/a.txt", false),
+      // 文字クラスとエスケープ
+      ("[ab].txt", "b.txt", true),
+      ("[ab].txt", "c.txt", false),
+      ("[!ab].txt", "c.txt", true),
+      ("[a-c]1", "b1", true),
+      ("[]]", "]", true),
+      ("\\*.txt", "*.txt", true),
+      ("\\*.txt", "a.txt", false),
+      // 日本語のファイル名
+      ("*.md", "メモ/議事録.md", true),
+    ];
+    for (pattern, path, expected) in cases {
+      assert_eq!(
+        Glob::new(pattern).unwrap().matches(path),
+        expected,
+        "{} ~ {}",
+        pattern,
+        path
+      );
+    }
+  }
+
+  // 一致しない `*` が多いパターンでも長い名前を短い時間で調べられる
+  #[test]
+  fn test_pathological_pattern() {
+    let name = "a".repeat(200);
+    let started = std::time::Instant::now();
+    assert!(!Glob::new("*a*a*a*a*a*a*a*a*a*a*b").unwrap().matches(&name));
+    assert!(!Glob::new("**a**a**a**a**a**a**b")
+      .unwrap()
+      .matches(&format!("{}/{}", name, name)));
+    assert!(Glob::new("*a*a*a*a*a*a*a*a*a*a").unwrap().matches(&name));
+    assert!(started.elapsed() < std::time::Duration::from_secs(10));
+  }
+
+  // 閉じていない文字クラスはエラー
+  #[test]
+  fn test_invalid_pattern() {
+    assert!(Glob::new("[abc").is_err());
+    assert!(Glob::new("a\\[b").is_ok());
+  }
+}
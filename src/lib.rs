@@ -11,6 +11,24 @@ mod platforms;
 #[cfg(not(target_os = "macos"))]
 mod platforms;
 
+// クリップボード環境の診断
+mod doctor;
+
+// 複数形式の一貫した読み取り
+mod snapshot;
+
+// テキストからのファイルパス抽出
+mod text_paths;
+
+// 読み取ったファイルのメタデータの取得
+mod file_metadata;
+
+// include / exclude のグロブのパターン
+mod glob;
+
+// コピーしたフォルダーの中のファイルの展開
+mod path_expansion;
+
 #[cfg(target_os = "windows")]
 use platforms::windows as current_platform;
 
@@ -22,15 +40,150 @@ use platforms::linux as current_platform;
 
 // napi エラー型エイリアス
 type NapiError = napi::Error;
-use napi::Status; // Import Status
+use napi::bindgen_prelude::{AsyncTask, Buffer};
+use napi::threadsafe_function::{
+  ErrorStrategy, ThreadSafeCallContext, ThreadsafeFunction, ThreadsafeFunctionCallMode,
+};
+use napi::{Env, JsBuffer, JsFunction, JsObject, JsString, JsUnknown, Ref, Status, ValueType}; // Import Status
 use std::fs;
 use std::io::{Error as IoError, ErrorKind}; // ... existing code ...
+use std::mem::ManuallyDrop;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+use std::time::Duration;
 
 // OS固有のエラーをNapiエラーに変換するヘルパー関数
 #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
-fn platform_error_to_napi(e: std::io::Error) -> NapiError {
+fn platform_error_to_napi(e: std::io::Error) -> ClipboardError {
   let reason = format!("{} clipboard error: {}", std::env::consts::OS, e);
-  NapiError::new(Status::GenericFailure, reason) // Use Status::GenericFailure
+  // タイムアウトは JavaScript 側で区別できるよう、code を ETIMEDOUT にする
+  let status = if e.kind() == ErrorKind::TimedOut {
+    ErrorCode::TimedOut
+  } else {
+    ErrorCode::Status(Status::GenericFailure)
+  };
+  ClipboardError { status, reason }
+}
+
+/// JavaScript に投げるエラーの `code`
+///
+/// napi の `Status` に加えて、タイムアウトを `"ETIMEDOUT"` で区別する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+  /// napi の `Status` (`"InvalidArg"`, `"GenericFailure"` など)
+  Status(Status),
+  /// 操作がクリップボードのタイムアウトを超えた (`"ETIMEDOUT"`)
+  TimedOut,
+}
+
+impl AsRef<str> for ErrorCode {
+  fn as_ref(&self) -> &str {
+    match self {
+      ErrorCode::Status(status) => status.as_ref(),
+      ErrorCode::TimedOut => "ETIMEDOUT",
+    }
+  }
+}
+
+impl PartialEq<Status> for ErrorCode {
+  fn eq(&self, other: &Status) -> bool {
+    *self == ErrorCode::Status(*other)
+  }
+}
+
+/// クリップボード操作のエラー
+///
+/// `code` に `ErrorCode` を使う以外は napi のエラーと同じ。
+#[derive(Debug)]
+pub struct ClipboardError {
+  pub status: ErrorCode,
+  pub reason: String,
+}
+
+impl From<NapiError> for ClipboardError {
+  fn from(e: NapiError) -> Self {
+    Self {
+      status: ErrorCode::Status(e.status),
+      reason: e.reason,
+    }
+  }
+}
+
+impl From<ClipboardError> for napi::JsError<ErrorCode> {
+  fn from(e: ClipboardError) -> Self {
+    napi::Error::new(e.status, e.reason).into()
+  }
+}
+
+impl ClipboardError {
+  // code を保ったまま JavaScript のエラーにする（AsyncTask の reject などで使う）
+  fn into_js_error(self, env: Env) -> NapiError {
+    NapiError::from(napi::JsError::from(self).into_unknown(env))
+  }
+}
+
+/// クリップボードから読み取った 1 つのファイル
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct ClipboardFileEntry {
+  /// URI (`file:///...`, `smb://...`, `sftp://...`, `trash:///...` など)
+  pub uri: String,
+  /// ローカルで開けるパス。GVFS の URI は `$XDG_RUNTIME_DIR/gvfs` の下の FUSE のパス。
+  /// 分からない場合はnull。
+  pub path: Option<String>,
+  /// URI のスキーム (`"file"`, `"smb"`, `"sftp"` など)
+  pub scheme: String,
+  /// このマシンのファイル (`file://`) かどうか。GVFS の FUSE のパスでは false。
+  pub is_local: bool,
+  /// `withMetadata` を指定した場合の `path` のメタデータ。`path` がない場合はnull。
+  pub metadata: Option<ClipboardFileMetadata>,
+}
+
+impl From<platforms::file_entry::FileEntry> for ClipboardFileEntry {
+  fn from(entry: platforms::file_entry::FileEntry) -> Self {
+    Self {
+      uri: entry.uri,
+      path: entry.path,
+      scheme: entry.scheme,
+      is_local: entry.is_local,
+      metadata: None,
+    }
+  }
+}
+
+/// ファイルのメタデータ
+///
+/// `kind` はシンボリックリンク自体、それ以外はリンク先の値。
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct ClipboardFileMetadata {
+  /// パス（シンボリックリンクの場合はリンク先）が存在するかどうか
+  pub exists: bool,
+  /// 種類 (`"file"`, `"dir"`, `"symlink"`, `"other"`)。存在しない場合はnull。
+  pub kind: Option<String>,
+  /// サイズ（バイト）
+  pub size: Option<i64>,
+  /// 更新日時（UNIX エポックからのミリ秒。`new Date(mtimeMs)` で使える）
+  pub mtime_ms: Option<f64>,
+  /// パーミッションのビット（`0o644` など）。Windows ではnull。
+  pub mode: Option<u32>,
+  /// 読み取り専用かどうか
+  pub readonly: Option<bool>,
+}
+
+impl From<file_metadata::FileMetadata> for ClipboardFileMetadata {
+  fn from(metadata: file_metadata::FileMetadata) -> Self {
+    Self {
+      exists: metadata.exists,
+      kind: metadata.kind.map(|kind| kind.as_str().to_string()),
+      size: metadata.size.map(|size| size as i64),
+      mtime_ms: metadata.modified_ms,
+      mode: metadata.mode,
+      readonly: metadata.readonly,
+    }
+  }
 }
 
 /// クリップボードから読み取ったデータを保持する構造体
@@ -40,18 +193,265 @@ fn platform_error_to_napi(e: std::io::Error) -> NapiError {
 pub struct ClipboardContent {
   /// ファイルパスのリスト。読み取りに失敗した場合は空の配列。
   pub file_paths: Vec<String>,
+  /// ファイルの URI とパスのリスト。パスにできない URI (`trash://` など) も含む。
+  /// 読み取りに失敗した場合は空の配列。
+  pub entries: Vec<ClipboardFileEntry>,
   /// テキスト内容。読み取りに失敗した場合はnull。
   pub text: Option<String>,
+  /// `filePaths` の取得元。ファイル形式から読み取った場合は `"clipboard"`、
+  /// テキストから抽出した場合は `"text"`。パスがない場合はnull。
+  pub source: Option<String>,
+}
+
+/// `read_clipboard_text` で読み取ったテキストと、その形式と文字コード
+#[napi(object)]
+pub struct ClipboardText {
+  /// テキスト内容
+  pub text: String,
+  /// 読み取った形式（X11 のターゲット、ペーストボードタイプ、または `"CF_UNICODETEXT"`）
+  pub source_format: String,
+  /// デコードに使った文字コード（`"UTF-8"`, `"ISO-8859-1"`, `"COMPOUND_TEXT"`, `"UTF-16LE"`）
+  pub charset: String,
+  /// 変換できないバイトを U+FFFD に置き換えたかどうか
+  pub was_lossy: bool,
+}
+
+/// クリップボードがロックされている場合などの再試行のポリシー
+///
+/// 省略した項目は `set_clipboard_retry_policy` で設定した全体のポリシーの値になる。
+#[derive(Debug, Default, Clone)]
+#[napi(object)]
+pub struct RetryOptions {
+  /// 最初の試行を含めた最大の試行回数。1 なら再試行しない。デフォルトは 5。
+  pub attempts: Option<u32>,
+  /// 最初の再試行までの待ち時間（ミリ秒）。再試行のたびに 2 倍になる。デフォルトは 10。
+  pub backoff_ms: Option<u32>,
+  /// 最初の試行からの合計の制限時間（ミリ秒）。デフォルトは 1000。
+  pub deadline_ms: Option<u32>,
+}
+
+/// `read_clipboard_file_paths` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct ReadClipboardOptions {
+  /// ファイル形式のパスがない場合に、テキストからファイルパスを抽出するかどうか。
+  /// 存在するパスだけが `source: "text"` として返される。デフォルトは false。
+  pub parse_text_as_paths: Option<bool>,
+  /// 各エントリにパスのメタデータ（サイズ、更新日時、種類、パーミッション、存在するか）を
+  /// 含めるかどうか。デフォルトは false。
+  pub with_metadata: Option<bool>,
+  /// この呼び出しの再試行のポリシー
+  pub retry: Option<RetryOptions>,
+}
+
+/// `expand_clipboard_paths` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct ExpandClipboardPathsOptions {
+  /// サブフォルダーの中もたどるかどうか。false ならコピーしたフォルダーの直下だけ。デフォルトは true。
+  pub recursive: Option<bool>,
+  /// シンボリックリンクをたどるかどうか。false ならリンク自体を返す。デフォルトは false。
+  pub follow_symlinks: Option<bool>,
+  /// 返すエントリの最大数。達した場合は `truncated: true` で止める。デフォルトは 10000。
+  pub max_entries: Option<u32>,
+  /// 含めるファイルのグロブ (`"*.png"`, `"docs/**"`)。省略時はすべてのファイル。
+  pub include: Option<Vec<String>>,
+  /// 除くファイルとフォルダーのグロブ (`"node_modules"`, `".git"`)
+  pub exclude: Option<Vec<String>>,
+  /// クリップボードの読み取りの再試行のポリシー
+  pub retry: Option<RetryOptions>,
+}
+
+/// `expand_clipboard_paths` で展開したファイル
+#[napi(object)]
+pub struct ExpandedClipboardPath {
+  /// パス
+  pub path: String,
+  /// このファイルを含む、コピーされたパス
+  pub root: String,
+  /// `root` からの相対パス (`/` 区切り)。コピーされたのがファイルの場合はファイル名。
+  pub relative_path: String,
+  /// 種類 (`"file"`, `"symlink"`, `"other"`)
+  pub kind: String,
+  /// サイズ（バイト）
+  pub size: i64,
+}
+
+/// `expand_clipboard_paths` の結果
+#[napi(object)]
+pub struct ExpandedClipboardPaths {
+  /// 展開したファイル（フォルダーは含まない）
+  pub entries: Vec<ExpandedClipboardPath>,
+  /// `maxEntries` に達して途中で止めたかどうか
+  pub truncated: bool,
+  /// 読み取れなかったフォルダーなどのエラー
+  pub errors: Vec<String>,
+}
+
+impl From<path_expansion::Expansion> for ExpandedClipboardPaths {
+  fn from(expansion: path_expansion::Expansion) -> Self {
+    Self {
+      entries: expansion
+        .entries
+        .into_iter()
+        .map(|entry| ExpandedClipboardPath {
+          path: entry.path,
+          root: entry.root,
+          relative_path: entry.relative_path,
+          kind: entry.kind.as_str().to_string(),
+          size: entry.size as i64,
+        })
+        .collect(),
+      truncated: expansion.truncated,
+      errors: expansion.errors,
+    }
+  }
+}
+
+/// `snapshot_clipboard` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct SnapshotClipboardOptions {
+  /// 読み取る形式 (`"filePaths"`, `"text"`, `"raw"`)。省略時はすべての形式。
+  pub formats: Option<Vec<String>>,
+  /// 読み取り中にクリップボードの所有者が変わった場合に読み直す最大回数。デフォルトは 3。
+  pub max_attempts: Option<u32>,
+  /// この呼び出しの再試行のポリシー（ロックされている場合など、各形式の読み取りに使う）
+  pub retry: Option<RetryOptions>,
+}
+
+/// 1回の所有期間内に読み取ったクリップボードの内容
+#[napi(object)]
+pub struct ClipboardSnapshot {
+  /// ファイルパスのリスト。要求されていない、または読み取れなかった場合はnull。
+  pub file_paths: Option<Vec<String>>,
+  /// テキスト内容。要求されていない、または読み取れなかった場合はnull。
+  pub text: Option<String>,
+  /// 生のデータ。要求されていない、または読み取れなかった場合はnull。
+  pub raw: Option<Buffer>,
+  /// 読み取りを行った回数
+  pub attempts: u32,
+  /// 読み取りの前後で所有者が変わっていないことを確認できたかどうか。
+  /// 所有期間を取得できない環境 (X11 のない Wayland など) では false。
+  pub verified: bool,
+}
+
+/// `get_clipboard_change_count` / `has_clipboard_changed_since` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct ClipboardChangeOptions {
+  /// 対象のセレクション (`"clipboard"`, `"primary"`)。デフォルトは `"clipboard"`。
+  /// `"primary"` は Linux (X11) でのみ利用できる。
+  pub selection: Option<String>,
+}
+
+/// パスの先頭部分の対応（`from` で始まるパスを `to` で始まるパスにする）
+#[derive(Debug, Clone)]
+#[napi(object)]
+pub struct PathMappingOptions {
+  /// 置き換える先頭部分（`"/mnt/server"`、`"C:\\"` など）
+  pub from: String,
+  /// 置き換えた後の先頭部分。パスのほか `"sftp://user@host/srv"` のような URI も書ける。
+  pub to: String,
+}
+
+/// `write_clipboard_file_paths` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct WriteClipboardOptions {
+  /// 書き込み元を示す印 (`org.nspasteboard.source`) の内容。macOS の慣習ではバンドル ID。
+  /// `getClipboardOwnerInfo()` の `source` として読み取れる。
+  pub source_marker: Option<String>,
+  /// パスと一緒に書き込むテキストの形式
+  /// (`"none"`, `"paths"`, `"uris"`, `"shell-quoted"`, `"custom"`)。
+  /// 省略時は各プラットフォームの従来の内容（Windows は file URI、Linux はパス）。
+  pub text_representation: Option<String>,
+  /// `textRepresentation: "custom"` のテンプレート。パスごとに `{path}`, `{uri}`, `{name}`,
+  /// `{quoted}` を置き換え、改行区切りで並べる。
+  pub custom_template: Option<String>,
+  /// テキストのパスをこのディレクトリからの相対パスで表す（URI は絶対パスのまま）。
+  /// `textRepresentation` を省略した場合は `"paths"` になる。
+  pub relative_to: Option<String>,
+  /// テキストと URI のリストに書くパスを変換する対応表。最も長く一致するものを使う。
+  pub path_mappings: Option<Vec<PathMappingOptions>>,
+  /// WSL のディストリビューション名（`process.env.WSL_DISTRO_NAME` など）。
+  /// 指定すると `/mnt/c/...` を `C:\...`、それ以外を `\\wsl$\<distro>\...` にする対応を
+  /// `pathMappings` の後に追加する。
+  pub wsl_distro: Option<String>,
+  /// この呼び出しの再試行のポリシー
+  pub retry: Option<RetryOptions>,
+}
+
+/// `write_clipboard_text` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct WriteClipboardTextOptions {
+  /// 改行コード (`"lf"`, `"crlf"`, `"native"`)。省略時はテキストの改行をそのまま書き込む。
+  /// `"native"` は Windows では `"crlf"`、それ以外では `"lf"`。
+  pub line_endings: Option<String>,
+  /// テキスト中の存在するファイルパスを URI のリストとしても書き込むかどうか。デフォルトは false。
+  pub also_as_uri_list: Option<bool>,
+  /// この呼び出しの再試行のポリシー
+  pub retry: Option<RetryOptions>,
+}
+
+/// クリップボードの所有者の情報
+#[napi(object)]
+pub struct ClipboardOwnerInfo {
+  /// 所有者のウィンドウ（X11 のウィンドウ ID、Windows の HWND）。macOS では常にnull。
+  pub window: Option<i64>,
+  /// 所有者のプロセス ID。取得できない場合はnull。
+  pub pid: Option<u32>,
+  /// 所有者の実行ファイルのパス。取得できない場合はnull。
+  pub executable: Option<String>,
+  /// 書き込み元が付けた `org.nspasteboard.source` の印。ない場合はnull。
+  pub source: Option<String>,
+}
+
+/// `clear_clipboard` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct ClearClipboardOptions {
+  /// 対象のセレクション (`"clipboard"`, `"primary"`)。デフォルトは `"clipboard"`。
+  /// `"primary"` は Linux (X11) でのみ利用できる。
+  pub selection: Option<String>,
+  /// このプロセスが最後に書き込んだ内容がまだ残っている場合だけ空にするかどうか。
+  /// デフォルトは false。
+  pub only_if_owner: Option<bool>,
+  /// この呼び出しの再試行のポリシー
+  pub retry: Option<RetryOptions>,
+}
+
+/// `write_clipboard_lazy` のオプション
+#[napi(object, object_to_js = false)]
+pub struct LazyClipboardOptions {
+  /// 提供するフォーマット名（Linux は MIME タイプ、macOS はペーストボードタイプ、
+  /// Windows は登録済みのフォーマット名または数値の ID）
+  pub formats: Vec<String>,
+  /// 貼り付け先が要求したフォーマットのデータを返す関数。
+  /// `null` / `undefined` を返すとそのフォーマットは提供されない。
+  #[napi(ts_type = "(format: string) => Buffer | string | null | undefined")]
+  pub provider: JsFunction,
+}
+
+/// `start_file_drag` の結果
+#[napi(object)]
+pub struct FileDragResult {
+  /// `"dropped"`（ドロップされた）、`"rejected"`（ドロップ先がない、または受け付けられなかった）、
+  /// `"cancelled"`（Escape キーなどで中止された）のいずれか
+  pub outcome: String,
+  /// ドロップ先が実行した操作 (`"copy"`, `"move"`, `"link"` など)。ドロップされなかった場合は null
+  pub action: Option<String>,
 }
 
 /// クリップボードの読み取り結果を保持する構造体 (Rust内部用)
 /// 各フィールドは読み取り操作の成功/失敗を示す Result 型
 #[derive(Debug)]
 pub struct ClipboardReadResult {
-  /// ファイルパス読み取りの結果。成功時は`Vec<String>`、失敗時は`napi::Error`。
-  pub file_paths: Result<Vec<String>, NapiError>,
+  /// ファイル読み取りの結果。成功時は URI とパスの組の`Vec`、失敗時は`napi::Error`。
+  pub file_entries: Result<Vec<platforms::file_entry::FileEntry>, ClipboardError>,
   /// テキスト読み取りの結果。成功時は`Option<String>`、失敗時は`napi::Error`。
-  pub text: Result<Option<String>, NapiError>,
+  pub text: Result<Option<String>, ClipboardError>,
 }
 
 /// クリップボードのバイナリデータを読みやすい形式で表示するための構造体
@@ -69,6 +469,196 @@ pub struct ReadableClipboardContent {
   pub preview: Option<String>,
 }
 
+/// `clipboard_doctor` のオプション
+#[derive(Debug, Default)]
+#[napi(object)]
+pub struct ClipboardDoctorOptions {
+  /// クリップボードへの書き込み・読み取りの自己テストを行うかどうか。
+  /// クリップボードの内容が書き換わる。デフォルトは true。
+  pub round_trip: Option<bool>,
+}
+
+/// クリップボード環境の診断項目
+#[napi(object)]
+pub struct ClipboardDoctorCheck {
+  /// 項目名 (`display`, `xConnection`, `xfixes`, `clipboardManager`, `helperTools`, `roundTrip`)
+  pub name: String,
+  /// `"ok"`, `"warning"`, `"error"`, `"skipped"` のいずれか
+  pub status: String,
+  /// 結果の詳細
+  pub message: String,
+}
+
+/// クリップボードのヘルパーコマンドの検出結果
+#[napi(object)]
+pub struct ClipboardDoctorTool {
+  /// コマンド名
+  pub name: String,
+  /// 見つかった実行ファイルのパス。見つからない場合はnull。
+  pub path: Option<String>,
+  /// バージョン表示の1行目。取得できない場合はnull。
+  pub version: Option<String>,
+}
+
+/// クリップボード環境の診断結果
+#[napi(object)]
+pub struct ClipboardDoctorReport {
+  /// OS名 (`linux`, `macos`, `windows`)
+  pub platform: String,
+  /// `DISPLAY` 環境変数の値
+  pub display: Option<String>,
+  /// `WAYLAND_DISPLAY` 環境変数の値
+  pub wayland_display: Option<String>,
+  /// エラーとなった項目がなければ true
+  pub ok: bool,
+  /// 各診断項目の結果
+  pub checks: Vec<ClipboardDoctorCheck>,
+  /// ヘルパーコマンドの検出結果 (Linuxのみ)
+  pub tools: Vec<ClipboardDoctorTool>,
+}
+
+impl From<doctor::DoctorReport> for ClipboardDoctorReport {
+  fn from(report: doctor::DoctorReport) -> Self {
+    Self {
+      ok: report.ok(),
+      platform: report.platform,
+      display: report.display,
+      wayland_display: report.wayland_display,
+      checks: report
+        .checks
+        .into_iter()
+        .map(|c| ClipboardDoctorCheck {
+          name: c.name,
+          status: c.status.as_str().to_string(),
+          message: c.message,
+        })
+        .collect(),
+      tools: report
+        .tools
+        .into_iter()
+        .map(|t| ClipboardDoctorTool {
+          name: t.name,
+          path: t.path,
+          version: t.version,
+        })
+        .collect(),
+    }
+  }
+}
+
+/// クリップボードに書き込んだ内容の所有状態
+///
+/// 書き込み API の戻り値。別のアプリがクリップボードを書き換えると所有を失う。
+#[napi]
+#[derive(Debug)]
+pub struct ClipboardOwnership {
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  ownership: Arc<current_platform::Ownership>,
+  // onLost の監視を止めるためのフラグ
+  stop: Arc<AtomicBool>,
+}
+
+// セレクションごとに、このプロセスが最後に書き込んだ内容の所有状態
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+type LastWrite = (platforms::Selection, Arc<current_platform::Ownership>);
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+static LAST_WRITES: Mutex<Vec<LastWrite>> = Mutex::new(Vec::new());
+
+// clearClipboard({ onlyIfOwner }) で使うため、最後の書き込みとして記録する
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+fn remember_last_write(
+  selection: platforms::Selection,
+  ownership: &Arc<current_platform::Ownership>,
+) {
+  let mut last_writes = LAST_WRITES.lock().unwrap_or_else(|e| e.into_inner());
+  last_writes.retain(|(s, _)| *s != selection);
+  last_writes.push((selection, Arc::clone(ownership)));
+}
+
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+impl ClipboardOwnership {
+  fn new(selection: platforms::Selection, ownership: current_platform::Ownership) -> Self {
+    let ownership = Arc::new(ownership);
+    remember_last_write(selection, &ownership);
+    Self {
+      ownership,
+      stop: Arc::new(AtomicBool::new(false)),
+    }
+  }
+}
+
+#[napi]
+impl ClipboardOwnership {
+  /// Returns whether the content written by this call is still on the clipboard.
+  ///
+  /// Uses the X11 selection owner or the `wl-copy` process on Linux, and the clipboard
+  /// sequence number (`GetClipboardSequenceNumber` / `changeCount`) on Windows and macOS.
+  #[napi]
+  pub fn is_owner(&self) -> Result<bool, ClipboardError> {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    {
+      self.ownership.is_owner().map_err(platform_error_to_napi)
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    {
+      Err(NapiError::from_reason("Unsupported operating system").into())
+    }
+  }
+
+  /// Calls `callback` once when another application replaces the clipboard contents.
+  ///
+  /// The watcher runs on a background thread and does not keep the Node.js process alive.
+  /// It keeps running after this handle is garbage-collected, so
+  /// `writeClipboardFilePaths(paths).onLost(callback)` works without keeping the handle.
+  /// Call `dispose()` to stop watching.
+  ///
+  /// Throws if the ownership cannot be tracked (e.g. on Linux without an X server or
+  /// `wl-copy`). If watching fails later, `callback` is called once with the error.
+  #[napi(ts_args_type = "callback: (error?: Error) => void")]
+  pub fn on_lost(&self, env: Env, callback: JsFunction) -> Result<(), ClipboardError> {
+    // 追跡できない場合は監視を始めずにエラーを返す
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    self.ownership.is_owner().map_err(platform_error_to_napi)?;
+
+    let mut lost: ThreadsafeFunction<Option<ClipboardError>, ErrorStrategy::Fatal> = callback
+      .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<Option<ClipboardError>>| {
+        Ok(match ctx.value {
+          Some(error) => vec![napi::JsError::from(error).into_unknown(ctx.env)],
+          None => Vec::new(),
+        })
+      })?;
+    lost.unref(&env)?;
+
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    {
+      let ownership = Arc::clone(&self.ownership);
+      let stop = Arc::clone(&self.stop);
+      std::thread::spawn(move || match ownership.wait_until_lost(&stop) {
+        Ok(true) => {
+          lost.call(None, ThreadsafeFunctionCallMode::NonBlocking);
+        }
+        Ok(false) => {}
+        Err(e) => {
+          lost.call(
+            Some(platform_error_to_napi(e)),
+            ThreadsafeFunctionCallMode::NonBlocking,
+          );
+        }
+      });
+    }
+
+    Ok(())
+  }
+
+  /// Stops all `onLost` watchers of this handle.
+  #[napi]
+  pub fn dispose(&self) {
+    self.stop.store(true, Ordering::SeqCst);
+  }
+}
+
 /// Hello World関数 - 動作確認用
 #[napi]
 pub fn hello_world() -> String {
@@ -91,17 +681,53 @@ pub fn hello_world() -> String {
 /// # Arguments
 /// * `paths` - A list of absolute or relative file paths to copy.
 ///   - The paths will be registered to the clipboard in the appropriate format for each OS.
-///   - Passing an empty list will result in an error.
+///   - Passing an empty list clears the clipboard on every platform, exactly like
+///     `clearClipboard()` (no source marker is written).
+/// * `options` - Optional write options.
+///   - `sourceMarker`: Also write an `org.nspasteboard.source` marker (conventionally the
+///     bundle id of the writing application) that `getClipboardOwnerInfo()` reports as `source`.
+///     On Linux the marker needs an X server; with only Wayland the call fails.
+///   - `textRepresentation`: The text written next to the file list, the same on every OS:
+///     `"none"`, `"paths"` (one per line), `"uris"` (`file://` URIs, one per line),
+///     `"shell-quoted"` (quoted for the platform shell, space-separated) or `"custom"`
+///     (`customTemplate` expanded per path with `{path}`, `{uri}`, `{name}` and `{quoted}`).
+///     Defaults to `file://` URIs on Windows and plain paths on Linux. On Linux a text
+///     representation needs an X server, like the source marker (otherwise the call fails).
+///   - `relativeTo`: Show the paths in the text relative to this directory (e.g. for
+///     "Copy Relative Path"), using `..` where needed. The file list and `file://` URIs stay
+///     absolute, and a path that cannot be made relative (another drive or UNC share) stays
+///     absolute. Implies `textRepresentation: "paths"` when that is omitted.
+///   - `pathMappings`: `{ from, to }` prefixes that translate the paths in the text and in
+///     the `text/uri-list` (e.g. a sshfs mount to `sftp://user@host/dir`). The longest
+///     matching prefix wins, after `.` and `..` are resolved; unmatched paths are unchanged.
+///   - `wslDistro`: Appends the WSL mappings for this distribution after `pathMappings`, so
+///     `/mnt/c/...` becomes `C:\...` and other paths `\\wsl$\<distro>\...`.
 ///
 /// # Returns
-/// * Returns `Ok(())` if the operation succeeds.
-/// * Returns `Err(napi::Error)` if an error occurs.
+/// * Returns `Ok(ClipboardOwnership)` if the operation succeeds. Use it to learn when another
+///   application replaces the clipboard contents (`isOwner()`, `onLost(callback)`).
+/// * Returns `Err(napi::Error)` for an unknown `textRepresentation` (or `"custom"` without
+///   `customTemplate`), a `relativeTo` directory that does not exist, or if an error occurs.
 ///
 /// # Note
 /// * This function will actually change the contents of the system clipboard.
 /// * Please be careful when running tests.
 #[napi]
-pub fn write_clipboard_file_paths(paths: Vec<String>) -> Result<(), NapiError> {
+pub fn write_clipboard_file_paths(
+  paths: Vec<String>,
+  options: Option<WriteClipboardOptions>,
+) -> Result<ClipboardOwnership, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let source_marker = options.source_marker;
+  let text = parse_path_text(
+    options.text_representation,
+    options.custom_template,
+    options.relative_to,
+    options.path_mappings,
+    options.wsl_distro,
+  )?;
+  let retry = retry_policy(options.retry);
+
   #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
   {
     // === 追加: 事前バリデーションで全プラットフォーム共通メッセージを生成 ===
@@ -124,23 +750,184 @@ pub fn write_clipboard_file_paths(paths: Vec<String>) -> Result<(), NapiError> {
       return Err(platform_error_to_napi(io_err));
     }
 
+    // 空の一覧はすべてのプラットフォームでクリップボードを空にする
     // パスが有効であれば OS 依存の実装に委譲
-    current_platform::write_clipboard_file_paths(&paths).map_err(platform_error_to_napi)?;
+    let ownership = platforms::retry::with_policy(retry, || {
+      if paths.is_empty() {
+        current_platform::clear_clipboard(platforms::Selection::Clipboard)
+      } else {
+        current_platform::track_ownership(platforms::Selection::Clipboard, || {
+          current_platform::write_clipboard_file_paths(
+            &paths,
+            source_marker.as_deref(),
+            text.as_ref(),
+          )
+        })
+      }
+    })
+    .map_err(platform_error_to_napi)?;
     println!("write_clipboard_file_paths: {:?}", &paths);
+    Ok(ClipboardOwnership::new(
+      platforms::Selection::Clipboard,
+      ownership,
+    ))
   }
 
   #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
   {
-    return Err(NapiError::from_reason("Unsupported operating system"));
+    let _ = (paths, source_marker, text, retry);
+    Err(NapiError::from_reason("Unsupported operating system").into())
   }
+}
 
-  Ok(())
+/// Writes plain text to the OS clipboard.
+///
+/// # Arguments
+/// * `text` - The text to write.
+/// * `options` - Optional write options.
+///   - `lineEndings`: Convert every line break (`\n`, `\r\n` or `\r`) to `"lf"`, `"crlf"` or
+///     `"native"` (CRLF on Windows, LF elsewhere). By default the text is written unchanged.
+///   - `alsoAsUriList`: Also publish the existing paths found in the text (recognized like
+///     `parseTextAsPaths`) as a list of `file://` URIs, so file managers can paste them.
+///
+/// # Returns
+/// * Returns `Ok(ClipboardOwnership)` if the operation succeeds.
+/// * Returns `Err(napi::Error)` for an unknown `lineEndings` value or if the text cannot be written.
+///   On Linux `alsoAsUriList` with paths fails without an X server instead of writing the text
+///   alone.
+///
+/// # Note
+/// * Linux publishes `UTF8_STRING`, `text/plain;charset=utf-8` and `text/plain` (plus
+///   `text/uri-list` with `alsoAsUriList`, which needs an X server), Windows publishes
+///   `CF_UNICODETEXT` (plus a registered `text/uri-list` format) and macOS publishes
+///   `public.utf8-plain-text` (plus `public.file-url` items).
+#[napi]
+pub fn write_clipboard_text(
+  text: String,
+  options: Option<WriteClipboardTextOptions>,
+) -> Result<ClipboardOwnership, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let line_endings = match options.line_endings.as_deref() {
+    None => None,
+    Some(name) => Some(
+      platforms::text_encoding::LineEndings::from_name(name).ok_or_else(|| {
+        NapiError::new(
+          Status::InvalidArg,
+          format!("Unknown line endings: {}", name),
+        )
+      })?,
+    ),
+  };
+  let retry = retry_policy(options.retry);
+
+  let text = match line_endings {
+    Some(line_endings) => line_endings.apply(&text),
+    None => text,
+  };
+  let paths = if options.also_as_uri_list.unwrap_or(false) {
+    text_paths::parse_text_as_paths(&text)
+  } else {
+    Vec::new()
+  };
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let ownership = platforms::retry::with_policy(retry, || {
+      current_platform::track_ownership(platforms::Selection::Clipboard, || {
+        current_platform::write_clipboard_text(&text, &paths)
+      })
+    })
+    .map_err(platform_error_to_napi)?;
+    Ok(ClipboardOwnership::new(
+      platforms::Selection::Clipboard,
+      ownership,
+    ))
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = (text, paths, retry);
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+/// Clears the OS clipboard.
+///
+/// # Arguments
+/// * `options` - Optional clear options.
+///   - `selection`: `"clipboard"` (default) or `"primary"` (Linux X11 only).
+///   - `onlyIfOwner`: Only clear when the content most recently written by this process
+///     (`writeClipboardFilePaths`, `writeClipboardText`, `writeClipboardLazy` or an earlier clear)
+///     is still on the selection, so data copied by another application in the meantime is
+///     left alone.
+///
+/// # Returns
+/// * Returns `Ok(true)` if the selection was cleared, `Ok(false)` if it was left untouched because
+///   of `onlyIfOwner`.
+/// * Returns `Err(napi::Error)` for an unknown selection, or if the clipboard cannot be cleared
+///   (e.g. on Linux without an X server, `xsel` or `wl-copy`).
+///
+/// # Note
+/// * `writeClipboardFilePaths([])` behaves like `clearClipboard()`.
+/// * On X11 the selection owner is removed; a clipboard manager may take it over again.
+#[napi]
+pub fn clear_clipboard(options: Option<ClearClipboardOptions>) -> Result<bool, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let selection = parse_selection(options.selection)?;
+  let only_if_owner = options.only_if_owner.unwrap_or(false);
+  let retry = retry_policy(options.retry);
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    if only_if_owner {
+      let last_write = LAST_WRITES
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|(s, _)| *s == selection)
+        .map(|(_, ownership)| Arc::clone(ownership));
+      let Some(ownership) = last_write else {
+        return Ok(false);
+      };
+      if !ownership.is_owner().map_err(platform_error_to_napi)? {
+        return Ok(false);
+      }
+    }
+
+    let ownership =
+      platforms::retry::with_policy(retry, || current_platform::clear_clipboard(selection))
+        .map_err(platform_error_to_napi)?;
+    remember_last_write(selection, &Arc::new(ownership));
+    Ok(true)
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = (selection, only_if_owner, retry);
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
 }
 
 /// Reads content from the OS clipboard, trying to extract both file paths and text independently.
 ///
+/// # Arguments
+/// * `options` - Optional read options.
+///   - `parseTextAsPaths`: When no file paths are on the clipboard, recognize existing paths
+///     in the text (absolute, `~/`, `file://`, quoted/escaped shell paths and Windows drive/UNC
+///     paths) and return them as `filePaths` with `source: "text"`.
+///   - `withMetadata`: Also return `metadata` for each entry that has a `path`: `exists`,
+///     `kind` (`"file"`, `"dir"`, `"symlink"` or `"other"`), `size`, `mtimeMs`, `mode`
+///     (Unix permission bits) and `readonly`. The paths are stat'ed in parallel in Rust,
+///     which is much faster than calling `fs.stat` for each path of a large selection.
+///
 /// # Returns
 /// * Returns `Ok(ClipboardContent)` containing results for both file paths and text reads.
+///   - `entries` lists every copied URI as `{ uri, path, scheme, isLocal }`, including
+///     non-`file://` URIs such as GVFS `smb://`, `sftp://`, `trash://` and `recent://`
+///     locations copied from Nautilus. On Linux a GVFS URI gets the FUSE path under
+///     `$XDG_RUNTIME_DIR/gvfs` as `path` when gvfsd-fuse exposes the mount (`isLocal` stays
+///     `false`); otherwise `path` is null.
+///   - `filePaths` holds the `path` of each entry that has one.
 /// * Returns `Err(napi::Error)` if both file paths and text reads failed.
 ///
 /// # Note
@@ -148,13 +935,18 @@ pub fn write_clipboard_file_paths(paths: Vec<String>) -> Result<(), NapiError> {
 /// * If at least one of the reads succeeds, the function returns success with available data.
 /// * Only returns an error if both file paths and text reads fail.
 #[napi]
-pub fn read_clipboard_file_paths() -> napi::Result<ClipboardContent> {
-  let internal_result = {
+pub fn read_clipboard_file_paths(
+  options: Option<ReadClipboardOptions>,
+) -> Result<ClipboardContent, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let retry = retry_policy(options.retry);
+
+  let internal_result = platforms::retry::with_policy(retry, || {
     #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
     {
-      // ファイルパスの読み取りを試みる
-      let file_paths_result =
-        current_platform::read_clipboard_file_paths().map_err(platform_error_to_napi);
+      // ファイルの URI とパスの読み取りを試みる
+      let file_entries_result =
+        current_platform::read_clipboard_file_entries().map_err(platform_error_to_napi);
 
       // テキストの読み取りを試みる
       let text_result = match current_platform::read_clipboard_text() {
@@ -172,7 +964,7 @@ pub fn read_clipboard_file_paths() -> napi::Result<ClipboardContent> {
       };
 
       ClipboardReadResult {
-        file_paths: file_paths_result,
+        file_entries: file_entries_result,
         text: text_result,
       }
     }
@@ -181,37 +973,51 @@ pub fn read_clipboard_file_paths() -> napi::Result<ClipboardContent> {
     {
       // サポートされていないOSの場合、両方の結果をエラーとして返す
       ClipboardReadResult {
-        file_paths: Err(NapiError::from_reason("Unsupported OS for file paths")),
-        text: Err(NapiError::from_reason("Unsupported OS for text")),
+        file_entries: Err(NapiError::from_reason("Unsupported OS for file paths").into()),
+        text: Err(NapiError::from_reason("Unsupported OS for text").into()),
       }
     }
-  };
+  });
 
   // 両方エラーであれば、エラーを返す
-  if internal_result.file_paths.is_err() && internal_result.text.is_err() {
-    // ファイルパスとテキストの両方が取得できなかった場合
-    let file_paths_err = internal_result.file_paths.unwrap_err();
-    let text_err = internal_result.text.unwrap_err();
-    return Err(NapiError::from_reason(format!(
-      "Failed to read clipboard content: file paths error: {}, text error: {}",
-      file_paths_err.reason, text_err.reason
-    )));
+  if let (Err(file_paths_err), Err(text_err)) =
+    (&internal_result.file_entries, &internal_result.text)
+  {
+    // ファイルパスとテキストの両方が取得できなかった場合（どちらもタイムアウトならタイムアウト）
+    let status = if file_paths_err.status == text_err.status {
+      file_paths_err.status
+    } else {
+      ErrorCode::Status(Status::GenericFailure)
+    };
+    return Err(ClipboardError {
+      status,
+      reason: format!(
+        "Failed to read clipboard content: file paths error: {}, text error: {}",
+        file_paths_err.reason, text_err.reason
+      ),
+    });
   }
 
   // 少なくとも一方が成功した場合は、結果を返す
   let mut result = ClipboardContent::default();
 
   // ファイルパスの結果を処理
-  match &internal_result.file_paths {
-    Ok(paths) => {
-      result.file_paths = paths.clone();
+  match &internal_result.file_entries {
+    Ok(entries) => {
+      result.file_paths = entries
+        .iter()
+        .filter_map(|entry| entry.path.clone())
+        .collect();
+      result.entries = entries.iter().cloned().map(Into::into).collect();
     }
     Err(_) => {
       // ファイルパスの読み取りに失敗した場合は、rawデータをテキストとして試す
       if result.text.is_none() && internal_result.text.is_err() {
         // テキストもファイルパスも取得できなかった場合、raw読み取りを試みる
         #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
-        if let Ok(raw_data) = current_platform::read_clipboard_raw() {
+        if let Ok(raw_data) =
+          platforms::retry::with_policy(retry, current_platform::read_clipboard_raw)
+        {
           if !raw_data.is_empty() {
             // UTF-8として解釈を試みる
             if let Ok(text) = String::from_utf8(raw_data.clone()) {
@@ -230,6 +1036,36 @@ pub fn read_clipboard_file_paths() -> napi::Result<ClipboardContent> {
     result.text = text;
   }
 
+  if !result.file_paths.is_empty() {
+    result.source = Some("clipboard".to_string());
+  } else if options.parse_text_as_paths.unwrap_or(false) {
+    // ファイル形式のパスがなければ、テキストからパスを抽出する
+    if let Some(text) = &result.text {
+      result.file_paths = text_paths::parse_text_as_paths(text);
+      result.entries = result
+        .file_paths
+        .iter()
+        .map(|path| platforms::file_entry::FileEntry::from_path(path).into())
+        .collect();
+      if !result.file_paths.is_empty() {
+        result.source = Some("text".to_string());
+      }
+    }
+  }
+
+  // パスのあるエントリのメタデータをまとめて取得する
+  if options.with_metadata.unwrap_or(false) {
+    let paths: Vec<&str> = result
+      .entries
+      .iter()
+      .filter_map(|entry| entry.path.as_deref())
+      .collect();
+    let mut metadata = file_metadata::read_all(&paths).into_iter();
+    for entry in result.entries.iter_mut().filter(|e| e.path.is_some()) {
+      entry.metadata = metadata.next().map(Into::into);
+    }
+  }
+
   // 常にtextフィールドを確保する（nullでも含める）
   if result.text.is_none() {
     result.text = None;
@@ -238,37 +1074,1115 @@ pub fn read_clipboard_file_paths() -> napi::Result<ClipboardContent> {
   Ok(result)
 }
 
-#[cfg(test)]
-mod tests {
-  use super::*;
-
-  use std::env::temp_dir;
-  use std::fs::File;
-  use std::io::Write;
-
-  // hello_world関数のテスト
-  #[test]
-  fn test_hello_world() {
-    let result = hello_world();
-    // OSによって出力が異なるため、特定の文字列は検証せず
-    // 空でないことだけ確認する
-    assert!(!result.is_empty());
-    assert!(result.contains("Rust"));
+/// Reads the clipboard text and reports which format and charset it was decoded from.
+///
+/// # Returns
+/// * Returns `Ok(ClipboardText)` with `{ text, sourceFormat, charset, wasLossy }`.
+///   - Linux: the targets are tried in the order `UTF8_STRING`, `text/plain;charset=utf-8`,
+///     `STRING` (ISO-8859-1), `COMPOUND_TEXT` and `TEXT`, and each is decoded with its own
+///     charset, so text from legacy X applications (e.g. EUC-JP inside `COMPOUND_TEXT`)
+///     is not garbled.
+///   - Windows: `CF_UNICODETEXT` decoded as UTF-16LE.
+///   - macOS: the first pasteboard type that holds a string, as UTF-8.
+///   - `wasLossy` is `true` when some bytes could not be decoded and became U+FFFD.
+/// * Returns `Err(napi::Error)` if there is no text on the clipboard or it cannot be accessed.
+#[napi]
+pub fn read_clipboard_text() -> Result<ClipboardText, ClipboardError> {
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let decoded =
+      current_platform::read_clipboard_text_decoded().map_err(platform_error_to_napi)?;
+    Ok(ClipboardText {
+      text: decoded.text,
+      source_format: decoded.source_format,
+      charset: decoded.charset,
+      was_lossy: decoded.was_lossy,
+    })
   }
 
-  // 空の入力に対するエラーテスト
-  #[test]
-  fn test_write_clipboard_file_paths_empty_input() {
-    let result = write_clipboard_file_paths(vec![]);
-    println!("test_write_clipboard_file_paths_empty_input: {:?}", result);
-    assert!(result.is_ok());
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    Err(NapiError::from_reason("Unsupported operating system").into())
   }
+}
 
-  // 実際のファイルを作成してコピーするテスト
-  // 注意: このテストは実際のクリップボードを変更します
-  #[test]
-  fn test_copy_real_files() {
-    // テスト用の一時ファイルを作成
+/// Reads the file paths on the clipboard and lists the files inside the copied folders.
+///
+/// # Arguments
+/// * `options` - Optional expansion options.
+///   - `recursive`: Walk into subfolders (default `true`). With `false` only the files directly
+///     inside each copied folder are listed.
+///   - `followSymlinks`: Follow symbolic links (default `false`, the link itself is listed).
+///     Each folder is walked at most once, so link loops end. Linked folders are walked after
+///     all others, so a folder is listed under its real path, and files reached only through
+///     a link come last.
+///   - `maxEntries`: Stop after this many entries and set `truncated` (default 10000).
+///   - `include` / `exclude`: Glob patterns (`*`, `**`, `?`, `[...]`) matched against the
+///     `/`-separated path relative to the copied folder. A pattern without `/` matches the
+///     file name at any depth. `exclude` also skips the contents of matching folders.
+///
+/// # Returns
+/// * Returns `Ok(ClipboardPathExpansion)`. `result()` walks the folders on the libuv thread
+///   pool and resolves with `{ entries, truncated, errors }`; it can only be called once. Each entry is
+///   `{ path, root, relativePath, kind, size }`, where `relativePath` is relative to the
+///   copied `root` (the file name when a file was copied). Folders themselves are not
+///   listed, and folders that cannot be read are reported in `errors`.
+/// * `cancel()` stops the walk and makes `result()` reject.
+/// * Returns `Err(napi::Error)` for an invalid glob pattern or if the clipboard cannot be read.
+#[napi]
+pub fn expand_clipboard_paths(
+  options: Option<ExpandClipboardPathsOptions>,
+) -> Result<ClipboardPathExpansion, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let globs = |patterns: Option<Vec<String>>| -> napi::Result<Vec<glob::Glob>> {
+    patterns
+      .unwrap_or_default()
+      .iter()
+      .map(|pattern| {
+        glob::Glob::new(pattern).map_err(|reason| NapiError::new(Status::InvalidArg, reason))
+      })
+      .collect()
+  };
+  let expand_options = path_expansion::ExpandOptions {
+    recursive: options.recursive.unwrap_or(true),
+    follow_symlinks: options.follow_symlinks.unwrap_or(false),
+    max_entries: options
+      .max_entries
+      .map_or(path_expansion::DEFAULT_MAX_ENTRIES, |max| max as usize),
+    include: globs(options.include)?,
+    exclude: globs(options.exclude)?,
+  };
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let roots = platforms::retry::with_policy(
+      retry_policy(options.retry),
+      current_platform::read_clipboard_file_paths,
+    )
+    .map_err(platform_error_to_napi)?;
+    Ok(ClipboardPathExpansion::new(roots, expand_options))
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = (expand_options, options.retry);
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+/// `expand_clipboard_paths` で開始した展開
+#[napi]
+pub struct ClipboardPathExpansion {
+  cancelled: Arc<AtomicBool>,
+  // result() でタスクに渡すまでの、たどるパスとオプション
+  pending: Mutex<Option<(Vec<String>, path_expansion::ExpandOptions)>>,
+}
+
+impl ClipboardPathExpansion {
+  // result() が呼ばれたらワーカースレッドでたどる
+  fn new(roots: Vec<String>, options: path_expansion::ExpandOptions) -> Self {
+    Self {
+      cancelled: Arc::new(AtomicBool::new(false)),
+      pending: Mutex::new(Some((roots, options))),
+    }
+  }
+}
+
+#[napi]
+impl ClipboardPathExpansion {
+  /// Stops the walk. `result()` then rejects with a cancellation error.
+  #[napi]
+  pub fn cancel(&self) {
+    self.cancelled.store(true, Ordering::SeqCst);
+  }
+
+  /// Walks the copied folders on the libuv thread pool and resolves with the expanded files.
+  /// Can only be called once.
+  #[napi(ts_return_type = "Promise<ExpandedClipboardPaths>")]
+  pub fn result(&self) -> napi::Result<AsyncTask<ExpansionTask>> {
+    let (roots, options) = self
+      .pending
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .take()
+      .ok_or_else(|| NapiError::new(Status::InvalidArg, "result() can only be called once"))?;
+    Ok(AsyncTask::new(ExpansionTask {
+      roots,
+      options,
+      cancelled: Arc::clone(&self.cancelled),
+    }))
+  }
+}
+
+/// `ClipboardPathExpansion::result` の非同期タスク（cancel() で設定されるフラグを確認しながらたどる）
+pub struct ExpansionTask {
+  roots: Vec<String>,
+  options: path_expansion::ExpandOptions,
+  cancelled: Arc<AtomicBool>,
+}
+
+impl napi::Task for ExpansionTask {
+  type Output = ExpandedClipboardPaths;
+  type JsValue = ExpandedClipboardPaths;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    path_expansion::expand(&self.roots, &self.options, &self.cancelled)
+      .map(Into::into)
+      .map_err(|e| NapiError::from_reason(e.to_string()))
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+}
+
+/// Reads several clipboard formats as one consistent snapshot.
+///
+/// # Arguments
+/// * `options` - Optional snapshot options.
+///   - `formats`: Formats to read (`"filePaths"`, `"text"`, `"raw"`). Defaults to all of them.
+///   - `maxAttempts`: How many times to re-read when the clipboard owner changes mid-read (default: 3).
+///
+/// # Returns
+/// * Returns `Ok(ClipboardSnapshot)` whose formats all come from the same clipboard ownership,
+///   checked with the X11 selection timestamp, `GetClipboardSequenceNumber` on Windows or
+///   `NSPasteboard.changeCount` on macOS. A format that could not be read is `null`.
+/// * Returns `Err(napi::Error)` for an unknown format, or if the clipboard changed during every attempt.
+#[napi]
+pub fn snapshot_clipboard(
+  options: Option<SnapshotClipboardOptions>,
+) -> Result<ClipboardSnapshot, ClipboardError> {
+  let options = options.unwrap_or_default();
+  let formats = options.formats.unwrap_or_else(|| {
+    vec![
+      "filePaths".to_string(),
+      "text".to_string(),
+      "raw".to_string(),
+    ]
+  });
+
+  if let Some(unknown) = formats
+    .iter()
+    .find(|f| !matches!(f.as_str(), "filePaths" | "text" | "raw"))
+  {
+    return Err(
+      NapiError::new(
+        Status::InvalidArg,
+        format!("Unknown clipboard format: {}", unknown),
+      )
+      .into(),
+    );
+  }
+  let wants = |format: &str| formats.iter().any(|f| f == format);
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let max_attempts = options
+      .max_attempts
+      .unwrap_or(snapshot::DEFAULT_MAX_ATTEMPTS);
+    let retry = retry_policy(options.retry);
+    let snapshot = platforms::retry::with_policy(retry, || {
+      snapshot::read_consistent(max_attempts, current_platform::clipboard_epoch, || {
+        let file_paths = wants("filePaths")
+          .then(|| current_platform::read_clipboard_file_paths().ok())
+          .flatten();
+        let text = wants("text")
+          .then(|| current_platform::read_clipboard_text().ok())
+          .flatten();
+        let raw = wants("raw")
+          .then(|| current_platform::read_clipboard_raw().ok())
+          .flatten();
+        (file_paths, text, raw)
+      })
+    })
+    .map_err(platform_error_to_napi)?;
+
+    let (file_paths, text, raw) = snapshot.value;
+    Ok(ClipboardSnapshot {
+      file_paths,
+      text,
+      raw: raw.map(Buffer::from),
+      attempts: snapshot.attempts,
+      verified: snapshot.verified,
+    })
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = wants;
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+/// Returns a value that increases every time the clipboard changes.
+///
+/// # Arguments
+/// * `options` - Optional change options.
+///   - `selection`: `"clipboard"` (default) or `"primary"` (Linux only).
+///
+/// # Returns
+/// * Returns `Ok(number)` without reading the clipboard contents. The value comes from
+///   XFIXES selection events on Linux (counted from the first call in this process),
+///   `GetClipboardSequenceNumber` on Windows and `NSPasteboard.changeCount` on macOS.
+/// * Returns `Err(napi::Error)` for an unknown selection, or if the change counter is unavailable
+///   (e.g. no X server on Linux).
+#[napi]
+pub fn get_clipboard_change_count(
+  options: Option<ClipboardChangeOptions>,
+) -> Result<i64, ClipboardError> {
+  let selection = parse_selection(options.unwrap_or_default().selection)?;
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    current_platform::clipboard_change_count(selection)
+      .map(|count| count as i64)
+      .map_err(platform_error_to_napi)
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = selection;
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+/// Checks whether the clipboard changed since `token` was obtained.
+///
+/// # Arguments
+/// * `token` - A value previously returned by `getClipboardChangeCount` for the same selection.
+/// * `options` - Optional change options (see `getClipboardChangeCount`).
+///
+/// # Returns
+/// * Returns `Ok(true)` if the current change count differs from `token`.
+#[napi]
+pub fn has_clipboard_changed_since(
+  token: i64,
+  options: Option<ClipboardChangeOptions>,
+) -> Result<bool, ClipboardError> {
+  Ok(get_clipboard_change_count(options)? != token)
+}
+
+/// Identifies the application that put the current data on the clipboard.
+///
+/// # Returns
+/// * Returns `Ok(ClipboardOwnerInfo)`. Fields that cannot be derived on this platform are null:
+///   - Linux (X11): the `XGetSelectionOwner` window, its `_NET_WM_PID` and `/proc/<pid>/exe` (null
+///     when the owner's `WM_CLIENT_MACHINE` is another host).
+///   - Windows: the `GetClipboardOwner` window, its process ID and executable.
+///   - macOS: there is no owner API, so `pid` / `executable` come from the running application
+///     whose bundle id matches the `org.nspasteboard.source` marker.
+///   - `source` is the `org.nspasteboard.source` marker on every platform
+///     (see `writeClipboardFilePaths(paths, { sourceMarker })`).
+/// * Returns `Err(napi::Error)` if the clipboard cannot be accessed (e.g. no X server and
+///   no clipboard helper on Linux).
+#[napi]
+pub fn get_clipboard_owner_info() -> Result<ClipboardOwnerInfo, ClipboardError> {
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let info = current_platform::clipboard_owner_info().map_err(platform_error_to_napi)?;
+    Ok(ClipboardOwnerInfo {
+      window: info.window.map(|w| w as i64),
+      pid: info.pid,
+      executable: info.executable,
+      source: info.source,
+    })
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+// 呼び出しごとの再試行のオプションを、全体のポリシーに重ねて変換する
+// オプションがない場合は None（全体のポリシーをそのまま使う）
+fn retry_policy(options: Option<RetryOptions>) -> Option<platforms::retry::RetryPolicy> {
+  let options = options?;
+  let global = platforms::retry::global_policy();
+  Some(platforms::retry::RetryPolicy {
+    attempts: options.attempts.unwrap_or(global.attempts).max(1),
+    backoff: options
+      .backoff_ms
+      .map_or(global.backoff, |ms| Duration::from_millis(ms as u64)),
+    deadline: options
+      .deadline_ms
+      .map_or(global.deadline, |ms| Duration::from_millis(ms as u64)),
+  })
+}
+
+// JavaScript から渡されたセレクション名を変換する
+fn parse_selection(selection: Option<String>) -> napi::Result<platforms::Selection> {
+  match selection {
+    None => Ok(platforms::Selection::Clipboard),
+    Some(name) => platforms::Selection::from_name(&name)
+      .ok_or_else(|| NapiError::new(Status::InvalidArg, format!("Unknown selection: {}", name))),
+  }
+}
+
+// JavaScript から渡されたテキストの形式、相対パスの基準、パスの対応表を変換する
+// どれも指定されていない場合は None（各プラットフォームの従来の内容）
+fn parse_path_text(
+  name: Option<String>,
+  custom_template: Option<String>,
+  relative_to: Option<String>,
+  path_mappings: Option<Vec<PathMappingOptions>>,
+  wsl_distro: Option<String>,
+) -> napi::Result<Option<platforms::text_representation::PathText>> {
+  use platforms::path_mapping::{wsl_mappings, PathMapping};
+  use platforms::text_representation::{PathText, TextRepresentation};
+
+  let representation = match name.as_deref() {
+    None => None,
+    Some("custom") => Some(TextRepresentation::Custom(custom_template.ok_or_else(
+      || {
+        NapiError::new(
+          Status::InvalidArg,
+          "textRepresentation \"custom\" requires customTemplate".to_string(),
+        )
+      },
+    )?)),
+    Some(name) => Some(TextRepresentation::from_name(name).ok_or_else(|| {
+      NapiError::new(
+        Status::InvalidArg,
+        format!("Unknown text representation: {}", name),
+      )
+    })?),
+  };
+
+  // 書き込むパスと同じく、シンボリックリンクを解決した絶対パスを基準にする
+  let relative_to = match relative_to {
+    None => None,
+    Some(dir) => Some(
+      fs::canonicalize(&dir)
+        .map_err(|e| {
+          NapiError::new(
+            Status::InvalidArg,
+            format!("Failed to canonicalize relativeTo {}: {}", dir, e),
+          )
+        })?
+        .to_string_lossy()
+        .into_owned(),
+    ),
+  };
+
+  let mut mappings: Vec<PathMapping> = path_mappings
+    .unwrap_or_default()
+    .into_iter()
+    .map(|mapping| PathMapping::new(mapping.from, mapping.to))
+    .collect();
+  if let Some(distro) = wsl_distro {
+    mappings.extend(wsl_mappings(&distro));
+  }
+
+  if representation.is_none() && relative_to.is_none() && mappings.is_empty() {
+    return Ok(None);
+  }
+  // 形式を省略した場合は各プラットフォームの従来の形式（Windows は file URI、それ以外はパス）
+  let default = if cfg!(target_os = "windows") && relative_to.is_none() {
+    TextRepresentation::Uris
+  } else {
+    TextRepresentation::Paths
+  };
+  Ok(Some(PathText {
+    representation: representation.unwrap_or(default),
+    relative_to,
+    mappings,
+  }))
+}
+
+/// Reads one clipboard format and writes it to a file without holding the whole payload in memory.
+///
+/// # Arguments
+/// * `format` - The format to read: a MIME type / X11 target on Linux (`"image/png"`),
+///   a pasteboard type on macOS (`"public.png"`), or a registered format name or numeric
+///   format ID on Windows (`"PNG"`, `"13"`).
+/// * `dest_path` - The file to write. It is replaced only after the whole payload was read.
+///
+/// # Returns
+/// * Returns `Ok(number)` with the number of bytes written.
+/// * Returns `Err(napi::Error)` if the format is not available or reading/writing fails.
+#[napi]
+pub fn read_clipboard_format_to_file(
+  format: String,
+  dest_path: String,
+) -> Result<i64, ClipboardError> {
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    use std::io::Write;
+
+    // 途中で失敗しても既存のファイルを壊さないよう、一時ファイルに書いてから置き換える
+    let temp_path = format!("{}.clip-filepaths-{}.tmp", dest_path, std::process::id());
+    let file = fs::File::create(&temp_path).map_err(|e| {
+      NapiError::new(
+        Status::GenericFailure,
+        format!("Failed to create {}: {}", dest_path, e),
+      )
+    })?;
+    let mut writer = std::io::BufWriter::new(file);
+
+    let result =
+      current_platform::read_clipboard_format(&format, &mut |chunk| writer.write_all(chunk));
+    let result = result.and_then(|bytes| {
+      writer.flush()?;
+      fs::rename(&temp_path, &dest_path)?;
+      Ok(bytes)
+    });
+
+    result.map(|bytes| bytes as i64).map_err(|e| {
+      let _ = fs::remove_file(&temp_path);
+      platform_error_to_napi(e)
+    })
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = (format, dest_path);
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+/// Reads one clipboard format as a Node.js `Readable` stream of `Buffer` chunks.
+///
+/// # Arguments
+/// * `format` - The format to read (see `readClipboardFormatToFile`).
+///
+/// # Returns
+/// * A `Readable` fed from a background thread: directly from the helper process output or
+///   X11 `INCR` chunks on Linux, and in 64 KiB slices of the native buffer elsewhere.
+///   Read errors (e.g. the format is not available) are emitted as an `error` event.
+/// * The thread only reads ahead while the consumer asks for data, so a slow consumer does not
+///   buffer the whole payload, and `destroy()` stops the read. A stream that is neither read to
+///   the end nor destroyed keeps the process alive, like a paused socket.
+/// * Requires `process.getBuiltinModule` (Node.js 20.16 or later).
+#[napi(ts_return_type = "import('stream').Readable")]
+pub fn read_clipboard_format_stream(env: Env, format: String) -> napi::Result<JsObject> {
+  let readable_class = readable_class(&env)?;
+  let mut stream_options = env.create_object()?;
+  stream_options.set_named_property("read", env.create_function("read", request_stream_data)?)?;
+  stream_options.set_named_property("destroy", env.create_function("destroy", close_stream)?)?;
+  let mut readable = readable_class.new_instance(&[stream_options])?;
+
+  // read() / destroy() は読み取りスレッドと共有する要求の状態を更新する
+  let demand = Arc::new(platforms::stream::Demand::default());
+  env.wrap(&mut readable, Arc::clone(&demand))?;
+
+  let push: ThreadsafeFunction<Option<Vec<u8>>, ErrorStrategy::Fatal> =
+    bind_method(&readable, "push")?
+      .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<Option<Vec<u8>>>| {
+        Ok(vec![ctx.value.map(Buffer::from)])
+      })?;
+  let destroy: ThreadsafeFunction<ClipboardError, ErrorStrategy::Fatal> = bind_method(
+    &readable, "destroy",
+  )?
+  .create_threadsafe_function(0, |ctx: ThreadSafeCallContext<ClipboardError>| {
+    Ok(vec![napi::JsError::from(ctx.value).into_unknown(ctx.env)])
+  })?;
+
+  std::thread::spawn(move || {
+    #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+    let result = current_platform::read_clipboard_format(&format, &mut |chunk| {
+      push_chunk(&push, &demand, chunk)
+    })
+    .map_err(platform_error_to_napi);
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+    let result: Result<u64, ClipboardError> = {
+      let _ = format;
+      Err(NapiError::from_reason("Unsupported operating system").into())
+    };
+
+    match result {
+      Ok(_) => push.call(None, ThreadsafeFunctionCallMode::Blocking),
+      // destroy() された場合は何もしない
+      Err(_) if demand.is_closed() => Status::Ok,
+      Err(e) => destroy.call(e, ThreadsafeFunctionCallMode::Blocking),
+    };
+  });
+
+  Ok(readable)
+}
+
+// 受け取り側がデータを求めるまで待ってからチャンクを push する
+// push() が false を返した（バッファがいっぱいの）場合は、次の read() まで止める
+#[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+fn push_chunk(
+  push: &ThreadsafeFunction<Option<Vec<u8>>, ErrorStrategy::Fatal>,
+  demand: &Arc<platforms::stream::Demand>,
+  chunk: &[u8],
+) -> Result<(), IoError> {
+  demand.wait()?;
+
+  // 止める処理は read() と同じ JS スレッドで行い、push の後の read() を取りこぼさない
+  let (pushed_tx, pushed_rx) = mpsc::channel();
+  let paused = Arc::clone(demand);
+  let status = push.call_with_return_value(
+    Some(chunk.to_vec()),
+    ThreadsafeFunctionCallMode::Blocking,
+    move |more: bool| {
+      if !more {
+        paused.pause();
+      }
+      let _ = pushed_tx.send(());
+      Ok(())
+    },
+  );
+  if status != Status::Ok {
+    return Err(IoError::other(format!("Stream closed ({})", status)));
+  }
+  pushed_rx
+    .recv()
+    .map_err(|_| IoError::other("Stream closed"))
+}
+
+// Readable の read()。読み取りスレッドに次のチャンクを求める
+#[js_function]
+fn request_stream_data(ctx: napi::CallContext) -> napi::Result<napi::JsUndefined> {
+  let this: JsObject = ctx.this_unchecked();
+  ctx
+    .env
+    .unwrap::<Arc<platforms::stream::Demand>>(&this)?
+    .request();
+  ctx.env.get_undefined()
+}
+
+// Readable の destroy()。読み取りスレッドを止める
+#[js_function(2)]
+fn close_stream(ctx: napi::CallContext) -> napi::Result<napi::JsUndefined> {
+  let this: JsObject = ctx.this_unchecked();
+  ctx
+    .env
+    .unwrap::<Arc<platforms::stream::Demand>>(&this)?
+    .close();
+  let error: JsUnknown = ctx.get(0)?;
+  let callback: JsFunction = ctx.get(1)?;
+  callback.call(None, &[error])?;
+  ctx.env.get_undefined()
+}
+
+#[js_function]
+fn noop(ctx: napi::CallContext) -> napi::Result<napi::JsUndefined> {
+  ctx.env.get_undefined()
+}
+
+// `stream.Readable` のコンストラクタを取得する
+// ネイティブモジュールからは require を使えないため process.getBuiltinModule を使う
+fn readable_class(env: &Env) -> napi::Result<JsFunction> {
+  let process: JsObject = env.get_global()?.get_named_property("process")?;
+  let get_builtin_module: JsUnknown = process.get_named_property("getBuiltinModule")?;
+  if get_builtin_module.get_type()? != ValueType::Function {
+    return Err(NapiError::new(
+      Status::GenericFailure,
+      "readClipboardFormatStream requires process.getBuiltinModule (Node.js 20.16 or later)",
+    ));
+  }
+  let get_builtin_module: JsFunction = unsafe { get_builtin_module.cast() };
+  let stream = get_builtin_module
+    .call(Some(&process), &[env.create_string("stream")?])?
+    .coerce_to_object()?;
+  stream.get_named_property("Readable")
+}
+
+// `object[name].bind(object)` を取得する
+fn bind_method(object: &JsObject, name: &str) -> napi::Result<JsFunction> {
+  let method: JsUnknown = object.get_named_property(name)?;
+  let method = method.coerce_to_object()?;
+  let bind: JsFunction = method.get_named_property("bind")?;
+  let bound = bind.call(Some(&method), &[object])?;
+  Ok(unsafe { bound.cast() })
+}
+
+/// Registers formats on the clipboard and renders their data only when a paste target asks for it.
+///
+/// # Arguments
+/// * `options` - Lazy write options.
+///   - `formats`: Format names to offer (MIME types on Linux, pasteboard types on macOS and
+///     registered format names on Windows).
+///   - `provider`: Called with a format name when that format is requested. Returns a `Buffer`
+///     or a string, or `null`/`undefined` to refuse the format.
+///
+/// # Returns
+/// * A `ClipboardOwnership` handle, like `writeClipboardFilePaths`.
+///
+/// # Note
+/// * Linux serves the X11 `CLIPBOARD` selection directly (`SelectionRequest`) and needs an
+///   X server. Each format is rendered once and kept for later pastes.
+/// * Windows uses delayed rendering (`WM_RENDERFORMAT`) and macOS uses
+///   `NSPasteboardItemDataProvider`, which needs a running main run loop (e.g. Electron).
+/// * The data is only available while this process is running, and a provider that does not
+///   return within the clipboard timeout is treated as a failure. A format whose provider fails
+///   is refused, so the paste target sees it as unavailable.
+/// * On Linux and Windows the provider is called on the JavaScript thread through the event loop.
+///   Reading a lazily written format synchronously from the same process (for example
+///   `readClipboardFormatToFile` right after `writeClipboardLazy`) blocks that thread, so the
+///   provider cannot run and the read fails after the clipboard timeout. Read such formats from
+///   another process.
+#[napi]
+pub fn write_clipboard_lazy(
+  env: Env,
+  options: LazyClipboardOptions,
+) -> Result<ClipboardOwnership, ClipboardError> {
+  if options.formats.is_empty() {
+    return Err(NapiError::new(Status::InvalidArg, "formats must not be empty").into());
+  }
+
+  #[cfg(any(target_os = "windows", target_os = "macos", target_os = "linux"))]
+  {
+    let provider = JsProvider::new(&env, options.provider)?;
+    let provider: platforms::lazy::Provider = Arc::new(move |format: &str| provider.render(format));
+    let ownership = current_platform::track_ownership(platforms::Selection::Clipboard, || {
+      current_platform::write_clipboard_lazy(&options.formats, provider)
+    })
+    .map_err(platform_error_to_napi)?;
+    Ok(ClipboardOwnership::new(
+      platforms::Selection::Clipboard,
+      ownership,
+    ))
+  }
+
+  #[cfg(not(any(target_os = "windows", target_os = "macos", target_os = "linux")))]
+  {
+    let _ = env;
+    Err(NapiError::from_reason("Unsupported operating system").into())
+  }
+}
+
+// provider 関数への呼び出し要求
+enum ProviderCall {
+  // フォーマット名と、結果の送り先
+  Render(String, SyncSender<Result<Option<Vec<u8>>, IoError>>),
+  // provider 関数への参照を解放する
+  Release,
+}
+
+// JS の provider 関数への参照。JS スレッドでのみ触る
+// 解放は ProviderCall::Release で行い、環境の終了時に残っていた参照は破棄しない
+struct ProviderRef(ManuallyDrop<Ref<()>>);
+
+unsafe impl Send for ProviderRef {}
+
+// napi_env は JS スレッドでのみ使う
+struct RawEnv(napi::sys::napi_env);
+
+unsafe impl Send for RawEnv {}
+unsafe impl Sync for RawEnv {}
+
+// バックグラウンドのスレッドや OS のコールバックから JS の provider 関数を呼び出す
+struct JsProvider {
+  calls: ThreadsafeFunction<ProviderCall, ErrorStrategy::Fatal>,
+  callback: Arc<Mutex<Option<ProviderRef>>>,
+  env: RawEnv,
+  js_thread: ThreadId,
+}
+
+impl JsProvider {
+  fn new(env: &Env, provider: JsFunction) -> napi::Result<Self> {
+    let callback = Arc::new(Mutex::new(Some(ProviderRef(ManuallyDrop::new(
+      env.create_reference(provider)?,
+    )))));
+
+    // provider の例外を JS スレッド上で受け取れるよう、TSFN からは何もしない関数を呼び、
+    // provider 自体はコールバックの中で呼び出す
+    let mut calls: ThreadsafeFunction<ProviderCall, ErrorStrategy::Fatal> = env
+      .create_function("provider", noop)?
+      .create_threadsafe_function(0, {
+        let callback = Arc::clone(&callback);
+        move |ctx: ThreadSafeCallContext<ProviderCall>| {
+          match ctx.value {
+            ProviderCall::Render(format, result) => {
+              let _ = result.send(call_provider(&ctx.env, &callback, &format));
+            }
+            ProviderCall::Release => {
+              let released = callback.lock().unwrap_or_else(|e| e.into_inner()).take();
+              if let Some(ProviderRef(reference)) = released {
+                ManuallyDrop::into_inner(reference).unref(ctx.env)?;
+              }
+            }
+          }
+          Ok(Vec::<JsUnknown>::new())
+        }
+      })?;
+    // 貼り付けを待つためにプロセスを終了させないようにはしない
+    calls.unref(env)?;
+
+    Ok(Self {
+      calls,
+      callback,
+      env: RawEnv(env.raw()),
+      js_thread: thread::current().id(),
+    })
+  }
+
+  // provider でフォーマットのデータを生成する
+  fn render(&self, format: &str) -> Result<Option<Vec<u8>>, IoError> {
+    // macOS ではメインスレッド（= JS スレッド）から要求が届くため、直接呼び出す
+    if thread::current().id() == self.js_thread {
+      let env = unsafe { Env::from_raw(self.env.0) };
+      return env
+        .run_in_scope(|| Ok(call_provider(&env, &self.callback, format)))
+        .unwrap_or_else(|e| Err(IoError::other(e.reason)));
+    }
+
+    // JS スレッドが同期的な処理（このプロセス自身のセレクションの読み取りなど）で
+    // 止まっている間は provider を呼び出せないため、タイムアウトまで待って失敗する
+    let (result_tx, result_rx) = mpsc::sync_channel(1);
+    self.calls.call(
+      ProviderCall::Render(format.to_string(), result_tx),
+      ThreadsafeFunctionCallMode::NonBlocking,
+    );
+    match result_rx.recv_timeout(platforms::timeout::timeout()) {
+      Ok(result) => result,
+      Err(mpsc::RecvTimeoutError::Timeout) => Err(IoError::new(
+        ErrorKind::TimedOut,
+        format!("Clipboard data provider timed out for format {}", format),
+      )),
+      Err(mpsc::RecvTimeoutError::Disconnected) => Err(IoError::other(
+        "Clipboard data provider is no longer available",
+      )),
+    }
+  }
+}
+
+impl Drop for JsProvider {
+  fn drop(&mut self) {
+    self.calls.call(
+      ProviderCall::Release,
+      ThreadsafeFunctionCallMode::NonBlocking,
+    );
+  }
+}
+
+// JS スレッド上で provider を呼び出し、戻り値をバイト列に変換する
+fn call_provider(
+  env: &Env,
+  callback: &Mutex<Option<ProviderRef>>,
+  format: &str,
+) -> Result<Option<Vec<u8>>, IoError> {
+  let callback = callback.lock().unwrap_or_else(|e| e.into_inner());
+  let Some(ProviderRef(reference)) = callback.as_ref() else {
+    return Err(IoError::other(
+      "Clipboard data provider is no longer available",
+    ));
+  };
+
+  let result = env
+    .get_reference_value::<JsFunction>(reference)
+    .and_then(|provider| provider.call(None, &[env.create_string(format)?]))
+    .and_then(provider_value_to_bytes);
+  result.map_err(|e| {
+    IoError::other(format!(
+      "Clipboard data provider failed for format {}: {}",
+      format, e.reason
+    ))
+  })
+}
+
+// provider の戻り値（Buffer / string / null / undefined）をバイト列に変換する
+fn provider_value_to_bytes(value: JsUnknown) -> napi::Result<Option<Vec<u8>>> {
+  match value.get_type()? {
+    ValueType::Undefined | ValueType::Null => Ok(None),
+    ValueType::String => {
+      let text = unsafe { value.cast::<JsString>() }.into_utf8()?;
+      Ok(Some(text.as_slice().to_vec()))
+    }
+    _ if value.is_buffer()? => {
+      let buffer = unsafe { value.cast::<JsBuffer>() }.into_value()?;
+      Ok(Some(buffer.to_vec()))
+    }
+    _ => Err(NapiError::new(
+      Status::InvalidArg,
+      "provider must return a Buffer, a string, null or undefined",
+    )),
+  }
+}
+
+/// Starts dragging the given files out of a window and resolves when the drag ends.
+///
+/// # Arguments
+/// * `window_handle` - The window the drag starts from, as returned by Electron's
+///   `BrowserWindow.getNativeWindowHandle()`.
+/// * `paths` - The files to drag. The drop target receives the same formats as
+///   `writeClipboardFilePaths` (`text/uri-list`, `x-special/gnome-copied-files` and text).
+///
+/// # Returns
+/// * A promise of `{ outcome, action }`.
+///
+/// # Note
+/// * Linux (X11) only. Call it while the mouse button is held (e.g. from a `dragstart` handler);
+///   the drop happens when the button is released, and Escape cancels the drag.
+/// * The pointer is grabbed from a separate X connection, which fails while another client holds
+///   a grab. Cancel Chromium's own drag (`event.preventDefault()` in `dragstart`) before calling
+///   it; a grab that is not released within the clipboard timeout rejects the call.
+/// * If the drop target does not answer within the clipboard timeout after the button is
+///   released, the drag is cancelled.
+#[napi(ts_return_type = "Promise<FileDragResult>")]
+pub fn start_file_drag(
+  window_handle: Buffer,
+  paths: Vec<String>,
+) -> napi::Result<AsyncTask<FileDragTask>> {
+  let origin = native_window_id(&window_handle)?;
+  Ok(AsyncTask::new(FileDragTask {
+    origin,
+    paths,
+    error: None,
+  }))
+}
+
+// `getNativeWindowHandle()` の Buffer からウィンドウ ID を取り出す
+fn native_window_id(handle: &[u8]) -> napi::Result<u64> {
+  match handle.len() {
+    8 => Ok(u64::from_ne_bytes(handle.try_into().unwrap_or_default())),
+    4 => Ok(u32::from_ne_bytes(handle.try_into().unwrap_or_default()).into()),
+    _ => Err(NapiError::new(
+      Status::InvalidArg,
+      "windowHandle must be the Buffer returned by getNativeWindowHandle()",
+    )),
+  }
+}
+
+/// ドラッグが終わるまでワーカースレッドで待つタスク
+pub struct FileDragTask {
+  origin: u64,
+  paths: Vec<String>,
+  // compute で失敗したときのエラー（reject で code を付けて返す）
+  error: Option<ClipboardError>,
+}
+
+impl napi::Task for FileDragTask {
+  type Output = FileDragResult;
+  type JsValue = FileDragResult;
+
+  fn compute(&mut self) -> napi::Result<Self::Output> {
+    #[cfg(target_os = "linux")]
+    {
+      let result = current_platform::start_file_drag(self.origin, &self.paths).map_err(|e| {
+        let error = platform_error_to_napi(e);
+        let napi_error = NapiError::from_reason(error.reason.clone());
+        self.error = Some(error);
+        napi_error
+      })?;
+      Ok(FileDragResult {
+        outcome: result.outcome.to_string(),
+        action: result.action,
+      })
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    {
+      let _ = (self.origin, &self.paths);
+      Err(NapiError::from_reason(
+        "startFileDrag is only available on Linux (X11); use webContents.startDrag instead",
+      ))
+    }
+  }
+
+  fn resolve(&mut self, _env: Env, output: Self::Output) -> napi::Result<Self::JsValue> {
+    Ok(output)
+  }
+
+  fn reject(&mut self, env: Env, err: NapiError) -> napi::Result<Self::JsValue> {
+    Err(match self.error.take() {
+      Some(error) => error.into_js_error(env),
+      None => err,
+    })
+  }
+}
+
+/// Sets the timeout for clipboard operations that wait on another process.
+///
+/// # Arguments
+/// * `timeout_ms` - Timeout in milliseconds (default: 5000). Must be greater than 0.
+///   - On Linux, a helper command (`xclip`, `xsel`, `wl-clipboard`) or X11 request that does not
+///     finish in time is killed and the call fails with an error whose `code` is `"ETIMEDOUT"`,
+///     instead of blocking the calling thread while the selection owner does not respond.
+///
+/// # Returns
+/// * Returns `Err(napi::Error)` with `InvalidArg` for `0`.
+#[napi]
+pub fn set_clipboard_timeout(timeout_ms: u32) -> napi::Result<()> {
+  if timeout_ms == 0 {
+    return Err(NapiError::new(
+      Status::InvalidArg,
+      "timeoutMs must be greater than 0",
+    ));
+  }
+  platforms::timeout::set_timeout(std::time::Duration::from_millis(timeout_ms as u64));
+  Ok(())
+}
+
+/// Returns the current timeout for clipboard operations in milliseconds.
+#[napi]
+pub fn get_clipboard_timeout() -> u32 {
+  platforms::timeout::timeout()
+    .as_millis()
+    .min(u32::MAX as u128) as u32
+}
+
+/// Sets the default retry policy for clipboard operations that fail transiently.
+///
+/// # Arguments
+/// * `policy` - The fields to change; omitted fields keep their current value.
+///   - `attempts`: Maximum number of attempts including the first one (default: 5, minimum 1).
+///   - `backoffMs`: Wait before the first retry in milliseconds, doubled after every retry
+///     (default: 10).
+///   - `deadlineMs`: Total time budget in milliseconds; no retry starts after it (default: 1000).
+///
+/// # Note
+/// * Retries cover failures that go away on their own: `OpenClipboard` failing while another
+///   process holds the clipboard on Windows, losing an X11 selection race or a conversion that
+///   fails while the owner changes on Linux, and a failed pasteboard write on macOS.
+/// * When retries run out, the error message reports how many attempts were made.
+/// * Individual calls can override the policy with their `retry` option.
+#[napi]
+pub fn set_clipboard_retry_policy(policy: RetryOptions) {
+  if let Some(policy) = retry_policy(Some(policy)) {
+    platforms::retry::set_policy(policy);
+  }
+}
+
+/// Returns the default retry policy for clipboard operations.
+#[napi]
+pub fn get_clipboard_retry_policy() -> RetryOptions {
+  let policy = platforms::retry::global_policy();
+  let millis = |d: Duration| d.as_millis().min(u32::MAX as u128) as u32;
+  RetryOptions {
+    attempts: Some(policy.attempts),
+    backoff_ms: Some(millis(policy.backoff)),
+    deadline_ms: Some(millis(policy.deadline)),
+  }
+}
+
+/// Returns the helper command used by the last clipboard operation.
+///
+/// # Returns
+/// * On Linux, `"xclip"`, `"xsel"` or `"wl-clipboard"`; the first installed helper that supports
+///   the requested operation is used.
+/// * `null` if no helper has run yet, or on platforms that use native APIs.
+#[napi]
+pub fn get_clipboard_helper() -> Option<String> {
+  #[cfg(target_os = "linux")]
+  {
+    platforms::linux::helper::last_used().map(|tool| tool.name().to_string())
+  }
+
+  #[cfg(not(target_os = "linux"))]
+  {
+    None
+  }
+}
+
+/// Checks the prerequisites for clipboard access in the current environment.
+///
+/// # Arguments
+/// * `options` - Optional doctor options.
+///   - `roundTrip`: Whether to write and read back a file path as a self-test (default: `true`).
+///     This changes the contents of the system clipboard.
+///
+/// # Returns
+/// * A structured report. On Linux it covers `DISPLAY`/`WAYLAND_DISPLAY`, the X connection,
+///   XFIXES availability, installed helper tools and clipboard manager presence.
+/// * The same report is available from the command line via `clip-filepaths-doctor`.
+#[napi]
+pub fn clipboard_doctor(options: Option<ClipboardDoctorOptions>) -> ClipboardDoctorReport {
+  let options = options.unwrap_or_default();
+  let doctor_options = doctor::DoctorOptions {
+    round_trip: options.round_trip.unwrap_or(true),
+  };
+  doctor::run(&doctor_options).into()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  use std::env::temp_dir;
+  use std::fs::File;
+  use std::io::Write;
+
+  // hello_world関数のテスト
+  #[test]
+  fn test_hello_world() {
+    let result = hello_world();
+    // OSによって出力が異なるため、特定の文字列は検証せず
+    // 空でないことだけ確認する
+    assert!(!result.is_empty());
+    assert!(result.contains("Rust"));
+  }
+
+  // 空の入力に対するエラーテスト
+  #[test]
+  fn test_write_clipboard_file_paths_empty_input() {
+    let result = write_clipboard_file_paths(vec![], None);
+    println!("test_write_clipboard_file_paths_empty_input: {:?}", result);
+    assert!(result.is_ok());
+  }
+
+  // 不明なセレクションはクリップボードに触れずにエラーになる
+  #[test]
+  fn test_clear_clipboard_unknown_selection() {
+    let err = clear_clipboard(Some(ClearClipboardOptions {
+      selection: Some("secondary".to_string()),
+      only_if_owner: None,
+      retry: None,
+    }))
+    .unwrap_err();
+    assert_eq!(err.status, Status::InvalidArg);
+  }
+
+  // タイムアウトは code が ETIMEDOUT のエラー、それ以外は GenericFailure になる
+  #[test]
+  fn test_platform_error_code() {
+    let err = platform_error_to_napi(IoError::new(ErrorKind::TimedOut, "timed out after 5 ms"));
+    assert_eq!(err.status, ErrorCode::TimedOut);
+    assert_eq!(err.status.as_ref(), "ETIMEDOUT");
+    assert!(err.reason.contains("timed out after 5 ms"));
+
+    let err = platform_error_to_napi(IoError::new(ErrorKind::NotFound, "No text"));
+    assert_eq!(err.status, Status::GenericFailure);
+    assert_eq!(err.status.as_ref(), "GenericFailure");
+  }
+
+  // タイムアウトの 0 は設定せずにエラーになる
+  #[test]
+  fn test_set_clipboard_timeout_rejects_zero() {
+    let before = get_clipboard_timeout();
+    let err = set_clipboard_timeout(0).unwrap_err();
+    assert_eq!(err.status, Status::InvalidArg);
+    assert_eq!(get_clipboard_timeout(), before);
+  }
+
+  // このプロセスが書き込んでいないセレクションは onlyIfOwner では空にしない
+  #[test]
+  fn test_clear_clipboard_only_if_owner_without_write() {
+    let cleared = clear_clipboard(Some(ClearClipboardOptions {
+      selection: Some("primary".to_string()),
+      only_if_owner: Some(true),
+      retry: None,
+    }));
+    assert!(matches!(cleared, Ok(false)), "{:?}", cleared);
+  }
+
+  // 呼び出しごとの再試行のオプションは、省略した項目を全体のポリシーで補う
+  #[test]
+  fn test_retry_policy_merges_with_global() {
+    assert_eq!(retry_policy(None), None);
+
+    let global = platforms::retry::global_policy();
+    let policy = retry_policy(Some(RetryOptions {
+      attempts: Some(0),
+      backoff_ms: Some(50),
+      deadline_ms: None,
+    }))
+    .unwrap();
+    assert_eq!(policy.attempts, 1);
+    assert_eq!(policy.backoff, Duration::from_millis(50));
+    assert_eq!(policy.deadline, global.deadline);
+  }
+
+  // 実際のファイルを作成してコピーするテスト
+  // 注意: このテストは実際のクリップボードを変更します
+  #[test]
+  fn test_copy_real_files() {
+    // テスト用の一時ファイルを作成
     let mut temp_files = Vec::new();
 
     for i in 0..2 {
@@ -285,7 +2199,7 @@ mod tests {
     }
 
     // ファイルをクリップボードにコピー
-    let result = write_clipboard_file_paths(temp_files.clone());
+    let result = write_clipboard_file_paths(temp_files.clone(), None);
 
     // コピー成功を確認
     assert!(result.is_ok(), "Failed to copy files: {:?}", result);
@@ -323,7 +2237,7 @@ mod tests {
     canonical_paths.sort();
 
     // ファイルパスをクリップボードにコピー
-    let copy_result = write_clipboard_file_paths(test_paths.clone());
+    let copy_result = write_clipboard_file_paths(test_paths.clone(), None);
     assert!(
       copy_result.is_ok(),
       "Failed to copy file paths to clipboard: {:?}",
@@ -342,4 +2256,30 @@ mod tests {
       let _ = std::fs::remove_file(path);
     }
   }
+
+  // セレクション名の変換と不正な名前のエラー
+  #[test]
+  fn test_parse_selection() {
+    assert_eq!(
+      parse_selection(None).unwrap(),
+      platforms::Selection::Clipboard
+    );
+    assert_eq!(
+      parse_selection(Some("primary".to_string())).unwrap(),
+      platforms::Selection::Primary
+    );
+    let err = parse_selection(Some("secondary".to_string())).unwrap_err();
+    assert_eq!(err.status, Status::InvalidArg);
+  }
+
+  // getNativeWindowHandle() の Buffer はネイティブのバイト順の整数
+  #[test]
+  fn test_native_window_id() {
+    assert_eq!(
+      native_window_id(&0x0420_0001u64.to_ne_bytes()).unwrap(),
+      0x0420_0001
+    );
+    assert_eq!(native_window_id(&7u32.to_ne_bytes()).unwrap(), 7);
+    assert!(native_window_id(&[1, 2, 3]).is_err());
+  }
 }
@@ -0,0 +1,452 @@
+// コピーしたフォルダーの中のファイルの展開
+//
+// フォルダーをコピーした場合、アップロードなどにはその中のファイルの一覧が必要になる。
+// コピーされた各パスをたどり、ファイルを各パスからの相対パスとともに返す。
+// 大きなフォルダーでも止められるように、最大数と中止のフラグを確認しながらたどる。
+
+use std::collections::{HashSet, VecDeque};
+use std::fs::{self, Metadata};
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::file_metadata::FileKind;
+use crate::glob::Glob;
+
+/// デフォルトのエントリの最大数
+pub const DEFAULT_MAX_ENTRIES: usize = 10_000;
+
+/// 展開のオプション
+#[derive(Debug, Clone)]
+pub struct ExpandOptions {
+  /// サブフォルダーの中もたどるかどうか（false ならコピーしたフォルダーの直下だけ）
+  pub recursive: bool,
+  /// シンボリックリンクをたどるかどうか（false ならリンク自体をエントリにする）
+  pub follow_symlinks: bool,
+  /// 返すエントリの最大数
+  pub max_entries: usize,
+  /// 空でなければ、いずれかに一致するファイルだけを返す
+  pub include: Vec<Glob>,
+  /// 一致するファイルとフォルダー（の中）を除く
+  pub exclude: Vec<Glob>,
+}
+
+impl Default for ExpandOptions {
+  fn default() -> Self {
+    Self {
+      recursive: true,
+      follow_symlinks: false,
+      max_entries: DEFAULT_MAX_ENTRIES,
+      include: Vec::new(),
+      exclude: Vec::new(),
+    }
+  }
+}
+
+/// 展開したファイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpandedEntry {
+  /// パス
+  pub path: String,
+  /// このファイルを含む、コピーされたパス
+  pub root: String,
+  /// `root` からの相対パス（`/` 区切り）。コピーされたのがファイルの場合はファイル名
+  pub relative_path: String,
+  /// 種類（フォルダーは含めない）
+  pub kind: FileKind,
+  /// サイズ（バイト）
+  pub size: u64,
+}
+
+/// 展開の結果
+#[derive(Debug, Clone, Default)]
+pub struct Expansion {
+  pub entries: Vec<ExpandedEntry>,
+  /// 最大数に達して途中で止めたかどうか
+  pub truncated: bool,
+  /// 読み取れなかったパスのエラー（展開は続ける）
+  pub errors: Vec<String>,
+}
+
+/// コピーされた各パスを展開する
+///
+/// フォルダーは名前順にたどり、フォルダー自体はエントリに含めない。
+/// シンボリックリンクをたどる場合、リンクしたフォルダーはリンクでないフォルダーをすべて
+/// たどった後にたどる。そのため同じフォルダーはリンクでないパスで返り、リンクからしか
+/// たどれないフォルダーのエントリは最後になる。
+/// `cancelled` が true になった場合は `ErrorKind::Interrupted` を返す。
+pub fn expand(
+  roots: &[String],
+  options: &ExpandOptions,
+  cancelled: &AtomicBool,
+) -> Result<Expansion, Error> {
+  let mut walker = Walker {
+    options,
+    cancelled,
+    expansion: Expansion::default(),
+  };
+  for root in roots {
+    if walker.expand_root(root)?.is_break() {
+      break;
+    }
+  }
+  Ok(walker.expansion)
+}
+
+// 続けるか（最大数に達したら止める）
+type Flow = std::ops::ControlFlow<()>;
+
+struct Walker<'a> {
+  options: &'a ExpandOptions,
+  cancelled: &'a AtomicBool,
+  expansion: Expansion,
+}
+
+impl Walker<'_> {
+  fn expand_root(&mut self, root: &str) -> Result<Flow, Error> {
+    let root_path = Path::new(root);
+    let Some((kind, metadata)) = self.stat(root_path) else {
+      return Ok(Flow::Continue(()));
+    };
+    if kind != FileKind::Directory {
+      let name = root_path
+        .file_name()
+        .map_or_else(|| root.to_string(), |n| n.to_string_lossy().into_owned());
+      return Ok(self.push(root, root_path, name, kind, &metadata));
+    }
+
+    // (フォルダー, root からの相対パス) をスタックでたどる
+    let mut stack = vec![(root_path.to_path_buf(), String::new())];
+    // リンクしたフォルダー。スタックが空になってから順にたどる
+    let mut linked = VecDeque::new();
+    // シンボリックリンクをたどる場合に、同じフォルダーを繰り返したどらないようにする
+    let mut visited = HashSet::new();
+    if self.options.follow_symlinks {
+      visited.extend(fs::canonicalize(root_path));
+    }
+
+    loop {
+      let (dir, prefix) = match stack.pop() {
+        Some(next) => next,
+        None => match linked.pop_front() {
+          // リンクでないパスですでにたどったフォルダーは除く
+          Some((dir, prefix)) if fs::canonicalize(&dir).is_ok_and(|c| visited.insert(c)) => {
+            (dir, prefix)
+          }
+          Some(_) => continue,
+          None => break,
+        },
+      };
+      self.check_cancelled()?;
+      let mut children = match fs::read_dir(&dir) {
+        Ok(children) => children.filter_map(Result::ok).collect::<Vec<_>>(),
+        Err(e) => {
+          self
+            .expansion
+            .errors
+            .push(format!("Failed to read directory {}: {}", dir.display(), e));
+          continue;
+        }
+      };
+      children.sort_by_key(|child| child.file_name());
+
+      let mut subdirs = Vec::new();
+      for child in children {
+        self.check_cancelled()?;
+        let path = child.path();
+        let name = child.file_name().to_string_lossy().into_owned();
+        let relative = if prefix.is_empty() {
+          name
+        } else {
+          format!("{}/{}", prefix, name)
+        };
+        if self
+          .options
+          .exclude
+          .iter()
+          .any(|glob| glob.matches(&relative))
+        {
+          continue;
+        }
+        let Some((kind, metadata)) = self.stat(&path) else {
+          continue;
+        };
+
+        if kind == FileKind::Directory {
+          if !self.options.recursive {
+            continue;
+          }
+          let is_link = child.file_type().is_ok_and(|t| t.is_symlink());
+          if is_link {
+            linked.push_back((path, relative));
+          } else if !self.options.follow_symlinks
+            || fs::canonicalize(&path).is_ok_and(|canonical| visited.insert(canonical))
+          {
+            subdirs.push((path, relative));
+          }
+        } else if self.push(root, &path, relative, kind, &metadata).is_break() {
+          return Ok(Flow::Break(()));
+        }
+      }
+      // 名前順にたどるため、逆順に積む
+      stack.extend(subdirs.into_iter().rev());
+    }
+    Ok(Flow::Continue(()))
+  }
+
+  // 種類とメタデータ（シンボリックリンクをたどる場合はリンク先）
+  fn stat(&mut self, path: &Path) -> Option<(FileKind, Metadata)> {
+    let metadata = match fs::symlink_metadata(path) {
+      Ok(metadata) => metadata,
+      Err(e) => {
+        self
+          .expansion
+          .errors
+          .push(format!("Failed to read {}: {}", path.display(), e));
+        return None;
+      }
+    };
+    // リンク先がない場合はリンク自体をエントリにする
+    let metadata = if metadata.file_type().is_symlink() && self.options.follow_symlinks {
+      fs::metadata(path).unwrap_or(metadata)
+    } else {
+      metadata
+    };
+    let kind = if metadata.file_type().is_symlink() {
+      FileKind::Symlink
+    } else if metadata.is_dir() {
+      FileKind::Directory
+    } else if metadata.is_file() {
+      FileKind::File
+    } else {
+      FileKind::Other
+    };
+    Some((kind, metadata))
+  }
+
+  // include に一致すればエントリに加える。最大数に達したら止める
+  fn push(
+    &mut self,
+    root: &str,
+    path: &Path,
+    relative: String,
+    kind: FileKind,
+    metadata: &Metadata,
+  ) -> Flow {
+    let included = self.options.include.is_empty()
+      || self
+        .options
+        .include
+        .iter()
+        .any(|glob| glob.matches(&relative));
+    if !included {
+      return Flow::Continue(());
+    }
+    if self.expansion.entries.len() >= self.options.max_entries {
+      self.expansion.truncated = true;
+      return Flow::Break(());
+    }
+    self.expansion.entries.push(ExpandedEntry {
+      path: path_string(path),
+      root: root.to_string(),
+      relative_path: relative,
+      kind,
+      size: metadata.len(),
+    });
+    Flow::Continue(())
+  }
+
+  fn check_cancelled(&self) -> Result<(), Error> {
+    if self.cancelled.load(Ordering::SeqCst) {
+      Err(Error::new(
+        ErrorKind::Interrupted,
+        "Expanding clipboard paths was cancelled",
+      ))
+    } else {
+      Ok(())
+    }
+  }
+}
+
+fn path_string(path: &Path) -> String {
+  path.to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+
+  // a.txt, b.png, sub/c.txt, sub/deep/d.png, node_modules/x.js を作る
+  fn tree(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!(
+      "clip-filepaths-expand-{}-{}",
+      name,
+      std::process::id()
+    ));
+    let _ = fs::remove_dir_all(&dir);
+    for (file, content) in [
+      ("a.txt", "a"),
+      ("b.png", "bb"),
+      ("sub/c.txt", "ccc"),
+      ("sub/deep/d.png", "dddd"),
+      ("node_modules/x.js", "x"),
+    ] {
+      let path = dir.join(file);
+      fs::create_dir_all(path.parent().unwrap()).unwrap();
+      fs::write(path, content).unwrap();
+    }
+    dir
+  }
+
+  fn relative_paths(expansion: &Expansion) -> Vec<&str> {
+    expansion
+      .entries
+      .iter()
+      .map(|entry| entry.relative_path.as_str())
+      .collect()
+  }
+
+  fn run(roots: &[String], options: &ExpandOptions) -> Expansion {
+    expand(roots, options, &AtomicBool::new(false)).unwrap()
+  }
+
+  // フォルダーは名前順にたどり、ファイルは相対パスとサイズとともに返る
+  #[test]
+  fn test_expand() {
+    let dir = tree("basic");
+    let root = path_string(&dir);
+    let file = path_string(&dir.join("a.txt"));
+
+    let expansion = run(&[root.clone(), file.clone()], &ExpandOptions::default());
+    assert_eq!(
+      relative_paths(&expansion),
+      [
+        "a.txt",
+        "b.png",
+        "node_modules/x.js",
+        "sub/c.txt",
+        "sub/deep/d.png",
+        // コピーされたのがファイルの場合はファイル名
+        "a.txt",
+      ]
+    );
+    assert_eq!(expansion.entries[4].size, 4);
+    assert_eq!(expansion.entries[4].root, root);
+    assert_eq!(expansion.entries[5].root, file);
+    assert!(!expansion.truncated);
+    assert!(expansion.errors.is_empty());
+
+    // 直下だけ
+    let options = ExpandOptions {
+      recursive: false,
+      ..ExpandOptions::default()
+    };
+    assert_eq!(
+      relative_paths(&run(std::slice::from_ref(&root), &options)),
+      ["a.txt", "b.png"]
+    );
+
+    // 存在しないパスはエラーに記録して続ける
+    let missing = path_string(&dir.join("missing"));
+    let expansion = run(&[missing, file], &ExpandOptions::default());
+    assert_eq!(relative_paths(&expansion), ["a.txt"]);
+    assert_eq!(expansion.errors.len(), 1);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  // include / exclude と最大数
+  #[test]
+  fn test_expand_filters_and_cap() {
+    let dir = tree("filters");
+    let root = vec![path_string(&dir)];
+
+    let options = ExpandOptions {
+      include: vec![Glob::new("*.png").unwrap()],
+      exclude: vec![Glob::new("deep").unwrap()],
+      ..ExpandOptions::default()
+    };
+    assert_eq!(relative_paths(&run(&root, &options)), ["b.png"]);
+
+    let options = ExpandOptions {
+      exclude: vec![
+        Glob::new("node_modules").unwrap(),
+        Glob::new("sub/*.txt").unwrap(),
+      ],
+      ..ExpandOptions::default()
+    };
+    assert_eq!(
+      relative_paths(&run(&root, &options)),
+      ["a.txt", "b.png", "sub/deep/d.png"]
+    );
+
+    let options = ExpandOptions {
+      max_entries: 2,
+      ..ExpandOptions::default()
+    };
+    let expansion = run(&root, &options);
+    assert_eq!(relative_paths(&expansion), ["a.txt", "b.png"]);
+    assert!(expansion.truncated);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  // シンボリックリンクはたどらなければリンク自体、たどる場合は循環を避け、
+  // リンクでないパスを優先する
+  #[cfg(unix)]
+  #[test]
+  fn test_expand_symlinks() {
+    let dir = tree("symlinks");
+    let outside = tree("symlinks-outside");
+    std::os::unix::fs::symlink(dir.join("sub"), dir.join("link")).unwrap();
+    std::os::unix::fs::symlink(&dir, dir.join("sub/loop")).unwrap();
+    std::os::unix::fs::symlink(outside.join("sub/deep"), dir.join("ext")).unwrap();
+    let root = vec![path_string(&dir)];
+
+    let expansion = run(&root, &ExpandOptions::default());
+    let links: Vec<&ExpandedEntry> = expansion
+      .entries
+      .iter()
+      .filter(|entry| entry.kind == FileKind::Symlink)
+      .collect();
+    assert_eq!(links.len(), 3);
+    assert_eq!(links[0].relative_path, "ext");
+    assert_eq!(links[1].relative_path, "link");
+
+    let options = ExpandOptions {
+      follow_symlinks: true,
+      ..ExpandOptions::default()
+    };
+    assert_eq!(
+      relative_paths(&run(&root, &options)),
+      [
+        "a.txt",
+        "b.png",
+        "node_modules/x.js",
+        "sub/c.txt",
+        "sub/deep/d.png",
+        // リンクからしかたどれないフォルダーは最後
+        "ext/d.png",
+      ]
+    );
+
+    fs::remove_dir_all(&dir).unwrap();
+    fs::remove_dir_all(&outside).unwrap();
+  }
+
+  // 中止した場合は Interrupted になる
+  #[test]
+  fn test_expand_cancelled() {
+    let dir = tree("cancel");
+    let err = expand(
+      &[path_string(&dir)],
+      &ExpandOptions::default(),
+      &AtomicBool::new(true),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Interrupted);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,330 @@
+// CF_HDROP のデータ（DROPFILES 構造体とファイル名の一覧）のエンコードとデコード
+//
+// Win32 API を使わずにバイト列だけを扱うため、どのプラットフォームでもテストできる。
+// レイアウト（すべてリトルエンディアン）:
+//   0  pFiles: u32  ファイル名の一覧までのオフセット
+//   4  pt.x:   i32  ドロップ位置
+//   8  pt.y:   i32
+//   12 fNC:    i32  pt が非クライアント領域の座標かどうか
+//   16 fWide:  i32  ファイル名が UTF-16 かどうか（0 なら ANSI）
+//   pFiles から NUL 終端のファイル名が続き、空の文字列（NUL がもう一つ）で終わる
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+use std::io::{Error, ErrorKind};
+
+/// DROPFILES 構造体のバイト数
+pub const DROPFILES_SIZE: usize = 20;
+
+/// CF_HDROP のデータの内容
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DropFiles {
+  /// ファイルのパス
+  pub paths: Vec<String>,
+  /// ドロップ位置（`pt`）
+  pub point: (i32, i32),
+  /// `point` が非クライアント領域の座標かどうか（`fNC`）
+  pub non_client: bool,
+  /// ファイル名を UTF-16 で格納するかどうか（`fWide`）
+  pub wide: bool,
+}
+
+impl DropFiles {
+  /// クリップボードへの書き込みに使う、UTF-16 のファイル名の一覧
+  pub fn new(paths: Vec<String>) -> Self {
+    Self {
+      paths,
+      point: (0, 0),
+      non_client: false,
+      wide: true,
+    }
+  }
+}
+
+/// DROPFILES のバイト列を作る
+///
+/// ANSI（`wide: false`）ではコードページに依存しない ASCII のパスだけを受け付ける。
+/// 空のパスや NUL を含むパスは一覧の終端と区別できないためエラーになる。
+pub fn encode(files: &DropFiles) -> Result<Vec<u8>, Error> {
+  let mut data = Vec::with_capacity(DROPFILES_SIZE + files.paths.len() * 64);
+  data.extend_from_slice(&(DROPFILES_SIZE as u32).to_le_bytes());
+  data.extend_from_slice(&files.point.0.to_le_bytes());
+  data.extend_from_slice(&files.point.1.to_le_bytes());
+  data.extend_from_slice(&i32::from(files.non_client).to_le_bytes());
+  data.extend_from_slice(&i32::from(files.wide).to_le_bytes());
+
+  for path in &files.paths {
+    if path.is_empty() || path.contains('\0') {
+      return Err(Error::new(
+        ErrorKind::InvalidInput,
+        format!("Path cannot be stored in CF_HDROP: {:?}", path),
+      ));
+    }
+    if files.wide {
+      for unit in path.encode_utf16().chain([0]) {
+        data.extend_from_slice(&unit.to_le_bytes());
+      }
+    } else {
+      if !path.is_ascii() {
+        return Err(Error::new(
+          ErrorKind::InvalidInput,
+          format!("ANSI CF_HDROP data can only contain ASCII paths: {}", path),
+        ));
+      }
+      data.extend_from_slice(path.as_bytes());
+      data.push(0);
+    }
+  }
+
+  // 一覧の終端。空の一覧でも NUL を 2 つ置く
+  let terminator = if files.paths.is_empty() { 2 } else { 1 };
+  let unit = if files.wide { 2 } else { 1 };
+  data.resize(data.len() + terminator * unit, 0);
+  Ok(data)
+}
+
+/// DROPFILES のバイト列を読み取る
+///
+/// ANSI のファイル名はコードページが分からないため、ASCII 以外のバイトを U+FFFD にする。
+/// コードページで変換する場合は [`decode_with`] を使う。
+#[cfg_attr(target_os = "windows", allow(dead_code))]
+pub fn decode(data: &[u8]) -> Result<DropFiles, Error> {
+  decode_with(data, |bytes| {
+    bytes
+      .iter()
+      .map(|&b| if b.is_ascii() { b as char } else { '\u{FFFD}' })
+      .collect()
+  })
+}
+
+/// DROPFILES のバイト列を読み取り、ANSI のファイル名を `ansi` で文字列にする
+///
+/// オフセットがデータの範囲外の場合や、一覧が終端されていない場合は
+/// `ErrorKind::InvalidData` を返す。
+pub fn decode_with(data: &[u8], ansi: impl Fn(&[u8]) -> String) -> Result<DropFiles, Error> {
+  if data.len() < DROPFILES_SIZE {
+    return Err(invalid_data(format!(
+      "DROPFILES data is too short: {} bytes",
+      data.len()
+    )));
+  }
+  let u32_at = |offset: usize| {
+    u32::from_le_bytes([
+      data[offset],
+      data[offset + 1],
+      data[offset + 2],
+      data[offset + 3],
+    ])
+  };
+  let offset = u32_at(0) as usize;
+  let point = (u32_at(4) as i32, u32_at(8) as i32);
+  let non_client = u32_at(12) != 0;
+  let wide = u32_at(16) != 0;
+
+  if offset < DROPFILES_SIZE || offset > data.len() {
+    return Err(invalid_data(format!(
+      "DROPFILES file list offset {} is outside the data ({} bytes)",
+      offset,
+      data.len()
+    )));
+  }
+  let list = &data[offset..];
+
+  let paths = if wide {
+    let units: Vec<u16> = list
+      .chunks_exact(2)
+      .map(|c| u16::from_le_bytes([c[0], c[1]]))
+      .collect();
+    split_list(&units)?
+      .into_iter()
+      .map(String::from_utf16_lossy)
+      .collect()
+  } else {
+    split_list(list)?.into_iter().map(ansi).collect()
+  };
+
+  Ok(DropFiles {
+    paths,
+    point,
+    non_client,
+    wide,
+  })
+}
+
+// NUL 区切りの一覧を空の要素（終端）まで分割する
+fn split_list<T: Copy + Default + PartialEq>(items: &[T]) -> Result<Vec<&[T]>, Error> {
+  let mut entries = Vec::new();
+  let mut rest = items;
+  loop {
+    let Some(end) = rest.iter().position(|&c| c == T::default()) else {
+      return Err(invalid_data(
+        "DROPFILES file list is not NUL-terminated".to_string(),
+      ));
+    };
+    if end == 0 {
+      return Ok(entries);
+    }
+    entries.push(&rest[..end]);
+    rest = &rest[end + 1..];
+  }
+}
+
+fn invalid_data(message: String) -> Error {
+  Error::new(ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn paths(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  // UTF-16 のファイル名は pt / fNC を含めてそのまま読み戻せる
+  #[test]
+  fn test_wide_round_trip() {
+    let files = DropFiles {
+      paths: paths(&[r"C:\Users\me\a b.txt", r"\\server\share\データ.txt"]),
+      point: (-10, 250),
+      non_client: true,
+      wide: true,
+    };
+    let data = encode(&files).unwrap();
+    assert_eq!(&data[..4], &20u32.to_le_bytes());
+    assert_eq!(&data[data.len() - 4..], &[0, 0, 0, 0]);
+    assert_eq!(decode(&data).unwrap(), files);
+  }
+
+  // ANSI のファイル名は 1 バイト単位で格納される
+  #[test]
+  fn test_ansi_round_trip() {
+    let files = DropFiles {
+      paths: paths(&[r"C:\a.txt", r"C:\b.txt"]),
+      wide: false,
+      ..DropFiles::new(Vec::new())
+    };
+    let data = encode(&files).unwrap();
+    assert_eq!(&data[DROPFILES_SIZE..], b"C:\\a.txt\0C:\\b.txt\0\0");
+    assert_eq!(decode(&data).unwrap(), files);
+  }
+
+  // ANSI の ASCII 以外のバイトは decode_with の変換関数で読む
+  #[test]
+  fn test_ansi_code_page() {
+    let mut data = encode(&DropFiles {
+      wide: false,
+      ..DropFiles::new(Vec::new())
+    })
+    .unwrap();
+    data.truncate(DROPFILES_SIZE);
+    data.extend_from_slice(b"C:\\caf\xe9.txt\0\0");
+
+    assert_eq!(
+      decode(&data).unwrap().paths,
+      paths(&["C:\\caf\u{FFFD}.txt"])
+    );
+    // Windows-1252 / Latin-1 として読む
+    let latin1 = decode_with(&data, |bytes| bytes.iter().map(|&b| b as char).collect()).unwrap();
+    assert_eq!(latin1.paths, paths(&["C:\\café.txt"]));
+  }
+
+  // 空の一覧も読み書きできる
+  #[test]
+  fn test_empty_list() {
+    let files = DropFiles::new(Vec::new());
+    assert_eq!(decode(&encode(&files).unwrap()).unwrap(), files);
+  }
+
+  // 格納できないパス
+  #[test]
+  fn test_encode_rejects_unrepresentable_paths() {
+    for bad in [vec![String::new()], paths(&["a\0b"])] {
+      let err = encode(&DropFiles::new(bad)).unwrap_err();
+      assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+    let ansi = DropFiles {
+      wide: false,
+      ..DropFiles::new(paths(&["C:\\データ"]))
+    };
+    assert_eq!(encode(&ansi).unwrap_err().kind(), ErrorKind::InvalidInput);
+  }
+
+  // 範囲外のオフセットや終端のない一覧はエラーになる
+  #[test]
+  fn test_decode_rejects_malformed_data() {
+    let valid = encode(&DropFiles::new(paths(&[r"C:\a.txt"]))).unwrap();
+
+    let mut cases: Vec<Vec<u8>> = vec![Vec::new(), valid[..DROPFILES_SIZE - 1].to_vec()];
+    for offset in [0u32, 19, valid.len() as u32 + 1, u32::MAX] {
+      let mut data = valid.clone();
+      data[..4].copy_from_slice(&offset.to_le_bytes());
+      cases.push(data);
+    }
+    // 終端の NUL がない / 最後のファイル名の途中で切れている
+    cases.push(valid[..valid.len() - 2].to_vec());
+    cases.push(valid[..valid.len() - 6].to_vec());
+    // オフセットがデータの末尾（一覧が空で終端もない）
+    let mut at_end = valid[..DROPFILES_SIZE].to_vec();
+    at_end[..4].copy_from_slice(&(DROPFILES_SIZE as u32).to_le_bytes());
+    cases.push(at_end);
+
+    for data in cases {
+      let err = decode(&data).unwrap_err();
+      assert_eq!(err.kind(), ErrorKind::InvalidData, "{:?}", data);
+    }
+  }
+
+  // テスト用の疑似乱数（xorshift64）
+  struct Rng(u64);
+
+  impl Rng {
+    fn next(&mut self) -> u64 {
+      self.0 ^= self.0 << 13;
+      self.0 ^= self.0 >> 7;
+      self.0 ^= self.0 << 17;
+      self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+      (self.next() % n as u64) as usize
+    }
+  }
+
+  // 任意のバイト列でパニックせず、読み取れたデータは書き戻しても同じ内容になる
+  #[test]
+  fn test_fuzz_decode() {
+    let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+    let seed = encode(&DropFiles::new(paths(&[r"C:\a.txt", r"D:\dir\b"]))).unwrap();
+
+    for i in 0..20_000 {
+      let data: Vec<u8> = if i % 2 == 0 {
+        // 正しいデータの一部を書き換える・切り詰める
+        let mut data = seed.clone();
+        for _ in 0..=rng.below(4) {
+          let at = rng.below(data.len());
+          data[at] = rng.next() as u8;
+        }
+        data.truncate(rng.below(seed.len() + 1));
+        data
+      } else {
+        // ランダムなバイト列（ヘッダーはそれらしくする）
+        let len = rng.below(80);
+        let mut data: Vec<u8> = (0..len).map(|_| rng.next() as u8 & 0x41).collect();
+        if len >= DROPFILES_SIZE {
+          data[..4].copy_from_slice(&(rng.below(len + 4) as u32).to_le_bytes());
+        }
+        data
+      };
+
+      if let Ok(files) = decode(&data) {
+        assert!(files.paths.iter().all(|p| !p.is_empty()));
+        // 不正な UTF-16 は U+FFFD に置き換わるため、書き戻したものを読み直して比べる
+        if files.wide || files.paths.iter().all(|p| p.is_ascii()) {
+          let encoded = encode(&files).unwrap();
+          assert_eq!(decode(&encoded).unwrap(), files);
+        }
+      }
+    }
+  }
+}
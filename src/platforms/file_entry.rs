@@ -0,0 +1,110 @@
+// クリップボードから読み取ったファイルの URI とパス
+//
+// text/uri-list やペーストボードの URL には file:// 以外の URI（GVFS の smb://、sftp://、
+// trash:// など）も入るため、URI と、ローカルで開けるパス（分かる場合）の組として扱う。
+// ファイルシステムにはアクセスせず、文字列だけで処理するため、どのプラットフォームでもテストできる。
+
+use crate::platforms::text_representation::file_uri;
+use crate::platforms::windows_path::percent_decode;
+
+/// クリップボードから読み取った 1 つのファイル
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileEntry {
+  /// URI
+  pub uri: String,
+  /// ローカルで開けるパス（file:// のパスや GVFS の FUSE のパス）。分からない場合は None
+  pub path: Option<String>,
+  /// URI のスキーム（小文字）
+  pub scheme: String,
+  /// このマシンのファイル（file:// の URI）かどうか
+  pub is_local: bool,
+}
+
+impl FileEntry {
+  /// URI から作る。パスは、このマシンの file:// の URI の場合だけ設定する
+  pub fn from_uri(uri: &str) -> Self {
+    let scheme = uri
+      .split_once(':')
+      .map_or("", |(scheme, _)| scheme)
+      .to_ascii_lowercase();
+    let path = if scheme == "file" {
+      local_file_path(uri)
+    } else {
+      None
+    };
+    Self {
+      uri: uri.to_string(),
+      is_local: path.is_some(),
+      path,
+      scheme,
+    }
+  }
+
+  /// ローカルの絶対パスから作る
+  pub fn from_path(path: &str) -> Self {
+    Self {
+      uri: file_uri(path),
+      path: Some(path.to_string()),
+      scheme: "file".to_string(),
+      is_local: true,
+    }
+  }
+}
+
+// ホストが空か `localhost` の file URI（`file:///path`、`file:/path`）をパスにする
+fn local_file_path(uri: &str) -> Option<String> {
+  let rest = &uri["file:".len()..];
+  let path = match rest.strip_prefix("//") {
+    Some(authority_and_path) => {
+      let path_start = authority_and_path.find('/')?;
+      let host = &authority_and_path[..path_start];
+      if !host.is_empty() && !host.eq_ignore_ascii_case("localhost") {
+        return None;
+      }
+      &authority_and_path[path_start..]
+    }
+    None if rest.starts_with('/') => rest,
+    None => return None,
+  };
+  Some(percent_decode(path))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // URI のスキームとパス（file:// 以外のパスは分からない）
+  #[test]
+  fn test_from_uri() {
+    let cases = [
+      ("file:///tmp/a%20b.txt", "file", Some("/tmp/a b.txt")),
+      ("file:///tmp/a b.txt", "file", Some("/tmp/a b.txt")),
+      ("FILE://localhost/etc/hosts", "file", Some("/etc/hosts")),
+      ("file:/tmp/x", "file", Some("/tmp/x")),
+      ("file:///tmp/%E6%97%A5.txt", "file", Some("/tmp/日.txt")),
+      // 別のホストのファイル
+      ("file://server/share/a", "file", None),
+      ("smb://server/share/a.txt", "smb", None),
+      ("sftp://me@host/home/me", "sftp", None),
+      ("trash:///a.txt", "trash", None),
+      ("recent:///", "recent", None),
+    ];
+    for (uri, scheme, path) in cases {
+      let entry = FileEntry::from_uri(uri);
+      assert_eq!(entry.uri, uri);
+      assert_eq!(entry.scheme, scheme, "{}", uri);
+      assert_eq!(entry.path.as_deref(), path, "{}", uri);
+      assert_eq!(entry.is_local, path.is_some(), "{}", uri);
+    }
+  }
+
+  // ローカルのパスは file URI になる
+  #[test]
+  fn test_from_path() {
+    let entry = FileEntry::from_path("/tmp/a b.txt");
+    assert_eq!(entry.uri, "file:///tmp/a%20b.txt");
+    assert_eq!(entry.path.as_deref(), Some("/tmp/a b.txt"));
+    assert_eq!(entry.scheme, "file");
+    assert!(entry.is_local);
+  }
+}
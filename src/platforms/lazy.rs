@@ -0,0 +1,162 @@
+// 貼り付け先が要求したときにデータを生成する書き込み（遅延レンダリング）の共通処理
+//
+// Windows の WM_RENDERFORMAT や macOS の NSPasteboardItemDataProvider は
+// フォーマットごとに一度だけデータを要求するが、X11 では貼り付けのたびに
+// SelectionRequest が届くため、生成したデータを保持しておく。
+
+use std::io::Error;
+use std::sync::Arc;
+
+/// 要求されたフォーマットのデータを生成する関数
+///
+/// そのフォーマットを提供できない場合は `Ok(None)` を返す。
+pub type Provider = Arc<dyn Fn(&str) -> Result<Option<Vec<u8>>, Error> + Send + Sync>;
+
+// フォーマットのデータの生成状態
+enum Rendered {
+  Pending,
+  Ready(Arc<[u8]>),
+  Unavailable,
+}
+
+/// 提供するフォーマットと、そのデータ
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct Contents {
+  entries: Vec<(String, Rendered)>,
+  provider: Option<Provider>,
+}
+
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+impl Contents {
+  /// 要求されたときに `provider` でデータを生成する
+  pub fn deferred(formats: &[String], provider: Provider) -> Self {
+    Self {
+      entries: formats
+        .iter()
+        .map(|format| (format.clone(), Rendered::Pending))
+        .collect(),
+      provider: Some(provider),
+    }
+  }
+
+  /// 提供するフォーマット名
+  pub fn formats(&self) -> impl Iterator<Item = &str> {
+    self.entries.iter().map(|(format, _)| format.as_str())
+  }
+
+  /// `format` のデータを返す。提供しないフォーマットの場合は `Ok(None)`
+  ///
+  /// まだ生成していなければ provider を呼び出す。provider がエラーを返した場合は
+  /// データを保持せずにそのエラーを返し、次の要求で再び呼び出す。
+  pub fn get(&mut self, format: &str) -> Result<Option<Arc<[u8]>>, Error> {
+    let Some((_, rendered)) = self.entries.iter_mut().find(|(f, _)| f == format) else {
+      return Ok(None);
+    };
+    if let Rendered::Pending = rendered {
+      let Some(provider) = self.provider.as_ref() else {
+        return Ok(None);
+      };
+      *rendered = match provider(format)? {
+        Some(data) => Rendered::Ready(Arc::from(data)),
+        None => Rendered::Unavailable,
+      };
+    }
+    match rendered {
+      Rendered::Ready(data) => Ok(Some(Arc::clone(data))),
+      _ => Ok(None),
+    }
+  }
+}
+
+impl From<Vec<(String, Vec<u8>)>> for Contents {
+  fn from(contents: Vec<(String, Vec<u8>)>) -> Self {
+    Self {
+      entries: contents
+        .into_iter()
+        .map(|(format, data)| (format, Rendered::Ready(Arc::from(data))))
+        .collect(),
+      provider: None,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::ErrorKind;
+  use std::sync::atomic::{AtomicUsize, Ordering};
+
+  fn formats(names: &[&str]) -> Vec<String> {
+    names.iter().map(|s| s.to_string()).collect()
+  }
+
+  // provider は要求されたフォーマットについて一度だけ呼び出される
+  #[test]
+  fn test_deferred_renders_once_on_demand() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider: Provider = {
+      let calls = Arc::clone(&calls);
+      Arc::new(move |format| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Some(format.as_bytes().to_vec()))
+      })
+    };
+    let mut contents = Contents::deferred(&formats(&["image/png", "text/plain"]), provider);
+    assert_eq!(calls.load(Ordering::SeqCst), 0);
+
+    assert_eq!(
+      contents.get("text/plain").unwrap().as_deref(),
+      Some(&b"text/plain"[..])
+    );
+    assert_eq!(
+      contents.get("text/plain").unwrap().as_deref(),
+      Some(&b"text/plain"[..])
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // 提供していないフォーマットでは provider を呼び出さない
+    assert!(contents.get("application/zip").unwrap().is_none());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+  }
+
+  // None は保持され、エラーは次の要求で再試行される
+  #[test]
+  fn test_deferred_unavailable_and_error() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider: Provider = {
+      let calls = Arc::clone(&calls);
+      Arc::new(move |format| {
+        let n = calls.fetch_add(1, Ordering::SeqCst);
+        match format {
+          "a" => Ok(None),
+          _ if n < 2 => Err(Error::new(ErrorKind::TimedOut, "timed out")),
+          _ => Ok(Some(vec![1, 2, 3])),
+        }
+      })
+    };
+    let mut contents = Contents::deferred(&formats(&["a", "b"]), provider);
+
+    assert!(contents.get("a").unwrap().is_none());
+    assert!(contents.get("a").unwrap().is_none());
+    assert_eq!(contents.get("b").unwrap_err().kind(), ErrorKind::TimedOut);
+    assert_eq!(
+      contents.get("b").unwrap().as_deref(),
+      Some(&[1u8, 2, 3][..])
+    );
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+  }
+
+  // 書き込み時に渡したデータはそのまま返される
+  #[test]
+  fn test_ready_contents() {
+    let mut contents = Contents::from(vec![("text/uri-list".to_string(), b"file:///a".to_vec())]);
+    assert_eq!(
+      contents.formats().collect::<Vec<_>>(),
+      vec!["text/uri-list"]
+    );
+    assert_eq!(
+      contents.get("text/uri-list").unwrap().as_deref(),
+      Some(&b"file:///a"[..])
+    );
+  }
+}
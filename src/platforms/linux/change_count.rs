@@ -0,0 +1,164 @@
+// セレクションの変更回数を数える実装
+//
+// XFIXES 拡張が使える場合は、セレクションの所有者が変わるたびに届く
+// XFixesSelectionNotify イベントを数える。イベントは接続に溜まるため、
+// 値を問い合わせたときにまとめて読み出せばよく、監視用のスレッドは不要。
+// XFIXES がない場合は、問い合わせのたびに所有期間を比較して変化を数える。
+
+use std::io::Error;
+use std::os::raw::c_int;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use x11::xlib;
+
+use super::helper::Selection;
+use super::x11_selection::{owner_change, XConnection};
+
+const SELECTIONS: [Selection; 2] = [Selection::Clipboard, Selection::Primary];
+
+static TRACKER: Mutex<Option<Tracker>> = Mutex::new(None);
+
+/// セレクションごとの変更回数
+///
+/// X11 に依存しない部分を分けておき、テストできるようにしている。
+#[derive(Debug, Default)]
+struct ChangeCounter {
+  counts: [u64; 2],
+  // XFIXES がない場合に、前回観測した所有期間
+  epochs: [Option<u64>; 2],
+}
+
+impl ChangeCounter {
+  fn index(selection: Selection) -> usize {
+    match selection {
+      Selection::Clipboard => 0,
+      Selection::Primary => 1,
+    }
+  }
+
+  fn count(&self, selection: Selection) -> u64 {
+    self.counts[Self::index(selection)]
+  }
+
+  // 所有者の変更イベントを 1 回分記録する
+  fn record_change(&mut self, selection: Selection) {
+    self.counts[Self::index(selection)] += 1;
+  }
+
+  // 観測した所有期間が前回と異なれば変更として記録する
+  // 初回の観測は基準値とするだけで、変更としては数えない
+  fn observe_epoch(&mut self, selection: Selection, epoch: u64) {
+    let index = Self::index(selection);
+    if self.epochs[index].is_some_and(|previous| previous != epoch) {
+      self.counts[index] += 1;
+    }
+    self.epochs[index] = Some(epoch);
+  }
+}
+
+// 変更の監視に使う X サーバーへの接続
+struct Tracker {
+  conn: XConnection,
+  // XFIXES のイベント番号の基準値。XFIXES がない場合は None
+  xfixes_event_base: Option<c_int>,
+  counter: ChangeCounter,
+}
+
+impl Tracker {
+  fn open() -> Result<Self, Error> {
+    let conn = XConnection::open()?;
+    let xfixes_event_base = conn.watch_owner_changes(&SELECTIONS);
+    Ok(Self {
+      conn,
+      xfixes_event_base,
+      counter: ChangeCounter::default(),
+    })
+  }
+
+  fn change_count(&mut self, selection: Selection) -> Result<u64, Error> {
+    match self.xfixes_event_base {
+      Some(event_base) => self.drain_xfixes_events(event_base),
+      None => {
+        let epoch = self.conn.selection_epoch(selection)?;
+        self.counter.observe_epoch(selection, epoch);
+      }
+    }
+    Ok(self.counter.count(selection))
+  }
+
+  // 接続に溜まっている XFixesSelectionNotify イベントを読み出して数える
+  fn drain_xfixes_events(&mut self, event_base: c_int) {
+    let atoms = SELECTIONS.map(|s| (s, self.conn.selection_atom(s)));
+    // サーバーとの往復で、問い合わせの直前の変更（このプロセスの書き込みも含む）の
+    // イベントまで受け取ってから数える
+    unsafe {
+      xlib::XSync(self.conn.display(), xlib::False);
+    }
+    // すでに届いているイベントだけを読み出し、待たない
+    while let Some(event) = self.conn.next_event(Instant::now()) {
+      let Some((selection_atom, _)) = owner_change(&event, event_base) else {
+        continue;
+      };
+      if let Some((selection, _)) = atoms.iter().find(|(_, atom)| *atom == selection_atom) {
+        self.counter.record_change(*selection);
+      }
+    }
+  }
+}
+
+/// セレクションが変更されるたびに増加する値を取得する
+///
+/// 値はこのプロセスで最初に問い合わせた時点を 0 とする。
+pub fn change_count(selection: Selection) -> Result<u64, Error> {
+  let mut tracker = TRACKER.lock().unwrap_or_else(|e| e.into_inner());
+  if tracker.is_none() {
+    *tracker = Some(Tracker::open()?);
+  }
+
+  tracker
+    .as_mut()
+    .expect("tracker is initialized")
+    .change_count(selection)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // イベントはセレクションごとに数えられる
+  #[test]
+  fn test_record_change_per_selection() {
+    let mut counter = ChangeCounter::default();
+    counter.record_change(Selection::Clipboard);
+    counter.record_change(Selection::Clipboard);
+    counter.record_change(Selection::Primary);
+    assert_eq!(counter.count(Selection::Clipboard), 2);
+    assert_eq!(counter.count(Selection::Primary), 1);
+  }
+
+  // 所有期間が変わったときだけ数え、初回の観測は数えない
+  #[test]
+  fn test_observe_epoch() {
+    let mut counter = ChangeCounter::default();
+    counter.observe_epoch(Selection::Clipboard, 10);
+    assert_eq!(counter.count(Selection::Clipboard), 0);
+    counter.observe_epoch(Selection::Clipboard, 10);
+    assert_eq!(counter.count(Selection::Clipboard), 0);
+    counter.observe_epoch(Selection::Clipboard, 11);
+    counter.observe_epoch(Selection::Clipboard, 0);
+    assert_eq!(counter.count(Selection::Clipboard), 2);
+    assert_eq!(counter.count(Selection::Primary), 0);
+  }
+
+  // X サーバーがある環境では変更回数を取得できる
+  #[test]
+  fn test_change_count() {
+    if std::env::var("DISPLAY").map_or(true, |d| d.is_empty()) {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let first = change_count(Selection::Clipboard).unwrap();
+    assert!(change_count(Selection::Clipboard).unwrap() >= first);
+  }
+}
@@ -0,0 +1,266 @@
+// GVFS のマウントの FUSE のパス
+//
+// Nautilus などで smb:// や sftp:// の場所からコピーすると、URI のまま text/uri-list に入る。
+// gvfsd-fuse が動いていれば、各マウントは `$XDG_RUNTIME_DIR/gvfs/<マウント名>` として
+// 開けるため、URI をそのパスにする。マウント名は `smb-share:server=host,share=docs` や
+// `sftp:host=example.com,user=me` のような、種類と `キー=値`（値は %XX でエスケープ）の並び。
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::platforms::file_entry::FileEntry;
+use crate::platforms::windows_path::percent_decode;
+
+/// GVFS の FUSE のマウントの一覧
+#[derive(Debug, Default)]
+pub struct GvfsMounts {
+  root: PathBuf,
+  names: Vec<String>,
+}
+
+impl GvfsMounts {
+  /// `$XDG_RUNTIME_DIR/gvfs` のマウントを調べる（gvfsd-fuse が動いていない場合は空）
+  pub fn discover() -> Self {
+    let Some(runtime_dir) = env::var_os("XDG_RUNTIME_DIR") else {
+      return Self::default();
+    };
+    let root = Path::new(&runtime_dir).join("gvfs");
+    let names = fs::read_dir(&root)
+      .map(|entries| {
+        entries
+          .filter_map(Result::ok)
+          .filter_map(|entry| entry.file_name().into_string().ok())
+          .collect()
+      })
+      .unwrap_or_default();
+    Self { root, names }
+  }
+
+  /// URI から作ったエントリ（file:// 以外は、FUSE のパスにできればパスを設定する）
+  pub fn entry(&self, uri: &str) -> FileEntry {
+    let mut entry = FileEntry::from_uri(uri);
+    if entry.path.is_none() && entry.scheme != "file" {
+      entry.path = self.resolve(uri);
+    }
+    entry
+  }
+
+  /// URI に対応する FUSE のパス。対応するマウントがない場合や、パスに `..` などを含む場合は None
+  pub fn resolve(&self, uri: &str) -> Option<String> {
+    let uri = RemoteUri::parse(uri)?;
+    self.names.iter().find_map(|name| {
+      let rest = uri.path_in(&Mount::parse(name)?)?;
+      let mut path = self.root.join(name);
+      path.extend(rest);
+      Some(path.to_string_lossy().into_owned())
+    })
+  }
+}
+
+// `scheme://user@host:port/path` の各部分（パスの要素はデコード済み）
+struct RemoteUri {
+  scheme: String,
+  user: Option<String>,
+  host: String,
+  port: Option<String>,
+  components: Vec<String>,
+}
+
+impl RemoteUri {
+  fn parse(uri: &str) -> Option<Self> {
+    let (scheme, rest) = uri.split_once("://")?;
+    let (authority, path) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+    let (user, host_port) = match authority.rsplit_once('@') {
+      Some((user, host_port)) => (Some(user), host_port),
+      None => (None, authority),
+    };
+    let components: Vec<String> = path
+      .split('/')
+      .filter(|c| !c.is_empty())
+      .map(percent_decode)
+      .collect();
+    // デコード後の `.` / `..` や `/`・NUL を含む要素はマウントの外を指しうるため、パスにしない
+    let unsafe_component = |c: &String| c == "." || c == ".." || c.contains(['/', '\0']);
+    if components.iter().any(unsafe_component) {
+      return None;
+    }
+
+    let (host, port) = match host_port.rsplit_once(':') {
+      Some((host, port)) if !port.is_empty() && port.bytes().all(|b| b.is_ascii_digit()) => {
+        (host, Some(port.to_string()))
+      }
+      _ => (host_port, None),
+    };
+    Some(Self {
+      scheme: scheme.to_ascii_lowercase(),
+      // smb の `DOMAIN;user` はユーザー名だけを比べる
+      user: user.map(|user| percent_decode(user.rsplit(';').next().unwrap_or(user))),
+      host: percent_decode(host),
+      port,
+      components,
+    })
+  }
+
+  // `mount` のマウントの中にあれば、マウントのディレクトリからのパスの要素を返す
+  fn path_in<'a>(&'a self, mount: &Mount) -> Option<&'a [String]> {
+    // URI のスキームとマウントの種類、ホストのキー、パスの先頭に含まれる共有名のキー
+    let (kind, host_key, share_key) = match self.scheme.as_str() {
+      "smb" => ("smb-share", "server", Some("share")),
+      "afp" => ("afp-volume", "host", Some("volume")),
+      "davs" => ("dav", "host", None),
+      scheme => (scheme, "host", None),
+    };
+    if mount.kind != kind
+      || !mount
+        .get(host_key)
+        .is_some_and(|host| host.eq_ignore_ascii_case(&self.host))
+      || mount.get("user") != self.user.as_deref()
+      || mount.get("port") != self.port.as_deref()
+    {
+      return None;
+    }
+    if kind == "dav" && (self.scheme == "davs") != (mount.get("ssl") == Some("true")) {
+      return None;
+    }
+
+    let mut rest = &self.components[..];
+    if let Some(key) = share_key {
+      let share = rest.first()?;
+      if !mount.get(key)?.eq_ignore_ascii_case(share) {
+        return None;
+      }
+      rest = &rest[1..];
+    }
+    // WebDAV などはサーバーの一部のディレクトリだけをマウントする
+    if let Some(prefix) = mount.get("prefix") {
+      let prefix: Vec<&str> = prefix.split('/').filter(|c| !c.is_empty()).collect();
+      if rest.len() < prefix.len() || rest.iter().zip(&prefix).any(|(a, b)| a != b) {
+        return None;
+      }
+      rest = &rest[prefix.len()..];
+    }
+    Some(rest)
+  }
+}
+
+// マウント名（`種類:キー=値,キー=値`）
+struct Mount {
+  kind: String,
+  keys: Vec<(String, String)>,
+}
+
+impl Mount {
+  fn parse(name: &str) -> Option<Self> {
+    let (kind, keys) = name.split_once(':')?;
+    Some(Self {
+      kind: kind.to_string(),
+      keys: keys
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .map(|(key, value)| (key.to_string(), percent_decode(value)))
+        .collect(),
+    })
+  }
+
+  fn get(&self, key: &str) -> Option<&str> {
+    self
+      .keys
+      .iter()
+      .find(|(k, _)| k == key)
+      .map(|(_, value)| value.as_str())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn mounts(names: &[&str]) -> GvfsMounts {
+    GvfsMounts {
+      root: PathBuf::from("/run/user/1000/gvfs"),
+      names: names.iter().map(|name| name.to_string()).collect(),
+    }
+  }
+
+  // マウントの種類ごとに URI を FUSE のパスにする
+  #[test]
+  fn test_resolve() {
+    let gvfs = mounts(&[
+      "smb-share:server=nas.local,share=docs",
+      "sftp:host=example.com,user=me",
+      "sftp:host=example.com,port=2222,user=me",
+      "dav:host=cloud.example.com,ssl=true,user=me,prefix=%2Fremote.php%2Fwebdav",
+      "afp-volume:host=mac.local,user=me,volume=Share",
+    ]);
+    let cases = [
+      (
+        "smb://nas.local/docs/report%202024.pdf",
+        Some("/run/user/1000/gvfs/smb-share:server=nas.local,share=docs/report 2024.pdf"),
+      ),
+      (
+        "smb://NAS.local/Docs",
+        Some("/run/user/1000/gvfs/smb-share:server=nas.local,share=docs"),
+      ),
+      (
+        "sftp://me@example.com/home/me/a.txt",
+        Some("/run/user/1000/gvfs/sftp:host=example.com,user=me/home/me/a.txt"),
+      ),
+      (
+        "sftp://me@example.com:2222/srv/b",
+        Some("/run/user/1000/gvfs/sftp:host=example.com,port=2222,user=me/srv/b"),
+      ),
+      (
+        "davs://me@cloud.example.com/remote.php/webdav/notes.md",
+        Some(
+          "/run/user/1000/gvfs/dav:host=cloud.example.com,ssl=true,user=me,prefix=%2Fremote.php%2Fwebdav/notes.md",
+        ),
+      ),
+      (
+        "afp://me@mac.local/Share/x",
+        Some("/run/user/1000/gvfs/afp-volume:host=mac.local,user=me,volume=Share/x"),
+      ),
+      // マウントされていない共有・ユーザー・ポート・スキーム
+      ("smb://nas.local/photos/a.jpg", None),
+      ("sftp://other@example.com/home/other", None),
+      ("sftp://me@example.com:22/home/me", None),
+      ("dav://me@cloud.example.com/remote.php/webdav/a", None),
+      ("davs://me@cloud.example.com/other/a", None),
+      ("trash:///a.txt", None),
+      // マウントの外を指す要素（エンコードされたものを含む）は解決しない
+      ("sftp://me@example.com/%2e%2e/%2e%2e/etc/passwd", None),
+      ("sftp://me@example.com/home/../../etc/passwd", None),
+      ("sftp://me@example.com/home/./me", None),
+      ("smb://nas.local/docs/a%2F..%2F..%2Fb", None),
+      ("smb://nas.local/docs/a%00b", None),
+    ];
+    for (uri, expected) in cases {
+      assert_eq!(gvfs.resolve(uri).as_deref(), expected, "{}", uri);
+    }
+  }
+
+  // file:// はそのまま、それ以外は解決できればパスを設定する（ローカルのファイルではない）
+  #[test]
+  fn test_entry() {
+    let gvfs = mounts(&["smb-share:server=nas,share=docs"]);
+
+    let entry = gvfs.entry("smb://nas/docs/a.txt");
+    assert_eq!(entry.scheme, "smb");
+    assert_eq!(
+      entry.path.as_deref(),
+      Some("/run/user/1000/gvfs/smb-share:server=nas,share=docs/a.txt")
+    );
+    assert!(!entry.is_local);
+
+    let entry = gvfs.entry("file:///tmp/a.txt");
+    assert_eq!(entry.path.as_deref(), Some("/tmp/a.txt"));
+    assert!(entry.is_local);
+
+    let entry = gvfs.entry("recent:///a.txt");
+    assert_eq!(entry.path, None);
+    assert_eq!(
+      GvfsMounts::default().entry("smb://nas/docs/a.txt").path,
+      None
+    );
+  }
+}
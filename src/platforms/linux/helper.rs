@@ -0,0 +1,884 @@
+// クリップボード操作を外部コマンド（xclip / xsel / wl-clipboard）に委譲する実装
+
+use std::env;
+use std::ffi::OsStr;
+use std::io::{Error, ErrorKind, Read, Write};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::platforms::stream::{Sink, CHUNK_SIZE};
+use crate::platforms::timeout;
+
+// 子プロセスの終了を確認する間隔
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+// セレクションを保持するコマンドが起動直後に失敗しないか確認する時間
+const HOLDER_STARTUP: Duration = Duration::from_millis(100);
+
+pub use crate::platforms::Selection;
+
+/// クリップボード操作に使うヘルパーコマンド
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HelperTool {
+  Xclip,
+  Xsel,
+  /// `wl-copy` / `wl-paste`
+  WlClipboard,
+}
+
+impl HelperTool {
+  /// 対応しているすべてのヘルパー
+  pub const ALL: [HelperTool; 3] = [HelperTool::Xclip, HelperTool::Xsel, HelperTool::WlClipboard];
+
+  /// ヘルパーの名前
+  pub fn name(&self) -> &'static str {
+    match self {
+      HelperTool::Xclip => "xclip",
+      HelperTool::Xsel => "xsel",
+      HelperTool::WlClipboard => "wl-clipboard",
+    }
+  }
+
+  /// 書き込みに使うコマンド
+  pub fn write_program(&self) -> &'static str {
+    match self {
+      HelperTool::Xclip => "xclip",
+      HelperTool::Xsel => "xsel",
+      HelperTool::WlClipboard => "wl-copy",
+    }
+  }
+
+  /// 読み取りに使うコマンド
+  pub fn read_program(&self) -> &'static str {
+    match self {
+      HelperTool::Xclip => "xclip",
+      HelperTool::Xsel => "xsel",
+      HelperTool::WlClipboard => "wl-paste",
+    }
+  }
+
+  /// バージョン表示の引数
+  pub fn version_args(&self) -> Vec<String> {
+    match self {
+      HelperTool::Xclip => args(&["-version"]),
+      HelperTool::Xsel | HelperTool::WlClipboard => args(&["--version"]),
+    }
+  }
+
+  /// 書き込み時の引数。ターゲット（MIME タイプ）を指定できないヘルパーでは None
+  pub fn write_args(&self, selection: Selection, target: Option<&str>) -> Option<Vec<String>> {
+    match self {
+      HelperTool::Xclip => {
+        let mut a = args(&["-selection", xclip_selection(selection)]);
+        if let Some(target) = target {
+          a.extend(args(&["-t", target]));
+        }
+        Some(a)
+      }
+      HelperTool::Xsel => {
+        if target.is_some() {
+          return None;
+        }
+        Some(args(&[xsel_selection(selection), "--input"]))
+      }
+      HelperTool::WlClipboard => {
+        let mut a = Vec::new();
+        if selection == Selection::Primary {
+          a.push("--primary".to_string());
+        }
+        if let Some(target) = target {
+          a.extend(args(&["--type", target]));
+        }
+        Some(a)
+      }
+    }
+  }
+
+  /// 読み取り時の引数。ターゲット（MIME タイプ）を指定できないヘルパーでは None
+  pub fn read_args(&self, selection: Selection, target: Option<&str>) -> Option<Vec<String>> {
+    match self {
+      HelperTool::Xclip => {
+        let mut a = args(&["-selection", xclip_selection(selection), "-o"]);
+        if let Some(target) = target {
+          a.extend(args(&["-t", target]));
+        }
+        Some(a)
+      }
+      HelperTool::Xsel => {
+        if target.is_some() {
+          return None;
+        }
+        Some(args(&[xsel_selection(selection), "--output"]))
+      }
+      HelperTool::WlClipboard => {
+        let mut a = args(&["--no-newline"]);
+        if selection == Selection::Primary {
+          a.push("--primary".to_string());
+        }
+        if let Some(target) = target {
+          a.extend(args(&["--type", target]));
+        }
+        Some(a)
+      }
+    }
+  }
+
+  /// セレクションを空にする引数。空にする機能がないヘルパー（xclip）では None
+  pub fn clear_args(&self, selection: Selection) -> Option<Vec<String>> {
+    match self {
+      HelperTool::Xclip => None,
+      HelperTool::Xsel => Some(args(&[xsel_selection(selection), "--clear"])),
+      HelperTool::WlClipboard => {
+        let mut a = args(&["--clear"]);
+        if selection == Selection::Primary {
+          a.push("--primary".to_string());
+        }
+        Some(a)
+      }
+    }
+  }
+
+  /// コマンドがインストールされているかどうか
+  pub fn is_installed(&self) -> bool {
+    find_in_path(self.write_program()).is_some() && find_in_path(self.read_program()).is_some()
+  }
+}
+
+fn args(items: &[&str]) -> Vec<String> {
+  items.iter().map(|s| s.to_string()).collect()
+}
+
+fn xclip_selection(selection: Selection) -> &'static str {
+  match selection {
+    Selection::Clipboard => "clipboard",
+    Selection::Primary => "primary",
+  }
+}
+
+fn xsel_selection(selection: Selection) -> &'static str {
+  match selection {
+    Selection::Clipboard => "--clipboard",
+    Selection::Primary => "--primary",
+  }
+}
+
+/// ヘルパーを試す順序を決める
+///
+/// Wayland のみのセッションでは wl-clipboard を優先し、
+/// それ以外では X11 のヘルパーを優先する。
+pub fn preferred_order(display: Option<&str>, wayland_display: Option<&str>) -> Vec<HelperTool> {
+  let wayland_only =
+    wayland_display.is_some_and(|w| !w.is_empty()) && display.is_none_or(|d| d.is_empty());
+  if wayland_only {
+    vec![HelperTool::WlClipboard, HelperTool::Xclip, HelperTool::Xsel]
+  } else {
+    HelperTool::ALL.to_vec()
+  }
+}
+
+// 最後に実行したヘルパー
+static LAST_USED: Mutex<Option<HelperTool>> = Mutex::new(None);
+
+/// 最後にクリップボード操作に使われたヘルパー
+pub fn last_used() -> Option<HelperTool> {
+  *LAST_USED.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+fn set_last_used(tool: HelperTool) {
+  *LAST_USED.lock().unwrap_or_else(|e| e.into_inner()) = Some(tool);
+}
+
+// 操作に使えるヘルパーを選ぶ
+fn select_tool(supports: impl Fn(HelperTool) -> bool) -> Result<HelperTool, Error> {
+  let display = env::var("DISPLAY").ok();
+  let wayland_display = env::var("WAYLAND_DISPLAY").ok();
+
+  preferred_order(display.as_deref(), wayland_display.as_deref())
+    .into_iter()
+    .find(|tool| supports(*tool) && tool.is_installed())
+    .ok_or_else(|| {
+      Error::new(
+        ErrorKind::Unsupported,
+        "No clipboard helper command found (tried xclip, xsel, wl-clipboard)",
+      )
+    })
+}
+
+/// データをクリップボードに書き込み、使われたヘルパーを返す
+///
+/// wl-clipboard では `wl-copy --foreground` を起動したままにし、そのプロセスを
+/// [`take_holder`] で取得できるようにする（セレクションが置き換えられると終了する）。
+pub fn write(selection: Selection, target: Option<&str>, data: &[u8]) -> Result<HelperTool, Error> {
+  let tool = select_tool(|t| t.write_args(selection, target).is_some())?;
+  let tool_args = tool.write_args(selection, target).unwrap_or_default();
+  set_last_used(tool);
+
+  let program = tool.write_program();
+  if tool == HelperTool::WlClipboard {
+    let mut holder_args = args(&["--foreground"]);
+    holder_args.extend(tool_args);
+    let holder = spawn_holder(program, &holder_args, data, timeout::timeout())?;
+    *HOLDER.lock().unwrap_or_else(|e| e.into_inner()) = Some(Arc::new(Mutex::new(holder)));
+    return Ok(tool);
+  }
+
+  // 書き込み後にヘルパーはバックグラウンドでセレクションを保持し続けるため、
+  // 出力はパイプにつながない（読み取りスレッドが終わらなくなる）
+  let output = run_command(program, &tool_args, Some(data), false, timeout::timeout())?;
+
+  if output.status.success() {
+    Ok(tool)
+  } else {
+    Err(Error::other(format!(
+      "{} command failed with exit code: {:?}",
+      program,
+      output.status.code()
+    )))
+  }
+}
+
+/// セレクションを空にし、使われたヘルパーを返す
+pub fn clear(selection: Selection) -> Result<HelperTool, Error> {
+  let tool = select_tool(|t| t.clear_args(selection).is_some())?;
+  let tool_args = tool.clear_args(selection).unwrap_or_default();
+  set_last_used(tool);
+
+  let program = tool.write_program();
+  let output = run_command(program, &tool_args, None, false, timeout::timeout())?;
+  if output.status.success() {
+    Ok(tool)
+  } else {
+    Err(Error::other(format!(
+      "{} command failed with exit code: {:?}",
+      program,
+      output.status.code()
+    )))
+  }
+}
+
+// セレクションを保持している wl-copy --foreground のプロセス
+static HOLDER: Mutex<Option<Arc<Mutex<Child>>>> = Mutex::new(None);
+
+/// 最後の書き込みでセレクションを保持しているプロセスを取り出す
+pub fn take_holder() -> Option<Arc<Mutex<Child>>> {
+  HOLDER.lock().unwrap_or_else(|e| e.into_inner()).take()
+}
+
+/// データを渡した後もセレクションを保持し続けるコマンドを起動する
+///
+/// 起動直後に失敗して終了した場合はエラーを返す。
+pub fn spawn_holder(
+  program: impl AsRef<OsStr>,
+  args: &[String],
+  data: &[u8],
+  timeout: Duration,
+) -> Result<Child, Error> {
+  let program = program.as_ref();
+  let name = program.to_string_lossy().into_owned();
+  let mut child = Command::new(program)
+    .args(args)
+    .stdin(Stdio::piped())
+    .stdout(Stdio::null())
+    .stderr(Stdio::null())
+    .spawn()
+    .map_err(|e| Error::other(format!("Failed to execute {} command: {}", name, e)))?;
+
+  if let Some(mut stdin) = child.stdin.take() {
+    let data = data.to_vec();
+    // 入力を閉じると書き込みの終わりが伝わる
+    let written = timeout::run_with_timeout(&format!("{} command", name), timeout, move || {
+      stdin.write_all(&data)
+    });
+    if let Err(e) = written {
+      abort(&mut child);
+      return Err(e);
+    }
+  }
+
+  // 接続できないなどの失敗はすぐに終了するので、少しだけ様子を見る
+  let deadline = Instant::now() + HOLDER_STARTUP.min(timeout);
+  while Instant::now() < deadline {
+    if let Some(status) = child.try_wait()? {
+      if !status.success() {
+        return Err(Error::other(format!(
+          "{} command failed with exit code: {:?}",
+          name,
+          status.code()
+        )));
+      }
+      break;
+    }
+    thread::sleep(POLL_INTERVAL);
+  }
+
+  Ok(child)
+}
+
+/// クリップボードから読み取った結果
+#[derive(Debug)]
+pub struct ReadOutput {
+  pub tool: HelperTool,
+  pub output: Output,
+}
+
+/// クリップボードからデータを読み取る
+pub fn read(selection: Selection, target: Option<&str>) -> Result<ReadOutput, Error> {
+  let tool = select_tool(|t| t.read_args(selection, target).is_some())?;
+  let tool_args = tool.read_args(selection, target).unwrap_or_default();
+  set_last_used(tool);
+
+  let program = tool.read_program();
+  let output = run_command(program, &tool_args, None, true, timeout::timeout())?;
+
+  Ok(ReadOutput { tool, output })
+}
+
+/// 分割して読み取った結果
+#[derive(Debug)]
+pub struct StreamOutput {
+  pub status: ExitStatus,
+  pub stderr: Vec<u8>,
+  /// `sink` に渡した合計バイト数
+  pub bytes: u64,
+}
+
+/// クリップボードのデータを、ヘルパーの標準出力から届いた順に `sink` へ渡す
+pub fn read_stream(
+  selection: Selection,
+  target: Option<&str>,
+  sink: &mut Sink,
+) -> Result<(HelperTool, StreamOutput), Error> {
+  let tool = select_tool(|t| t.read_args(selection, target).is_some())?;
+  let tool_args = tool.read_args(selection, target).unwrap_or_default();
+  set_last_used(tool);
+
+  let output = stream_command(tool.read_program(), &tool_args, sink, timeout::timeout())?;
+  Ok((tool, output))
+}
+
+/// コマンドを実行し、標準出力を届いた順に `sink` へ渡す
+///
+/// 出力全体をメモリに溜めない。`timeout` はチャンクごとの待ち時間で、
+/// 次のデータが届かないまま経過した場合は強制終了して `ErrorKind::TimedOut` を返す。
+pub fn stream_command(
+  program: impl AsRef<OsStr>,
+  args: &[String],
+  sink: &mut Sink,
+  timeout: Duration,
+) -> Result<StreamOutput, Error> {
+  let program = program.as_ref();
+  let name = program.to_string_lossy().into_owned();
+  let mut child = Command::new(program)
+    .args(args)
+    .stdin(Stdio::null())
+    .stdout(Stdio::piped())
+    .stderr(Stdio::piped())
+    .spawn()
+    .map_err(|e| Error::other(format!("Failed to execute {} command: {}", name, e)))?;
+
+  let stderr_reader = child.stderr.take().map(spawn_reader);
+  let chunks = child.stdout.take().map(spawn_chunk_reader);
+
+  let mut bytes = 0;
+  if let Some(chunks) = chunks {
+    loop {
+      match chunks.recv_timeout(timeout) {
+        Ok(Ok(chunk)) => {
+          if let Err(e) = sink(&chunk) {
+            abort(&mut child);
+            return Err(e);
+          }
+          bytes += chunk.len() as u64;
+        }
+        Ok(Err(e)) => {
+          abort(&mut child);
+          return Err(Error::other(format!(
+            "Failed to read from {} command: {}",
+            name, e
+          )));
+        }
+        Err(RecvTimeoutError::Timeout) => {
+          abort(&mut child);
+          return Err(timeout::timed_out(&format!("{} command", name), timeout));
+        }
+        Err(RecvTimeoutError::Disconnected) => break,
+      }
+    }
+  }
+
+  // 出力が閉じられた後、終了を待つ
+  let deadline = Instant::now() + timeout;
+  let status = loop {
+    if let Some(status) = child.try_wait()? {
+      break status;
+    }
+    if Instant::now() >= deadline {
+      abort(&mut child);
+      return Err(timeout::timed_out(&format!("{} command", name), timeout));
+    }
+    thread::sleep(POLL_INTERVAL);
+  };
+
+  Ok(StreamOutput {
+    status,
+    stderr: stderr_reader
+      .map(|r| r.join().unwrap_or_default())
+      .unwrap_or_default(),
+    bytes,
+  })
+}
+
+fn abort(child: &mut Child) {
+  let _ = child.kill();
+  let _ = child.wait();
+}
+
+// パイプから CHUNK_SIZE ずつ読み取ってチャネルに送る
+// 受け取り側が遅い場合はチャネルが埋まって読み取りが止まり、メモリを使いすぎない
+fn spawn_chunk_reader(
+  mut pipe: impl Read + Send + 'static,
+) -> mpsc::Receiver<Result<Vec<u8>, Error>> {
+  let (tx, rx) = mpsc::sync_channel(4);
+  thread::spawn(move || {
+    let mut buf = vec![0; CHUNK_SIZE];
+    loop {
+      match pipe.read(&mut buf) {
+        Ok(0) => break,
+        Ok(n) => {
+          if tx.send(Ok(buf[..n].to_vec())).is_err() {
+            break;
+          }
+        }
+        Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+        Err(e) => {
+          let _ = tx.send(Err(e));
+          break;
+        }
+      }
+    }
+  });
+  rx
+}
+
+/// コマンドを実行し、タイムアウトまでに終了しなければ強制終了する
+///
+/// * `input` - 標準入力に書き込むデータ
+/// * `capture` - 標準出力・標準エラー出力を取得するかどうか
+///
+/// タイムアウトした場合は `ErrorKind::TimedOut` のエラーを返す。
+pub fn run_command(
+  program: impl AsRef<OsStr>,
+  args: &[String],
+  input: Option<&[u8]>,
+  capture: bool,
+  timeout: Duration,
+) -> Result<Output, Error> {
+  let program = program.as_ref();
+  let name = program.to_string_lossy().into_owned();
+  let pipe_if = |enabled: bool| {
+    if enabled {
+      Stdio::piped()
+    } else {
+      Stdio::null()
+    }
+  };
+
+  let mut child = Command::new(program)
+    .args(args)
+    .stdin(pipe_if(input.is_some()))
+    .stdout(pipe_if(capture))
+    .stderr(pipe_if(capture))
+    .spawn()
+    .map_err(|e| Error::other(format!("Failed to execute {} command: {}", name, e)))?;
+  let deadline = Instant::now() + timeout;
+
+  // 子プロセスが入力を読まない・出力が詰まる場合でも待機ループが止まらないよう、
+  // パイプの読み書きは別スレッドで行う
+  let writer = match (input, child.stdin.take()) {
+    (Some(data), Some(mut stdin)) => {
+      let data = data.to_vec();
+      Some(thread::spawn(move || stdin.write_all(&data)))
+    }
+    _ => None,
+  };
+  let stdout_reader = child.stdout.take().map(spawn_reader);
+  let stderr_reader = child.stderr.take().map(spawn_reader);
+
+  let status = loop {
+    if let Some(status) = child.try_wait()? {
+      break status;
+    }
+    if Instant::now() >= deadline {
+      let _ = child.kill();
+      let _ = child.wait();
+      return Err(timeout::timed_out(&format!("{} command", name), timeout));
+    }
+    thread::sleep(POLL_INTERVAL);
+  };
+
+  if let Some(writer) = writer {
+    if let Ok(Err(e)) = writer.join() {
+      return Err(Error::other(format!(
+        "Failed to write to {} command: {}",
+        name, e
+      )));
+    }
+  }
+
+  let join_reader = |reader: Option<thread::JoinHandle<Vec<u8>>>| {
+    reader
+      .map(|r| r.join().unwrap_or_default())
+      .unwrap_or_default()
+  };
+
+  Ok(Output {
+    status,
+    stdout: join_reader(stdout_reader),
+    stderr: join_reader(stderr_reader),
+  })
+}
+
+fn spawn_reader(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<Vec<u8>> {
+  thread::spawn(move || {
+    let mut buf = Vec::new();
+    let _ = pipe.read_to_end(&mut buf);
+    buf
+  })
+}
+
+/// ヘルパーの標準エラー出力が「セレクションが空」を意味するかどうか
+pub fn is_empty_selection_message(tool: HelperTool, stderr: &str) -> bool {
+  match tool {
+    // xclip: "Error: target STRING not available"
+    HelperTool::Xclip => stderr.contains("not available"),
+    HelperTool::Xsel => stderr.trim().is_empty(),
+    // wl-paste: "Nothing is copied" / "No selection" / "No suitable type of content copied"
+    HelperTool::WlClipboard => {
+      stderr.contains("Nothing is copied")
+        || stderr.contains("No selection")
+        || stderr.contains("No suitable type")
+    }
+  }
+}
+
+/// `text/uri-list` の内容から URI の一覧を取り出す
+///
+/// 空行と `#` で始まるコメント行は無視する（RFC 2483）。
+pub fn parse_uri_list(content: &str) -> Vec<String> {
+  content
+    .lines()
+    .map(str::trim)
+    .filter(|line| !line.is_empty() && !line.starts_with('#'))
+    .map(str::to_string)
+    .collect()
+}
+
+/// PATH からコマンドを探す
+pub fn find_in_path(name: &str) -> Option<PathBuf> {
+  find_in_search_path(name, &env::var_os("PATH")?)
+}
+
+// PATH 形式の文字列に含まれるディレクトリからコマンドを探す
+// 実行権限のないファイルは起動できないため、次のディレクトリを探す
+fn find_in_search_path(name: &str, search_path: &OsStr) -> Option<PathBuf> {
+  use std::os::unix::fs::PermissionsExt;
+
+  env::split_paths(search_path)
+    .map(|dir| dir.join(name))
+    .find(|candidate| {
+      candidate
+        .metadata()
+        .is_ok_and(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::Path;
+
+  // xclip の引数
+  #[test]
+  fn test_xclip_args() {
+    let tool = HelperTool::Xclip;
+    assert_eq!(
+      tool.write_args(Selection::Clipboard, Some("text/uri-list")),
+      Some(args(&["-selection", "clipboard", "-t", "text/uri-list"]))
+    );
+    assert_eq!(
+      tool.read_args(Selection::Primary, None),
+      Some(args(&["-selection", "primary", "-o"]))
+    );
+    // xclip にはセレクションを空にする機能がない
+    assert_eq!(tool.clear_args(Selection::Clipboard), None);
+  }
+
+  // xsel はターゲットを指定できない
+  #[test]
+  fn test_xsel_args() {
+    let tool = HelperTool::Xsel;
+    assert_eq!(
+      tool.write_args(Selection::Clipboard, None),
+      Some(args(&["--clipboard", "--input"]))
+    );
+    assert_eq!(
+      tool.read_args(Selection::Primary, None),
+      Some(args(&["--primary", "--output"]))
+    );
+    assert_eq!(
+      tool.write_args(Selection::Clipboard, Some("text/uri-list")),
+      None
+    );
+    assert_eq!(
+      tool.read_args(Selection::Clipboard, Some("text/uri-list")),
+      None
+    );
+    assert_eq!(
+      tool.clear_args(Selection::Primary),
+      Some(args(&["--primary", "--clear"]))
+    );
+  }
+
+  // wl-copy / wl-paste の引数
+  #[test]
+  fn test_wl_clipboard_args() {
+    let tool = HelperTool::WlClipboard;
+    assert_eq!(
+      tool.write_args(Selection::Clipboard, Some("text/uri-list")),
+      Some(args(&["--type", "text/uri-list"]))
+    );
+    assert_eq!(
+      tool.read_args(Selection::Primary, Some("text/plain")),
+      Some(args(&["--no-newline", "--primary", "--type", "text/plain"]))
+    );
+    assert_eq!(
+      tool.clear_args(Selection::Primary),
+      Some(args(&["--clear", "--primary"]))
+    );
+    assert_eq!(tool.write_program(), "wl-copy");
+    assert_eq!(tool.read_program(), "wl-paste");
+  }
+
+  // Wayland のみのセッションでは wl-clipboard が優先される
+  #[test]
+  fn test_preferred_order() {
+    assert_eq!(
+      preferred_order(Some(":0"), None),
+      vec![HelperTool::Xclip, HelperTool::Xsel, HelperTool::WlClipboard]
+    );
+    assert_eq!(
+      preferred_order(Some(":0"), Some("wayland-0")),
+      vec![HelperTool::Xclip, HelperTool::Xsel, HelperTool::WlClipboard]
+    );
+    assert_eq!(
+      preferred_order(None, Some("wayland-0"))[0],
+      HelperTool::WlClipboard
+    );
+    assert_eq!(
+      preferred_order(Some(""), Some("wayland-0"))[0],
+      HelperTool::WlClipboard
+    );
+  }
+
+  // 空のセレクションを示すエラーメッセージ
+  #[test]
+  fn test_is_empty_selection_message() {
+    assert!(is_empty_selection_message(
+      HelperTool::Xclip,
+      "Error: target text/uri-list not available\n"
+    ));
+    assert!(!is_empty_selection_message(
+      HelperTool::Xclip,
+      "Error: Can't open display: :99\n"
+    ));
+    assert!(is_empty_selection_message(
+      HelperTool::WlClipboard,
+      "Nothing is copied\n"
+    ));
+    assert!(is_empty_selection_message(
+      HelperTool::WlClipboard,
+      "No selection\n"
+    ));
+    assert!(is_empty_selection_message(HelperTool::Xsel, ""));
+  }
+
+  // uri-list のパース
+  #[test]
+  fn test_parse_uri_list() {
+    let content =
+      "# comment\r\nfile:///tmp/a.txt\r\n\r\n  file:///tmp/b%20c.txt  \nsmb://host/share\n";
+    assert_eq!(
+      parse_uri_list(content),
+      vec![
+        "file:///tmp/a.txt",
+        "file:///tmp/b%20c.txt",
+        "smb://host/share"
+      ]
+    );
+    assert!(parse_uri_list("").is_empty());
+  }
+
+  // テスト用のヘルパースクリプトを作成する
+  // 並行するテストやテストの実行と書き込みが重ならないよう、プロセスとスクリプトごとのディレクトリに置く
+  fn fake_helper(name: &str, script: &str) -> PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = env::temp_dir().join(format!(
+      "clip-filepaths-fake-helpers-{}-{}",
+      std::process::id(),
+      name
+    ));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::write(&path, format!("#!/bin/sh\n{}\n", script)).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+  }
+
+  // 正常に終了するコマンドの出力を取得できる
+  #[test]
+  fn test_run_command_captures_output() {
+    let helper = fake_helper("fake-echo", "cat; echo err >&2");
+    let output = run_command(
+      &helper,
+      &[],
+      Some(b"file:///tmp/a.txt"),
+      true,
+      Duration::from_secs(5),
+    )
+    .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"file:///tmp/a.txt");
+    assert_eq!(output.stderr, b"err\n");
+  }
+
+  // 応答しない読み取りはタイムアウトし、子プロセスは終了させられる
+  #[test]
+  fn test_run_command_read_timeout() {
+    let helper = fake_helper("fake-hanging-read", "sleep 10");
+    let started = Instant::now();
+    let err = run_command(&helper, &[], None, true, Duration::from_millis(200)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(err.to_string().contains("timed out after 200 ms"));
+    assert!(started.elapsed() < Duration::from_secs(5));
+  }
+
+  // 入力を読まずに止まる書き込みもタイムアウトする
+  #[test]
+  fn test_run_command_write_timeout() {
+    let helper = fake_helper("fake-hanging-write", "sleep 10");
+    let data = vec![b'x'; 1024 * 1024];
+    let started = Instant::now();
+    let err =
+      run_command(&helper, &[], Some(&data), false, Duration::from_millis(200)).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(started.elapsed() < Duration::from_secs(5));
+  }
+
+  // 大きな出力は分割して渡され、全体をメモリに溜めずに受け取れる
+  #[test]
+  fn test_stream_command_chunks() {
+    // 1 MB の出力
+    let helper = fake_helper("fake-stream", "head -c 1048576 /dev/zero");
+    let mut largest = 0;
+    let mut total = 0;
+    let output = stream_command(
+      &helper,
+      &[],
+      &mut |chunk| {
+        largest = largest.max(chunk.len());
+        total += chunk.len();
+        Ok(())
+      },
+      Duration::from_secs(5),
+    )
+    .unwrap();
+    assert!(output.status.success());
+    assert_eq!(output.bytes, 1024 * 1024);
+    assert_eq!(total, 1024 * 1024);
+    assert!(largest <= CHUNK_SIZE);
+  }
+
+  // 途中で出力が止まった場合はタイムアウトする
+  #[test]
+  fn test_stream_command_stall_timeout() {
+    let helper = fake_helper("fake-stalled-stream", "printf abc; sleep 10");
+    let mut received = Vec::new();
+    let started = Instant::now();
+    let err = stream_command(
+      &helper,
+      &[],
+      &mut |chunk| {
+        received.extend_from_slice(chunk);
+        Ok(())
+      },
+      Duration::from_millis(300),
+    )
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert_eq!(received, b"abc");
+    assert!(started.elapsed() < Duration::from_secs(5));
+  }
+
+  // sink のエラーで読み取りが中断される
+  #[test]
+  fn test_stream_command_sink_error() {
+    let helper = fake_helper("fake-endless-stream", "cat /dev/zero");
+    let err = stream_command(
+      &helper,
+      &[],
+      &mut |_| Err(Error::other("disk full")),
+      Duration::from_secs(5),
+    )
+    .unwrap_err();
+    assert!(err.to_string().contains("disk full"));
+  }
+
+  // 入力を受け取った後も動き続けるコマンドはプロセスとして返される
+  #[test]
+  fn test_spawn_holder_keeps_running() {
+    let helper = fake_helper("fake-holder", "cat > /dev/null; sleep 10");
+    let mut child = spawn_holder(&helper, &[], b"data", Duration::from_secs(5)).unwrap();
+    assert!(child.try_wait().unwrap().is_none());
+    abort(&mut child);
+  }
+
+  // 起動直後に失敗したコマンドはエラーになる
+  #[test]
+  fn test_spawn_holder_reports_failure() {
+    let helper = fake_helper("fake-failing-holder", "cat > /dev/null; exit 3");
+    let err = spawn_holder(&helper, &[], b"data", Duration::from_secs(5)).unwrap_err();
+    assert!(err.to_string().contains("exit code: Some(3)"));
+  }
+
+  // PATH からコマンドを探せる。実行権限のないファイルは飛ばす
+  #[test]
+  fn test_find_in_search_path() {
+    let dir = env::temp_dir().join(format!(
+      "clip-filepaths-helper-path-test-{}",
+      std::process::id()
+    ));
+    let not_executable = dir.join("not-executable");
+    std::fs::create_dir_all(&not_executable).unwrap();
+    std::fs::File::create(not_executable.join("fake-clip-tool")).unwrap();
+    let executable = fake_helper("fake-clip-tool", "exit 0");
+
+    let search_path = env::join_paths([
+      Path::new("/path/does/not/exist"),
+      &not_executable,
+      executable.parent().unwrap(),
+    ])
+    .unwrap();
+    assert_eq!(
+      find_in_search_path("fake-clip-tool", &search_path),
+      Some(executable.clone())
+    );
+    assert_eq!(find_in_search_path("missing-clip-tool", &search_path), None);
+
+    let _ = std::fs::remove_dir_all(dir);
+    let _ = std::fs::remove_dir_all(executable.parent().unwrap());
+  }
+}
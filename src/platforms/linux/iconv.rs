@@ -0,0 +1,59 @@
+// iconv（glibc）による文字コードの変換
+//
+// COMPOUND_TEXT に含まれる EUC-JP などの文字集合を UTF-8 にするために使う。
+
+use std::ffi::CString;
+use std::os::raw::c_char;
+
+/// `charset` のバイト列を文字列に変換する
+///
+/// iconv が知らない文字コードの場合や、不正なバイト列を含む場合は None を返す。
+pub fn to_utf8(charset: &str, data: &[u8]) -> Option<String> {
+  let from = CString::new(charset).ok()?;
+  let to = CString::new("UTF-8").ok()?;
+
+  unsafe {
+    let cd = libc::iconv_open(to.as_ptr(), from.as_ptr());
+    if cd as isize == -1 {
+      return None;
+    }
+
+    // ここで扱う文字コードは 1 バイトあたり UTF-8 で 3 バイトを超えない
+    let mut input = data.to_vec();
+    let mut output = vec![0u8; data.len() * 4 + 16];
+    let mut in_ptr = input.as_mut_ptr() as *mut c_char;
+    let mut in_left = input.len();
+    let mut out_ptr = output.as_mut_ptr() as *mut c_char;
+    let mut out_left = output.len();
+
+    let result = libc::iconv(cd, &mut in_ptr, &mut in_left, &mut out_ptr, &mut out_left);
+    libc::iconv_close(cd);
+    if result == usize::MAX || in_left != 0 {
+      return None;
+    }
+
+    output.truncate(output.len() - out_left);
+    String::from_utf8(output).ok()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // COMPOUND_TEXT で使う文字集合を変換できる
+  #[test]
+  fn test_to_utf8() {
+    assert_eq!(
+      to_utf8("EUC-JP", b"\xc6\xfc\xcb\xdc\xb8\xec").as_deref(),
+      Some("日本語")
+    );
+    // JIS X 0212（SS3 に続く 2 バイト）
+    assert_eq!(to_utf8("EUC-JP", b"\x8f\xb0\xa1").as_deref(), Some("丂"));
+    assert_eq!(to_utf8("ISO-8859-7", b"\xe1").as_deref(), Some("α"));
+    assert_eq!(to_utf8("UCS-2BE", b"\x65\xe5").as_deref(), Some("日"));
+    // 不正なバイト列と未知の文字コード
+    assert_eq!(to_utf8("EUC-JP", b"\xc6"), None);
+    assert_eq!(to_utf8("NO-SUCH-CHARSET", b"a"), None);
+  }
+}
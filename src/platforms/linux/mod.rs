@@ -0,0 +1,647 @@
+// Linux向けのクリップボード操作実装
+
+pub mod change_count;
+pub mod gvfs;
+pub mod helper;
+pub mod iconv;
+pub mod owner_info;
+pub mod ownership;
+pub mod payload;
+pub mod x11_owner;
+pub mod x11_selection;
+pub mod xdnd;
+
+use std::io::{Error, ErrorKind};
+use std::sync::Mutex;
+
+use crate::platforms::file_entry::FileEntry;
+use crate::platforms::lazy::{Contents, Provider};
+use crate::platforms::owner_info::{self as source_marker, OwnerInfo, SOURCE_MARKER_FORMAT};
+use crate::platforms::retry;
+use crate::platforms::stream::Sink;
+use crate::platforms::text_encoding::{self, DecodedText, X11_TEXT_TARGETS};
+use crate::platforms::text_representation::{PathText, TextRepresentation};
+use helper::Selection;
+pub use ownership::Ownership;
+use x11_selection::{SelectionData, XConnection};
+
+use x11::xlib;
+
+/// X サーバーへの接続確認の結果
+#[derive(Debug, Clone, Default)]
+pub struct X11Probe {
+  /// XFIXES 拡張が利用可能かどうか
+  pub xfixes: bool,
+  /// `CLIPBOARD_MANAGER` セレクションの所有者（クリップボードマネージャー）が存在するかどうか
+  pub clipboard_manager: bool,
+}
+
+// ヘルパーコマンドを使用してファイルパスをクリップボードにコピーする
+// `source` を指定した場合は、書き込み元を示す印のターゲットも提供する
+// `text` を指定した場合は、テキストのターゲットにその形式のテキストを入れる
+// 空の一覧は呼び出し元で clear_clipboard に置き換えられる
+pub fn write_clipboard_file_paths(
+  paths: &[String],
+  source: Option<&str>,
+  text: Option<&PathText>,
+) -> Result<(), Error> {
+  // text/uri-list やテキストなど、提供するターゲットのデータを作る
+  let mut payload = payload::file_paths_payload(paths, text)?;
+
+  // ヘルパーは 1 つのターゲットしか提供できないため、印やテキストの形式を指定した場合は
+  // すべてのターゲットを提供できる X11 のセレクションの所有を優先する
+  if let Some(source) = source {
+    payload.push((SOURCE_MARKER_FORMAT.to_string(), source.as_bytes().to_vec()));
+  }
+  // X サーバーがない場合は、指定された印やテキストを省かずにエラーにする
+  let text_requested = text.is_some_and(|text| text.representation != TextRepresentation::None);
+  if source.is_some() || text_requested {
+    x11_owner::serve_in_background(Selection::Clipboard, payload).map_err(|e| {
+      Error::new(
+        ErrorKind::Unsupported,
+        format!(
+          "sourceMarker and textRepresentation require an X server: {}",
+          e
+        ),
+      )
+    })?;
+    println!(
+      "Copied {} files to clipboard on Linux (native X11)",
+      paths.len()
+    );
+    return Ok(());
+  }
+
+  // ターゲットを指定できるヘルパー（xclip / wl-copy）でクリップボードに書き込む
+  // ヘルパーは 1 つのターゲットしか提供できないため text/uri-list を使う
+  // ヘルパーがない場合は、X11 のセレクションを自分で所有してすべてのターゲットを提供する
+  let uri_list = payload
+    .iter()
+    .find(|(target, _)| target == payload::URI_LIST)
+    .map(|(_, data)| data.as_slice())
+    .unwrap_or_default();
+  let backend = match helper::write(Selection::Clipboard, Some(payload::URI_LIST), uri_list) {
+    Ok(tool) => tool.name(),
+    Err(e) if e.kind() == ErrorKind::Unsupported => {
+      x11_owner::serve_in_background(Selection::Clipboard, payload).map_err(|_| e)?;
+      "native X11"
+    }
+    Err(e) => return Err(e),
+  };
+
+  println!(
+    "Copied {} files to clipboard on Linux ({})",
+    paths.len(),
+    backend
+  );
+  Ok(())
+}
+
+// テキストをクリップボードにコピーする
+// `paths` が空でなければ text/uri-list も提供する。ヘルパーは 1 つのターゲットしか
+// 提供できないため、その場合は X11 のセレクションを所有してすべてのターゲットを提供する
+// （X サーバーがなければ text/uri-list を省かずにエラーにする）
+pub fn write_clipboard_text(text: &str, paths: &[String]) -> Result<(), Error> {
+  if !paths.is_empty() {
+    let payload = payload::text_payload(text, paths);
+    x11_owner::serve_in_background(Selection::Clipboard, payload).map_err(|e| {
+      Error::new(
+        ErrorKind::Unsupported,
+        format!("Publishing text/uri-list requires an X server: {}", e),
+      )
+    })?;
+    println!("Copied text to clipboard on Linux (native X11)");
+    return Ok(());
+  }
+
+  // ターゲットを指定しなければ、ヘルパーはテキストのターゲットをすべて提供する
+  let backend = match helper::write(Selection::Clipboard, None, text.as_bytes()) {
+    Ok(tool) => tool.name(),
+    Err(e) if e.kind() == ErrorKind::Unsupported => {
+      x11_owner::serve_in_background(Selection::Clipboard, payload::text_payload(text, &[]))
+        .map_err(|_| e)?;
+      "native X11"
+    }
+    Err(e) => return Err(e),
+  };
+
+  println!("Copied text to clipboard on Linux ({})", backend);
+  Ok(())
+}
+
+// セレクションを空にし、空になった状態の所有状態を返す
+// X サーバーがあれば所有者をなくし、ない場合はヘルパー（xsel / wl-copy）の機能を使う
+// X11 では所有者がいない状態が続いている間を「所有している」とみなす
+pub fn clear_clipboard(selection: Selection) -> Result<Ownership, Error> {
+  // 以前の書き込みで残っているプロセスは対象外
+  helper::take_holder();
+
+  let x_err = match XConnection::open() {
+    Ok(conn) => {
+      conn.clear_owner(selection)?;
+      return Ok(Ownership::X11 {
+        selection,
+        owner: 0,
+        conn: Mutex::new(conn),
+      });
+    }
+    Err(e) => e,
+  };
+  match helper::clear(selection) {
+    Ok(_) => Ok(Ownership::Untracked),
+    Err(e) if e.kind() == ErrorKind::Unsupported => Err(Error::new(
+      ErrorKind::Unsupported,
+      format!(
+        "Clearing the clipboard requires an X server, xsel or wl-copy ({})",
+        x_err
+      ),
+    )),
+    Err(e) => Err(e),
+  }
+}
+
+// 要求されたときに provider でデータを生成するようにクリップボードに書き込む
+// ヘルパーコマンドには書き込み時にデータを渡す必要があるため、X11 のセレクションを直接所有する
+pub fn write_clipboard_lazy(formats: &[String], provider: Provider) -> Result<(), Error> {
+  x11_owner::serve_in_background(Selection::Clipboard, Contents::deferred(formats, provider))
+    .map_err(|e| {
+      Error::new(
+        e.kind(),
+        format!("Lazy clipboard data requires an X server (DISPLAY): {}", e),
+      )
+    })?;
+  println!(
+    "Registered {} lazy formats to clipboard on Linux (native X11)",
+    formats.len()
+  );
+  Ok(())
+}
+
+/// ドラッグ＆ドロップの結果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDragResult {
+  /// `"dropped"`, `"rejected"`, `"cancelled"` のいずれか
+  pub outcome: &'static str,
+  /// ドロップ先が実行した操作（`"copy"`, `"move"`, `"link"` など）
+  pub action: Option<String>,
+}
+
+// `origin` ウィンドウから XDND でファイルをドラッグする
+// クリップボードへの書き込みと同じ形式（text/uri-list など）のデータを提供する
+pub fn start_file_drag(origin: u64, paths: &[String]) -> Result<FileDragResult, Error> {
+  if paths.is_empty() {
+    return Err(Error::new(ErrorKind::InvalidInput, "No paths to drag"));
+  }
+  let payload = payload::file_paths_payload(paths, None)?;
+  let (outcome, action) = xdnd::run_drag(origin as xlib::Window, payload)?;
+
+  let outcome = match outcome {
+    xdnd::DragOutcome::Dropped { .. } => "dropped",
+    xdnd::DragOutcome::Rejected => "rejected",
+    xdnd::DragOutcome::Cancelled => "cancelled",
+  };
+  // XdndActionCopy -> copy
+  let action = action.map(|name| {
+    name
+      .strip_prefix("XdndAction")
+      .map(str::to_lowercase)
+      .unwrap_or(name)
+  });
+  Ok(FileDragResult { outcome, action })
+}
+
+// クリップボードからテキストを読み取る
+pub fn read_clipboard_text() -> Result<String, Error> {
+  read_clipboard_text_decoded().map(|decoded| decoded.text)
+}
+
+// クリップボードのテキストを、文字コードの分かるターゲットを優先して読み取る
+// UTF8_STRING, text/plain;charset=utf-8, STRING, COMPOUND_TEXT, TEXT の順に試し、
+// ターゲットを指定できるヘルパーがない場合は X11 のセレクションを直接変換する
+pub fn read_clipboard_text_decoded() -> Result<DecodedText, Error> {
+  let mut failure = None;
+  for target in X11_TEXT_TARGETS {
+    let read = match helper::read(Selection::Clipboard, Some(target)) {
+      Ok(read) => read,
+      Err(e) if e.kind() == ErrorKind::Unsupported => {
+        let conn = XConnection::open().map_err(|_| e)?;
+        return read_text_native(&conn);
+      }
+      Err(e) => return Err(e),
+    };
+
+    if read.output.status.success() {
+      if !read.output.stdout.is_empty() {
+        // ヘルパーは返された型を出力しないため、要求したターゲットで文字コードを判断する
+        return Ok(text_encoding::decode_x11_text(
+          target,
+          target,
+          &read.output.stdout,
+          &iconv::to_utf8,
+        ));
+      }
+      continue;
+    }
+    // このターゲットが提供されていない場合は次を試す
+    let error = String::from_utf8_lossy(&read.output.stderr).into_owned();
+    if !helper::is_empty_selection_message(read.tool, &error) {
+      failure.get_or_insert(error);
+    }
+  }
+
+  match failure {
+    Some(error) => Err(Error::other(format!("Failed to read clipboard: {}", error))),
+    None => Err(Error::other("No text content in clipboard")),
+  }
+}
+
+// X11 のセレクションを直接変換してテキストを読み取る
+// TARGETS で提供されているターゲットを調べ、所有者が返した型で文字コードを判断する
+fn read_text_native(conn: &XConnection) -> Result<DecodedText, Error> {
+  if conn.owner(Selection::Clipboard) == 0 {
+    return Err(Error::other("No text content in clipboard"));
+  }
+  let timeout = crate::platforms::timeout::timeout();
+
+  // 提供されている名前（text/plain;charset=UTF-8 など大文字小文字の違いを含む）で要求する
+  let offered: Option<Vec<String>> =
+    conn
+      .convert(Selection::Clipboard, "TARGETS", timeout)?
+      .map(|data| {
+        data
+          .as_u32_items()
+          .into_iter()
+          .filter_map(|atom| conn.atom_name(atom as xlib::Atom))
+          .collect()
+      });
+  let targets: Vec<String> = match &offered {
+    Some(offered) => X11_TEXT_TARGETS
+      .iter()
+      .filter_map(|target| {
+        offered
+          .iter()
+          .find(|name| name.eq_ignore_ascii_case(target))
+          .cloned()
+      })
+      .collect(),
+    None => X11_TEXT_TARGETS.iter().map(|t| t.to_string()).collect(),
+  };
+
+  convert_text(&targets, |target| {
+    conn.convert(Selection::Clipboard, target, timeout)
+  })
+}
+
+// `targets` を順に `convert` で変換し、最初に得られたテキストを返す
+// 一時的な失敗（所有者の切り替わりなど）は再試行のポリシーに従って変換し直す
+fn convert_text(
+  targets: &[String],
+  mut convert: impl FnMut(&str) -> Result<Option<SelectionData>, Error>,
+) -> Result<DecodedText, Error> {
+  for target in targets {
+    let data = retry::retry(&format!("Converting the selection to {}", target), || {
+      convert(target)
+    })?;
+    if let Some(data) = data.filter(|d| !d.data.is_empty()) {
+      return Ok(text_encoding::decode_x11_text(
+        target,
+        &data.type_name,
+        &data.data,
+        &iconv::to_utf8,
+      ));
+    }
+  }
+  Err(Error::other("No text content in clipboard"))
+}
+
+// クリップボードからRAWデータを読み取る
+pub fn read_clipboard_raw() -> Result<Vec<u8>, Error> {
+  let read = helper::read(Selection::Clipboard, None)?;
+
+  if read.output.status.success() {
+    if read.output.stdout.is_empty() {
+      Err(Error::other("No data in clipboard"))
+    } else {
+      Ok(read.output.stdout)
+    }
+  } else {
+    let error = String::from_utf8_lossy(&read.output.stderr).into_owned();
+    if helper::is_empty_selection_message(read.tool, &error) {
+      return Err(Error::other("No data in clipboard"));
+    }
+    Err(Error::other(format!(
+      "Failed to read clipboard raw data: {}",
+      error
+    )))
+  }
+}
+
+// クリップボードからファイルパスを読み取る
+// GVFS の URI は FUSE のパスにできれば含め、パスにできない URI は含めない
+pub fn read_clipboard_file_paths() -> Result<Vec<String>, Error> {
+  Ok(
+    read_clipboard_file_entries()?
+      .into_iter()
+      .filter_map(|entry| entry.path)
+      .collect(),
+  )
+}
+
+// クリップボードの text/uri-list のすべての URI を読み取る
+pub fn read_clipboard_file_entries() -> Result<Vec<FileEntry>, Error> {
+  // ヘルパーでクリップボードからURI-listを読み取る
+  // ヘルパーがない場合は、X11 のセレクションを直接変換して読み取る
+  let read = match helper::read(Selection::Clipboard, Some("text/uri-list")) {
+    Ok(read) => read,
+    Err(e) if e.kind() == ErrorKind::Unsupported => {
+      return read_file_entries_native().map_err(|_| e);
+    }
+    Err(e) => return Err(e),
+  };
+
+  if read.output.status.success() {
+    // 有効なURIが見つからなくても空の配列を返す
+    Ok(uri_list_to_entries(&read.output.stdout))
+  } else {
+    let error = String::from_utf8_lossy(&read.output.stderr).into_owned();
+    // uri-list が存在しない場合は空の配列を返す
+    if helper::is_empty_selection_message(read.tool, &error) {
+      return Ok(Vec::new());
+    }
+    Err(Error::other(format!(
+      "Failed to read clipboard for file paths: {}",
+      error
+    )))
+  }
+}
+
+// 指定したターゲット（`image/png` など）のデータを分割して読み取る
+pub fn read_clipboard_format(format: &str, sink: &mut Sink) -> Result<u64, Error> {
+  // ヘルパーの標準出力から読み取る。ヘルパーがない場合は X11 のセレクションを直接変換する
+  let (tool, output) = match helper::read_stream(Selection::Clipboard, Some(format), sink) {
+    Ok(read) => read,
+    Err(e) if e.kind() == ErrorKind::Unsupported => {
+      let conn = XConnection::open().map_err(|_| e)?;
+      let mut bytes = 0;
+      let converted = retry::retry(&format!("Converting the selection to {}", format), || {
+        let result = conn.convert_to(
+          Selection::Clipboard,
+          format,
+          crate::platforms::timeout::timeout(),
+          &mut |chunk| {
+            bytes += chunk.len() as u64;
+            sink(chunk)
+          },
+        );
+        // 一部のデータを渡した後は、読み直すと重複するため再試行しない
+        match result {
+          Err(e) if bytes > 0 && retry::is_transient(&e) => Err(Error::other(e.to_string())),
+          result => result,
+        }
+      })?;
+      return match converted {
+        Some(_) => Ok(bytes),
+        None => Err(format_not_available(format)),
+      };
+    }
+    Err(e) => return Err(e),
+  };
+
+  if output.status.success() {
+    return Ok(output.bytes);
+  }
+  let error = String::from_utf8_lossy(&output.stderr).into_owned();
+  if helper::is_empty_selection_message(tool, &error) {
+    return Err(format_not_available(format));
+  }
+  Err(Error::other(format!(
+    "Failed to read clipboard format {}: {}",
+    format, error
+  )))
+}
+
+fn format_not_available(format: &str) -> Error {
+  Error::new(
+    ErrorKind::NotFound,
+    format!("Format {} is not available in clipboard", format),
+  )
+}
+
+// X11 のセレクションを text/uri-list に変換して URI を読み取る
+fn read_file_entries_native() -> Result<Vec<FileEntry>, Error> {
+  let conn = XConnection::open()?;
+  let data = retry::retry("Converting the selection to text/uri-list", || {
+    conn.convert(
+      Selection::Clipboard,
+      "text/uri-list",
+      crate::platforms::timeout::timeout(),
+    )
+  })?;
+  Ok(data.map_or_else(Vec::new, |d| uri_list_to_entries(&d.data)))
+}
+
+// text/uri-list の内容をエントリに変換する（file:// 以外は GVFS の FUSE のパスを探す）
+fn uri_list_to_entries(content: &[u8]) -> Vec<FileEntry> {
+  let content = String::from_utf8_lossy(content);
+  let uris = helper::parse_uri_list(&content);
+  let gvfs = if uris.iter().all(|uri| uri.starts_with("file://")) {
+    gvfs::GvfsMounts::default()
+  } else {
+    gvfs::GvfsMounts::discover()
+  };
+  uris.iter().map(|uri| gvfs.entry(uri)).collect()
+}
+
+// クリップボードの所有者のウィンドウ・プロセスと、書き込み元の印を取得する
+// X サーバーがない（Wayland のみの）場合は、ヘルパーで読める印だけを返す
+pub fn clipboard_owner_info() -> Result<OwnerInfo, Error> {
+  let conn = XConnection::open();
+  let mut info = match &conn {
+    Ok(conn) => owner_info::selection_owner_process(conn, Selection::Clipboard),
+    Err(_) => OwnerInfo::default(),
+  };
+
+  let mut marker = Vec::new();
+  match read_clipboard_format(SOURCE_MARKER_FORMAT, &mut |chunk| {
+    marker.extend_from_slice(chunk);
+    Ok(())
+  }) {
+    Ok(_) => info.source = source_marker::parse_source_marker(&marker),
+    // X サーバーもヘルパーもない場合は何も分からない
+    Err(e) if conn.is_err() && e.kind() == ErrorKind::Unsupported => {
+      return Err(Error::new(
+        ErrorKind::Unsupported,
+        format!("Clipboard owner cannot be determined: {}", e),
+      ));
+    }
+    // 印がない、または読み取れない場合は None のまま
+    Err(_) => {}
+  }
+  Ok(info)
+}
+
+// X サーバーに接続し、XFIXES 拡張とクリップボードマネージャーの有無を調べる
+pub fn probe_x11() -> Result<X11Probe, Error> {
+  let conn = XConnection::open()?;
+  let manager = conn.atom("CLIPBOARD_MANAGER");
+
+  Ok(X11Probe {
+    xfixes: conn.has_extension("XFIXES"),
+    clipboard_manager: unsafe { xlib::XGetSelectionOwner(conn.display(), manager) } != 0,
+  })
+}
+
+// クリップボードの所有期間を識別する値を取得する
+pub fn clipboard_epoch() -> Result<u64, Error> {
+  x11_selection::selection_epoch(Selection::Clipboard)
+}
+
+// 書き込みを実行し、その内容の所有状態を返す
+pub fn track_ownership(
+  selection: Selection,
+  write: impl FnOnce() -> Result<(), Error>,
+) -> Result<ownership::Ownership, Error> {
+  ownership::Ownership::track(selection, write)
+}
+
+// セレクションが変更されるたびに増加する値を取得する（XFIXES のイベントを数える）
+pub fn clipboard_change_count(selection: Selection) -> Result<u64, Error> {
+  change_count::change_count(selection)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::env::temp_dir;
+  use std::fs::File;
+
+  // 変換が一時的に失敗しても再試行して読み取り、失敗が続けば試行回数を含めて報告する
+  #[test]
+  fn test_convert_text_retries_transient_failures() {
+    let policy = retry::RetryPolicy {
+      attempts: 3,
+      backoff: std::time::Duration::from_millis(1),
+      deadline: std::time::Duration::from_secs(1),
+    };
+    let targets = vec!["UTF8_STRING".to_string()];
+    let text = |calls: &mut u32, failures: u32| {
+      *calls += 1;
+      if *calls <= failures {
+        return Err(retry::busy("Failed to read selection property"));
+      }
+      Ok(Some(SelectionData {
+        type_name: "UTF8_STRING".to_string(),
+        data: "こんにちは".as_bytes().to_vec(),
+      }))
+    };
+
+    let mut calls = 0;
+    let decoded = retry::with_policy(Some(policy), || {
+      convert_text(&targets, |_| text(&mut calls, 2))
+    })
+    .unwrap();
+    assert_eq!(decoded.text, "こんにちは");
+    assert_eq!(calls, 3);
+
+    let mut calls = 0;
+    let err = retry::with_policy(Some(policy), || {
+      convert_text(&targets, |_| text(&mut calls, 3))
+    })
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert!(err.to_string().contains("after 3 attempts"), "{}", err);
+    assert_eq!(calls, 3);
+  }
+
+  // URI生成の基本的なテスト（空白は %20 にエンコードされる）
+  #[test]
+  fn test_uri_generation() {
+    let tmp_dir = temp_dir();
+    let test_file_path = tmp_dir.join(format!("test linux uri {}.txt", std::process::id()));
+    let _ = File::create(&test_file_path).expect("Failed to create test file");
+
+    let path_str = test_file_path.to_string_lossy().to_string();
+    let canonical_path = test_file_path.canonicalize().unwrap();
+    let expected_uri = format!(
+      "file://{}",
+      canonical_path.to_string_lossy().replace(' ', "%20")
+    );
+
+    let payload = payload::file_paths_payload(&[path_str], None).unwrap();
+    assert_eq!(payload[0].0, payload::URI_LIST);
+    assert_eq!(String::from_utf8_lossy(&payload[0].1), expected_uri);
+
+    let _ = std::fs::remove_file(test_file_path);
+  }
+
+  // 不正なパスを扱えるかのテスト
+  #[test]
+  fn test_invalid_paths() {
+    let invalid_paths = vec![
+      "/path/does/not/exist/linux.txt".to_string(),
+      "invalid-path-linux.txt".to_string(),
+    ];
+
+    // copy_files_to_clipboard を呼び出すが、エラーが発生することを期待
+    let result = write_clipboard_file_paths(&invalid_paths, None, None);
+    assert!(result.is_err());
+
+    // エラーの種類とメッセージを検証
+    if let Err(err) = result {
+      assert_eq!(err.kind(), ErrorKind::InvalidInput);
+      assert!(err
+        .to_string()
+        .contains("Some paths could not be processed"));
+    }
+  }
+
+  // 実際のクリップボード操作テスト（CIではスキップ推奨）
+  #[test]
+  fn test_copy_to_clipboard() {
+    let tmp_dir = temp_dir();
+    let test_file_path = tmp_dir.join("test_linux_clipboard.txt");
+    let _ = File::create(&test_file_path).expect("Failed to create test file");
+
+    let path_str = test_file_path.to_string_lossy().to_string();
+
+    // クリップボードにコピー
+    let result = write_clipboard_file_paths(&[path_str], None, None);
+
+    // xclip がない環境や X11 がない環境では失敗することがある
+    // その場合はテストをパスさせるか、環境に応じた処理が必要
+    if let Err(e) = &result {
+      if e.kind() == ErrorKind::Unsupported
+        || e.to_string().contains("Failed to execute xclip command")
+        || e.to_string().contains("X11 server connection timed out")
+        || e.to_string().contains("No text property")
+      // Wayland で発生しうるエラー
+      {
+        println!("⚠️ クリップボードテストをスキップ: 環境の問題 ({})", e);
+        return;
+      }
+    }
+
+    assert!(result.is_ok(), "Copy operation failed: {:?}", result);
+
+    let _ = std::fs::remove_file(test_file_path);
+  }
+
+  // X サーバーがある環境では、空にしたセレクションの所有者はいなくなり、
+  // 別のクライアントが書き込むまで「所有している」状態が続く
+  #[test]
+  fn test_x11_clear_clipboard() {
+    if std::env::var("DISPLAY").map_or(true, |d| d.is_empty()) {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let owner = x11_owner::SelectionOwner::acquire(
+      Selection::Primary,
+      vec![("text/plain".to_string(), b"clear".to_vec())],
+    )
+    .unwrap();
+
+    let ownership = clear_clipboard(Selection::Primary).unwrap();
+    assert_eq!(owner.connection().owner(Selection::Primary), 0);
+    assert!(ownership.is_owner().unwrap());
+  }
+}
@@ -0,0 +1,179 @@
+// クリップボードの所有者のプロセスを調べる実装
+//
+// XGetSelectionOwner で得たウィンドウの `_NET_WM_PID` からプロセス ID を取得し、
+// `/proc/<pid>/exe` から実行ファイルを求める。ツールキットによっては所有者が
+// `_NET_WM_PID` を持たない非表示ウィンドウのため、`WM_CLIENT_LEADER` が指す
+// ウィンドウも確認する。`_NET_WM_PID` は所有者のホストでの値なので、`WM_CLIENT_MACHINE` が
+// このホストと異なる（リモートの X クライアント）場合はプロセスを調べない。
+
+use std::fs;
+
+use x11::xlib;
+
+use super::helper::Selection;
+use super::x11_selection::{SelectionData, XConnection};
+use crate::platforms::owner_info::OwnerInfo;
+
+/// X11 のセレクションの所有者のウィンドウとプロセスを調べる
+///
+/// 所有者がいない場合はすべての項目が None になる。
+pub fn selection_owner_process(conn: &XConnection, selection: Selection) -> OwnerInfo {
+  let window = conn.owner(selection);
+  if window == 0 {
+    return OwnerInfo::default();
+  }
+
+  let pid = window_pid(conn, window);
+  OwnerInfo {
+    window: Some(window),
+    pid,
+    executable: pid.and_then(process_executable),
+    source: None,
+  }
+}
+
+// ウィンドウ（またはそのクライアントリーダー）の `_NET_WM_PID` を読む
+//
+// `_NET_WM_PID` を持つウィンドウの `WM_CLIENT_MACHINE` がこのホストでなければ None
+fn window_pid(conn: &XConnection, window: xlib::Window) -> Option<u32> {
+  let read = |window: xlib::Window, property: &str| {
+    conn
+      .read_window_property(window, conn.atom(property), false)
+      .ok()
+  };
+  let hostname = local_hostname();
+  let local_pid = |window: xlib::Window| {
+    let pid = read(window, "_NET_WM_PID").and_then(|d| first_cardinal(&d, "CARDINAL"))?;
+    let machine = read(window, "WM_CLIENT_MACHINE");
+    let local = hostname
+      .as_deref()
+      .is_some_and(|hostname| is_local_machine(machine.as_ref(), hostname));
+    Some(local.then_some(pid))
+  };
+
+  if let Some(pid) = local_pid(window) {
+    return pid;
+  }
+  let leader = read(window, "WM_CLIENT_LEADER").and_then(|d| first_cardinal(&d, "WINDOW"))?;
+  if leader == 0 || leader as xlib::Window == window {
+    return None;
+  }
+  local_pid(leader as xlib::Window).flatten()
+}
+
+// `WM_CLIENT_MACHINE` がこのホストを指しているかどうか。プロパティがなければこのホストとみなす
+fn is_local_machine(machine: Option<&SelectionData>, hostname: &str) -> bool {
+  let Some(machine) = machine.filter(|m| !m.data.is_empty()) else {
+    return true;
+  };
+  let name = machine.data.split(|&b| b == 0).next().unwrap_or_default();
+  name.eq_ignore_ascii_case(hostname.as_bytes())
+}
+
+// このホストの名前
+fn local_hostname() -> Option<String> {
+  let mut buffer = [0u8; 256];
+  let result = unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+  if result != 0 {
+    return None;
+  }
+  let len = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+  String::from_utf8(buffer[..len].to_vec()).ok()
+}
+
+// 指定した型の 32 ビットのプロパティから最初の値を取り出す
+fn first_cardinal(data: &SelectionData, type_name: &str) -> Option<u32> {
+  if data.type_name != type_name {
+    return None;
+  }
+  data
+    .as_u32_items()
+    .first()
+    .copied()
+    .filter(|&value| value != 0)
+}
+
+/// プロセスの実行ファイルのパスを取得する
+///
+/// 他のユーザーのプロセスなどで読み取れない場合は None を返す。
+pub fn process_executable(pid: u32) -> Option<String> {
+  fs::read_link(format!("/proc/{}/exe", pid))
+    .ok()
+    .map(|path| path.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn property(type_name: &str, items: &[u32]) -> SelectionData {
+    SelectionData {
+      type_name: type_name.to_string(),
+      data: items.iter().flat_map(|i| i.to_le_bytes()).collect(),
+    }
+  }
+
+  // 型が一致し、0 でない最初の値だけを使う
+  #[test]
+  fn test_first_cardinal() {
+    assert_eq!(
+      first_cardinal(&property("CARDINAL", &[1234]), "CARDINAL"),
+      Some(1234)
+    );
+    assert_eq!(
+      first_cardinal(&property("WINDOW", &[1234]), "CARDINAL"),
+      None
+    );
+    assert_eq!(
+      first_cardinal(&property("CARDINAL", &[0]), "CARDINAL"),
+      None
+    );
+    assert_eq!(first_cardinal(&property("", &[]), "CARDINAL"), None);
+  }
+
+  // WM_CLIENT_MACHINE がない、またはこのホストの名前と一致する場合だけローカルとみなす
+  #[test]
+  fn test_is_local_machine() {
+    let machine = |name: &[u8]| SelectionData {
+      type_name: "STRING".to_string(),
+      data: name.to_vec(),
+    };
+    assert!(is_local_machine(None, "desk"));
+    assert!(is_local_machine(Some(&machine(b"desk")), "desk"));
+    assert!(is_local_machine(Some(&machine(b"DESK\0")), "desk"));
+    assert!(!is_local_machine(Some(&machine(b"build-server")), "desk"));
+    assert!(!is_local_machine(
+      Some(&machine(b"desk.example.com")),
+      "desk"
+    ));
+    assert!(local_hostname().is_some_and(|name| !name.is_empty()));
+  }
+
+  // 自分自身のプロセスの実行ファイルを取得できる
+  #[test]
+  fn test_process_executable() {
+    let exe = process_executable(std::process::id()).unwrap();
+    assert_eq!(
+      fs::canonicalize(exe).unwrap(),
+      fs::canonicalize(std::env::current_exe().unwrap()).unwrap()
+    );
+    assert_eq!(process_executable(u32::MAX), None);
+  }
+
+  // X サーバーがある環境では、自分が所有したセレクションの所有者として自分のプロセスが分かる
+  #[test]
+  fn test_x11_owner_process() {
+    if std::env::var("DISPLAY").map_or(true, |d| d.is_empty()) {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let owner = super::super::x11_owner::SelectionOwner::acquire(
+      Selection::Primary,
+      vec![("text/plain".to_string(), b"owner".to_vec())],
+    )
+    .unwrap();
+    let info = selection_owner_process(owner.connection(), Selection::Primary);
+    assert_eq!(info.window, Some(owner.connection().window()));
+    assert_eq!(info.pid, Some(std::process::id()));
+  }
+}
@@ -0,0 +1,181 @@
+// 書き込んだ内容の所有状態を追跡する実装
+//
+// X11 では書き込み直後のセレクション所有者ウィンドウを記録し、XFIXES の通知
+// （SelectionClear に相当する所有者の変更）で別のクライアントに取得されたことを検知する。
+// Wayland では wl-copy --foreground のプロセスが、セレクションが置き換えられた
+// （data-control の cancelled）ときに終了することを利用する。
+
+use std::io::{Error, ErrorKind};
+use std::process::Child;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11::xlib;
+
+use super::helper::{self, Selection};
+use super::x11_selection::{owner_change, XConnection};
+use crate::platforms::ownership::{poll_until_lost, POLL_INTERVAL};
+use crate::platforms::timeout;
+
+// バックグラウンドのヘルパーがセレクションを取得したか確認する間隔
+const OWNER_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+// バックグラウンドのヘルパーがセレクションを取得するまで待つ最大の時間
+// （Wayland 経由で書き込んだ場合など、X11 の所有者が変わらないこともあるため短くする）
+const OWNER_CHANGE_WAIT: Duration = Duration::from_millis(250);
+
+/// 書き込んだ内容の所有状態
+#[derive(Debug)]
+pub enum Ownership {
+  /// X11 のセレクションを `owner` ウィンドウが所有している
+  X11 {
+    selection: Selection,
+    owner: xlib::Window,
+    // is_owner で使う接続（呼び出しごとに接続しない）
+    conn: Mutex<XConnection>,
+  },
+  /// セレクションを保持している wl-copy --foreground のプロセス
+  Holder(Arc<Mutex<Child>>),
+  /// X サーバーも wl-copy もなく、所有状態を追跡できない
+  Untracked,
+}
+
+impl Ownership {
+  /// `write` を実行し、書き込んだ内容の所有者を記録する
+  pub fn track(
+    selection: Selection,
+    write: impl FnOnce() -> Result<(), Error>,
+  ) -> Result<Self, Error> {
+    let conn = XConnection::open().ok();
+    let before = conn.as_ref().map(|c| c.owner(selection));
+    // 以前の書き込みで残っているプロセスは対象外
+    helper::take_holder();
+
+    write()?;
+
+    if let Some(holder) = helper::take_holder() {
+      return Ok(Ownership::Holder(holder));
+    }
+    let (Some(conn), Some(before)) = (conn, before) else {
+      return Ok(Ownership::Untracked);
+    };
+
+    // xclip などはバックグラウンドのプロセスがセレクションを取得するため、
+    // 書き込みコマンドの終了直後には所有者がまだ変わっていないことがある
+    let deadline = Instant::now() + OWNER_CHANGE_WAIT.min(timeout::timeout());
+    let owner = loop {
+      let owner = conn.owner(selection);
+      if owner != 0 && owner != before {
+        break owner;
+      }
+      // 以前の所有者（他のアプリ）を記録すると、その内容を所有しているとみなしてしまう
+      if Instant::now() >= deadline {
+        return Ok(Ownership::Untracked);
+      }
+      thread::sleep(OWNER_POLL_INTERVAL);
+    };
+
+    Ok(Ownership::X11 {
+      selection,
+      owner,
+      conn: Mutex::new(conn),
+    })
+  }
+
+  /// 書き込んだ内容がまだセレクションにあるかどうか
+  pub fn is_owner(&self) -> Result<bool, Error> {
+    match self {
+      Ownership::X11 {
+        selection,
+        owner,
+        conn,
+      } => {
+        let conn = conn.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(conn.owner(*selection) == *owner)
+      }
+      Ownership::Holder(holder) => {
+        let mut holder = holder.lock().unwrap_or_else(|e| e.into_inner());
+        Ok(holder.try_wait()?.is_none())
+      }
+      Ownership::Untracked => Err(untracked()),
+    }
+  }
+
+  /// 所有を失うか `stop` が true になるまで待つ
+  ///
+  /// 所有を失った場合は true、打ち切られた場合は false を返す。
+  pub fn wait_until_lost(&self, stop: &AtomicBool) -> Result<bool, Error> {
+    match self {
+      Ownership::X11 {
+        selection, owner, ..
+      } => wait_for_owner_change(*selection, *owner, stop),
+      Ownership::Holder(_) => poll_until_lost(|| self.is_owner(), stop, POLL_INTERVAL),
+      Ownership::Untracked => Err(untracked()),
+    }
+  }
+}
+
+// 所有者の変更通知を待つ。XFIXES がない場合は所有者を定期的に確認する
+fn wait_for_owner_change(
+  selection: Selection,
+  owner: xlib::Window,
+  stop: &AtomicBool,
+) -> Result<bool, Error> {
+  let conn = XConnection::open()?;
+  let Some(event_base) = conn.watch_owner_changes(&[selection]) else {
+    return poll_until_lost(|| Ok(conn.owner(selection) == owner), stop, POLL_INTERVAL);
+  };
+
+  // 通知を受け取る設定をする前に所有者が変わっていた場合に備えて一度確認する
+  if conn.owner(selection) != owner {
+    return Ok(true);
+  }
+
+  let selection_atom = conn.selection_atom(selection);
+  while !stop.load(Ordering::SeqCst) {
+    while let Some(event) = conn.next_event(Instant::now() + POLL_INTERVAL) {
+      if let Some((atom, new_owner)) = owner_change(&event, event_base) {
+        if atom == selection_atom && new_owner != owner {
+          return Ok(true);
+        }
+      }
+    }
+  }
+  Ok(false)
+}
+
+fn untracked() -> Error {
+  Error::new(
+    ErrorKind::Unsupported,
+    "Clipboard ownership cannot be tracked without an X server or wl-copy",
+  )
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 追跡できない場合は is_owner / wait_until_lost がエラーになる
+  #[test]
+  fn test_untracked_ownership() {
+    let ownership = Ownership::Untracked;
+    assert_eq!(
+      ownership.is_owner().unwrap_err().kind(),
+      ErrorKind::Unsupported
+    );
+    let stop = AtomicBool::new(false);
+    assert!(ownership.wait_until_lost(&stop).is_err());
+  }
+
+  // 書き込みのエラーはそのまま返される
+  #[test]
+  fn test_track_propagates_write_error() {
+    let err = Ownership::track(Selection::Clipboard, || {
+      Err(Error::new(ErrorKind::InvalidInput, "bad path"))
+    })
+    .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+  }
+}
@@ -0,0 +1,229 @@
+// ファイルパスをクリップボードやドラッグ＆ドロップで受け渡す形式に変換する
+//
+// クリップボードへの書き込みと XDND のドラッグで同じ内容を提供するため、
+// ターゲット名とデータの組をここでまとめて作る。
+
+use std::fs;
+use std::io::{Error, ErrorKind};
+
+use crate::platforms::path_mapping::{map_path, PathMapping};
+use crate::platforms::text_representation::{file_uri, location_uri, PathText};
+
+/// ファイルの一覧を表すターゲット
+pub const URI_LIST: &str = "text/uri-list";
+
+/// GNOME 系のファイルマネージャー（Nautilus など）が貼り付けに使うターゲット
+pub const GNOME_COPIED_FILES: &str = "x-special/gnome-copied-files";
+
+// テキストとして貼り付けたときのターゲット
+const TEXT_TARGETS: [&str; 3] = ["UTF8_STRING", "text/plain;charset=utf-8", "text/plain"];
+
+/// ファイルパスを受け渡すためのターゲット名とデータの組
+///
+/// 最初の要素は `text/uri-list`。各パスは正規化され、存在しないパスが
+/// 一つでもあればエラーになる。テキストのターゲットには `text` の形式のテキストを入れる
+/// （None の場合は改行区切りのパス、`TextRepresentation::None` の場合はテキストを提供しない）。
+pub fn file_paths_payload(
+  paths: &[String],
+  text: Option<&PathText>,
+) -> Result<Vec<(String, Vec<u8>)>, Error> {
+  let mut canonical_paths = Vec::new();
+  let mut errors = Vec::new();
+
+  for path in paths {
+    match fs::canonicalize(path) {
+      Ok(p) => canonical_paths.push(p.display().to_string()),
+      Err(e) => errors.push(format!("Failed to canonicalize path {}: {}", path, e)),
+    }
+  }
+
+  // 無効なパスが一つでもあればエラー
+  if !errors.is_empty() {
+    let error_message = format!("Some paths could not be processed: {}", errors.join("; "));
+    return Err(Error::new(ErrorKind::InvalidInput, error_message));
+  }
+
+  Ok(payload_for_canonical_paths(&canonical_paths, text))
+}
+
+/// テキストを受け渡すためのターゲット名とデータの組
+///
+/// `paths`（絶対パス）が空でなければ、その一覧を `text/uri-list` としても提供する。
+pub fn text_payload(text: &str, paths: &[String]) -> Vec<(String, Vec<u8>)> {
+  let mut payload = Vec::new();
+  if !paths.is_empty() {
+    payload.push((URI_LIST.to_string(), uri_list(paths, &[]).into_bytes()));
+  }
+  payload.extend(
+    TEXT_TARGETS
+      .iter()
+      .map(|target| (target.to_string(), text.as_bytes().to_vec())),
+  );
+  payload
+}
+
+// 正規化済みのパスから各ターゲットのデータを作る
+fn payload_for_canonical_paths(
+  paths: &[String],
+  text: Option<&PathText>,
+) -> Vec<(String, Vec<u8>)> {
+  let mappings = text.map_or(&[][..], |text| text.mappings.as_slice());
+  let uri_list = uri_list(paths, mappings);
+  let text = match text {
+    Some(representation) => representation.render(paths),
+    None => Some(paths.join("\n")),
+  };
+
+  let mut payload = vec![
+    (URI_LIST.to_string(), uri_list.clone().into_bytes()),
+    (
+      GNOME_COPIED_FILES.to_string(),
+      format!("copy\n{}", uri_list).into_bytes(),
+    ),
+  ];
+  if let Some(text) = text {
+    payload.extend(
+      TEXT_TARGETS
+        .iter()
+        .map(|target| (target.to_string(), text.clone().into_bytes())),
+    );
+  }
+  payload
+}
+
+// URI を改行区切りでつなげる（text/uri-list フォーマット）
+// 対応表で変換できるパスは変換した先の URI（sftp:// など）にする。どちらもパーセントエンコードする
+fn uri_list(paths: &[String], mappings: &[PathMapping]) -> String {
+  let uris: Vec<String> = paths
+    .iter()
+    .map(|p| match map_path(p, mappings) {
+      Some(mapped) => location_uri(&mapped),
+      None => file_uri(p),
+    })
+    .collect();
+  uris.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::platforms::text_representation::TextRepresentation;
+
+  fn target<'a>(payload: &'a [(String, Vec<u8>)], name: &str) -> &'a str {
+    let (_, data) = payload.iter().find(|(t, _)| t == name).unwrap();
+    std::str::from_utf8(data).unwrap()
+  }
+
+  // 各ターゲットに同じファイルの一覧が入る
+  #[test]
+  fn test_payload_targets() {
+    let payload = payload_for_canonical_paths(&["/tmp/a b.txt".into(), "/tmp/c".into()], None);
+    assert_eq!(payload[0].0, URI_LIST);
+    assert_eq!(
+      target(&payload, URI_LIST),
+      "file:///tmp/a%20b.txt\nfile:///tmp/c"
+    );
+    assert_eq!(
+      target(&payload, GNOME_COPIED_FILES),
+      "copy\nfile:///tmp/a%20b.txt\nfile:///tmp/c"
+    );
+    assert_eq!(target(&payload, "UTF8_STRING"), "/tmp/a b.txt\n/tmp/c");
+    assert_eq!(target(&payload, "text/plain"), "/tmp/a b.txt\n/tmp/c");
+  }
+
+  // 指定した形式のテキストが入り、`none` ではテキストのターゲットを提供しない
+  #[test]
+  fn test_payload_text_representation() {
+    let paths: Vec<String> = vec!["/tmp/a b.txt".into(), "/tmp/c".into()];
+    let payload =
+      payload_for_canonical_paths(&paths, Some(&TextRepresentation::ShellQuoted.into()));
+    assert_eq!(target(&payload, "UTF8_STRING"), "'/tmp/a b.txt' /tmp/c");
+    assert_eq!(
+      target(&payload, URI_LIST),
+      "file:///tmp/a%20b.txt\nfile:///tmp/c"
+    );
+
+    let payload = payload_for_canonical_paths(&paths, Some(&TextRepresentation::None.into()));
+    let targets: Vec<&str> = payload.iter().map(|(t, _)| t.as_str()).collect();
+    assert_eq!(targets, [URI_LIST, GNOME_COPIED_FILES]);
+  }
+
+  // 対応表で変換したパスの URI が text/uri-list に入る。変換しないパスも同じようにエンコードする
+  #[test]
+  fn test_payload_path_mappings() {
+    let paths: Vec<String> = vec!["/home/me/mnt/server/a b".into(), "/tmp/c d".into()];
+    let text = PathText {
+      representation: TextRepresentation::Paths,
+      relative_to: None,
+      mappings: vec![PathMapping::new(
+        "/home/me/mnt/server",
+        "sftp://me@server/srv",
+      )],
+    };
+    let payload = payload_for_canonical_paths(&paths, Some(&text));
+    assert_eq!(
+      target(&payload, URI_LIST),
+      "sftp://me@server/srv/a%20b\nfile:///tmp/c%20d"
+    );
+    assert_eq!(
+      target(&payload, "UTF8_STRING"),
+      "sftp://me@server/srv/a%20b\n/tmp/c d"
+    );
+  }
+
+  // テキストは各テキストのターゲットに入り、パスがあれば text/uri-list も提供する
+  #[test]
+  fn test_text_payload() {
+    let payload = text_payload("see /tmp/a\r\n", &[]);
+    assert!(payload.iter().all(|(t, _)| t != URI_LIST));
+    assert_eq!(target(&payload, "UTF8_STRING"), "see /tmp/a\r\n");
+    assert_eq!(
+      target(&payload, "text/plain;charset=utf-8"),
+      "see /tmp/a\r\n"
+    );
+
+    let payload = text_payload("/tmp/a\n/tmp/b", &["/tmp/a".into(), "/tmp/b".into()]);
+    assert_eq!(payload[0].0, URI_LIST);
+    assert_eq!(target(&payload, URI_LIST), "file:///tmp/a\nfile:///tmp/b");
+    assert_eq!(target(&payload, "UTF8_STRING"), "/tmp/a\n/tmp/b");
+  }
+
+  // 書き込んだ text/uri-list を読み取り側の FileEntry::from_uri で元のパスに戻せる
+  #[test]
+  fn test_payload_uri_round_trip() {
+    use crate::platforms::file_entry::FileEntry;
+    use crate::platforms::linux::helper::parse_uri_list;
+
+    let dir = std::env::temp_dir().join(format!("clip-filepaths-payload-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let paths: Vec<String> = ["a%41 b.txt", "100%", "c#d?.txt"]
+      .iter()
+      .map(|name| {
+        let path = dir.join(name);
+        fs::write(&path, "x").unwrap();
+        path.canonicalize().unwrap().to_string_lossy().into_owned()
+      })
+      .collect();
+
+    let payload = file_paths_payload(&paths, None).unwrap();
+    let uris = parse_uri_list(target(&payload, URI_LIST));
+    assert!(uris[0].ends_with("/a%2541%20b.txt"), "{}", uris[0]);
+    let read: Vec<String> = uris
+      .iter()
+      .filter_map(|uri| FileEntry::from_uri(uri).path)
+      .collect();
+    assert_eq!(read, paths);
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+
+  // 存在しないパスはエラーになる
+  #[test]
+  fn test_payload_rejects_missing_paths() {
+    let err = file_paths_payload(&["/path/does/not/exist/payload.txt".into()], None).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    assert!(err
+      .to_string()
+      .contains("Some paths could not be processed"));
+  }
+}
@@ -0,0 +1,519 @@
+// X11 のセレクションを所有してデータを提供する実装
+//
+// 他のクライアントからの SelectionRequest に応答する。X サーバーの最大リクエストサイズを
+// 超えるデータは ICCCM の INCR 転送で分割して送る。
+
+use std::io::Error;
+use std::os::raw::{c_int, c_long, c_uchar};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use x11::xlib;
+
+use super::helper::Selection;
+use super::x11_selection::XConnection;
+use crate::platforms::lazy::Contents;
+use crate::platforms::retry;
+use crate::platforms::timeout;
+
+// プロパティ以外のリクエストのために確保しておくバイト数
+const REQUEST_OVERHEAD: usize = 100;
+
+/// 所有しているセレクションの状態
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OwnerState {
+  /// セレクションを所有している
+  Owned,
+  /// 他のクライアントがセレクションを取得した
+  Lost,
+}
+
+// 進行中の INCR 転送
+struct IncrTransfer {
+  requestor: xlib::Window,
+  property: xlib::Atom,
+  type_atom: xlib::Atom,
+  data: Arc<[u8]>,
+  offset: usize,
+  // 長さ 0 のチャンクを送って終了を通知したかどうか
+  finished: bool,
+  // 受け取り側が最後に応答した時刻（応答しなくなった転送を破棄するため）
+  last_activity: Instant,
+}
+
+/// X11 のセレクションの所有者
+pub struct SelectionOwner {
+  conn: XConnection,
+  selection: xlib::Atom,
+  time: xlib::Time,
+  // ターゲットのアトムと、対応する `contents` のフォーマット名
+  targets: Vec<(xlib::Atom, String)>,
+  contents: Contents,
+  transfers: Vec<IncrTransfer>,
+  // 1 回の XChangeProperty で送るデータの最大バイト数
+  max_chunk: usize,
+  state: OwnerState,
+}
+
+impl SelectionOwner {
+  /// セレクションを取得し、`contents`（ターゲット名とデータの組）を提供する
+  pub fn acquire(selection: Selection, contents: impl Into<Contents>) -> Result<Self, Error> {
+    let conn = XConnection::open()?;
+    let selection = conn.selection_atom(selection);
+    Self::acquire_atom(conn, selection, contents)
+  }
+
+  /// 任意のセレクションアトムを取得する
+  pub fn acquire_atom(
+    conn: XConnection,
+    selection: xlib::Atom,
+    contents: impl Into<Contents>,
+  ) -> Result<Self, Error> {
+    let contents = contents.into();
+    let targets = contents
+      .formats()
+      .map(|target| (conn.atom(target), target.to_string()))
+      .collect();
+
+    // 他のクライアントが同時に所有者になった場合は取得できないため、再試行する
+    // 古い時刻では後から所有者になったクライアントに負けるため、試行ごとに時刻を取り直す
+    let time = retry::retry("Acquiring the X11 selection", || {
+      let time = conn.server_time(timeout::timeout())?;
+      let owner = unsafe {
+        xlib::XSetSelectionOwner(conn.display(), selection, conn.window(), time);
+        xlib::XGetSelectionOwner(conn.display(), selection)
+      };
+      if owner != conn.window() {
+        return Err(retry::busy("Failed to acquire X11 selection ownership"));
+      }
+      Ok(time)
+    })?;
+
+    let max_request = unsafe { xlib::XMaxRequestSize(conn.display()) } as usize * 4;
+    Ok(Self {
+      conn,
+      selection,
+      time,
+      targets,
+      contents,
+      transfers: Vec::new(),
+      max_chunk: max_request.saturating_sub(REQUEST_OVERHEAD).max(4096),
+      state: OwnerState::Owned,
+    })
+  }
+
+  /// セレクションを所有している接続
+  pub fn connection(&self) -> &XConnection {
+    &self.conn
+  }
+
+  /// 進行中の INCR 転送があるかどうか
+  pub fn has_pending_transfers(&self) -> bool {
+    !self.transfers.is_empty()
+  }
+
+  /// `wait` の間、届いたリクエストに応答する
+  ///
+  /// セレクションを失った後も、進行中の INCR 転送は最後まで送る。
+  pub fn process_events(&mut self, wait: Duration) -> OwnerState {
+    self.process_events_with(wait, |_| {})
+  }
+
+  /// [`process_events`](Self::process_events) と同じだが、セレクション以外のイベント
+  /// （ClientMessage やポインターのイベントなど）を `other` に渡す
+  pub fn process_events_with(
+    &mut self,
+    wait: Duration,
+    mut other: impl FnMut(&xlib::XEvent),
+  ) -> OwnerState {
+    let deadline = Instant::now() + wait;
+    while let Some(event) = self.conn.next_event(deadline) {
+      if !self.handle_event(&event) {
+        other(&event);
+      }
+    }
+    self.drop_stalled_transfers();
+    self.state
+  }
+
+  // セレクションに関するイベントを処理する。それ以外のイベントなら false
+  fn handle_event(&mut self, event: &xlib::XEvent) -> bool {
+    match event.get_type() {
+      xlib::SelectionRequest => {
+        let request = unsafe { event.selection_request };
+        self.handle_request(&request);
+      }
+      xlib::SelectionClear => {
+        let clear = unsafe { event.selection_clear };
+        if clear.selection == self.selection && clear.window == self.conn.window() {
+          self.state = OwnerState::Lost;
+        }
+      }
+      xlib::PropertyNotify => {
+        let notify = unsafe { event.property };
+        if notify.state == xlib::PropertyDelete {
+          self.continue_transfer(notify.window, notify.atom);
+        }
+      }
+      _ => return false,
+    }
+    true
+  }
+
+  // SelectionRequest に応答する
+  fn handle_request(&mut self, request: &xlib::XSelectionRequestEvent) {
+    // 古いクライアントは property に None を指定するので、ターゲットを代わりに使う
+    let property = if request.property == 0 {
+      request.target
+    } else {
+      request.property
+    };
+
+    let accepted = request.selection == self.selection
+      && self.state == OwnerState::Owned
+      && (request.time == xlib::CurrentTime || request.time >= self.time)
+      && self.reply(request.requestor, property, request.target);
+
+    self.send_notify(request, if accepted { property } else { 0 });
+  }
+
+  // 要求されたターゲットのデータをプロパティに書き込む。対応していなければ false
+  fn reply(&mut self, requestor: xlib::Window, property: xlib::Atom, target: xlib::Atom) -> bool {
+    let display = self.conn.display();
+
+    if target == self.conn.atom("TARGETS") {
+      let mut targets: Vec<xlib::Atom> =
+        vec![self.conn.atom("TARGETS"), self.conn.atom("TIMESTAMP")];
+      targets.extend(self.targets.iter().map(|(atom, _)| *atom));
+      unsafe {
+        xlib::XChangeProperty(
+          display,
+          requestor,
+          property,
+          xlib::XA_ATOM,
+          32,
+          xlib::PropModeReplace,
+          targets.as_ptr() as *const c_uchar,
+          targets.len() as c_int,
+        );
+      }
+      return true;
+    }
+
+    if target == self.conn.atom("TIMESTAMP") {
+      let time = self.time as c_long;
+      unsafe {
+        xlib::XChangeProperty(
+          display,
+          requestor,
+          property,
+          xlib::XA_INTEGER,
+          32,
+          xlib::PropModeReplace,
+          &time as *const c_long as *const c_uchar,
+          1,
+        );
+      }
+      return true;
+    }
+
+    // 遅延レンダリングの場合は、ここで初めてデータを生成する
+    // 生成に失敗した場合は、提供しないフォーマットと同じく要求を拒否する
+    let Some((type_atom, format)) = self.targets.iter().find(|(atom, _)| *atom == target) else {
+      return false;
+    };
+    let type_atom = *type_atom;
+    let Ok(Some(data)) = self.contents.get(format) else {
+      return false;
+    };
+
+    if data.len() <= self.max_chunk {
+      change_property_bytes(display, requestor, property, type_atom, &data);
+      return true;
+    }
+
+    // 大きなデータは INCR で送る。最初にデータサイズの下限を INCR 型で書き込み、
+    // 受け取り側がプロパティを削除するたびに次のチャンクを書き込む
+    let size = data.len() as c_long;
+    unsafe {
+      xlib::XSelectInput(display, requestor, xlib::PropertyChangeMask);
+      xlib::XChangeProperty(
+        display,
+        requestor,
+        property,
+        self.conn.atom("INCR"),
+        32,
+        xlib::PropModeReplace,
+        &size as *const c_long as *const c_uchar,
+        1,
+      );
+    }
+    self
+      .transfers
+      .retain(|t| !(t.requestor == requestor && t.property == property));
+    self.transfers.push(IncrTransfer {
+      requestor,
+      property,
+      type_atom,
+      data,
+      offset: 0,
+      finished: false,
+      last_activity: Instant::now(),
+    });
+    true
+  }
+
+  // 受け取り側がプロパティを削除したら次のチャンクを送る
+  fn continue_transfer(&mut self, window: xlib::Window, property: xlib::Atom) {
+    let display = self.conn.display();
+    let Some(index) = self
+      .transfers
+      .iter()
+      .position(|t| t.requestor == window && t.property == property)
+    else {
+      return;
+    };
+
+    let transfer = &mut self.transfers[index];
+    if transfer.finished {
+      // 長さ 0 のチャンクが読み取られたので転送完了
+      unsafe {
+        xlib::XSelectInput(display, window, xlib::NoEventMask);
+      }
+      self.transfers.remove(index);
+      return;
+    }
+
+    let end = (transfer.offset + self.max_chunk).min(transfer.data.len());
+    change_property_bytes(
+      display,
+      window,
+      property,
+      transfer.type_atom,
+      &transfer.data[transfer.offset..end],
+    );
+    transfer.finished = transfer.offset == end;
+    transfer.offset = end;
+    transfer.last_activity = Instant::now();
+  }
+
+  // 受け取り側が応答しなくなった INCR 転送を破棄する
+  fn drop_stalled_transfers(&mut self) {
+    let timeout = timeout::timeout();
+    let display = self.conn.display();
+    self.transfers.retain(|t| {
+      let alive = t.last_activity.elapsed() < timeout;
+      if !alive {
+        unsafe {
+          xlib::XSelectInput(display, t.requestor, xlib::NoEventMask);
+        }
+      }
+      alive
+    });
+  }
+
+  fn send_notify(&self, request: &xlib::XSelectionRequestEvent, property: xlib::Atom) {
+    let mut event = xlib::XEvent {
+      selection: xlib::XSelectionEvent {
+        type_: xlib::SelectionNotify,
+        serial: 0,
+        send_event: xlib::True,
+        display: self.conn.display(),
+        requestor: request.requestor,
+        selection: request.selection,
+        target: request.target,
+        property,
+        time: request.time,
+      },
+    };
+    unsafe {
+      xlib::XSendEvent(
+        self.conn.display(),
+        request.requestor,
+        xlib::False,
+        xlib::NoEventMask,
+        &mut event,
+      );
+      xlib::XFlush(self.conn.display());
+    }
+  }
+}
+
+impl Drop for SelectionOwner {
+  fn drop(&mut self) {
+    if self.state == OwnerState::Owned {
+      unsafe {
+        xlib::XSetSelectionOwner(self.conn.display(), self.selection, 0, self.time);
+        xlib::XFlush(self.conn.display());
+      }
+    }
+  }
+}
+
+/// セレクションを取得し、他のクライアントに取得されるまでバックグラウンドで提供し続ける
+///
+/// セレクションの取得に失敗した場合はエラーを返す。
+pub fn serve_in_background(
+  selection: Selection,
+  contents: impl Into<Contents>,
+) -> Result<(), Error> {
+  let mut owner = SelectionOwner::acquire(selection, contents)?;
+  std::thread::spawn(move || {
+    while owner.process_events(Duration::from_millis(100)) == OwnerState::Owned
+      || owner.has_pending_transfers()
+    {}
+  });
+  Ok(())
+}
+
+// format 8 のデータをプロパティに書き込む
+fn change_property_bytes(
+  display: *mut xlib::Display,
+  window: xlib::Window,
+  property: xlib::Atom,
+  type_atom: xlib::Atom,
+  data: &[u8],
+) {
+  unsafe {
+    xlib::XChangeProperty(
+      display,
+      window,
+      property,
+      type_atom,
+      8,
+      xlib::PropModeReplace,
+      data.as_ptr(),
+      data.len() as c_int,
+    );
+    xlib::XFlush(display);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+  use std::thread;
+
+  static NEXT_SELECTION: AtomicUsize = AtomicUsize::new(0);
+
+  fn has_display() -> bool {
+    std::env::var("DISPLAY").is_ok_and(|d| !d.is_empty())
+  }
+
+  // 他のテストやユーザーのクリップボードと干渉しないよう、専用のセレクションで
+  // 所有者をスレッドで動かし、別の接続から読み取る
+  fn round_trip(target: &str, data: Vec<u8>) -> Option<Vec<u8>> {
+    round_trip_contents(target, Contents::from(vec![(target.to_string(), data)]))
+  }
+
+  fn round_trip_contents(target: &str, contents: Contents) -> Option<Vec<u8>> {
+    let name = format!(
+      "CLIP_FILEPATHS_TEST_{}_{}",
+      std::process::id(),
+      NEXT_SELECTION.fetch_add(1, Ordering::SeqCst)
+    );
+    let done = Arc::new(AtomicBool::new(false));
+    let (ready_tx, ready_rx) = std::sync::mpsc::channel();
+
+    let owner_thread = {
+      let done = Arc::clone(&done);
+      let name = name.clone();
+      thread::spawn(move || {
+        let conn = XConnection::open().expect("Failed to open X display");
+        let selection = conn.atom(&name);
+        let mut owner = SelectionOwner::acquire_atom(conn, selection, contents).unwrap();
+        ready_tx.send(()).unwrap();
+        while !done.load(Ordering::SeqCst) || owner.has_pending_transfers() {
+          owner.process_events(Duration::from_millis(20));
+        }
+      })
+    };
+
+    ready_rx.recv().unwrap();
+    let reader = XConnection::open().expect("Failed to open X display");
+    let result = reader
+      .convert_atom(reader.atom(&name), target, Duration::from_secs(30))
+      .unwrap();
+    done.store(true, Ordering::SeqCst);
+    owner_thread.join().unwrap();
+    result.map(|d| d.data)
+  }
+
+  // 小さなデータは 1 回のプロパティ変更で受け取れる
+  #[test]
+  fn test_round_trip_small_payload() {
+    if !has_display() {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let data = b"file:///tmp/a.txt".to_vec();
+    assert_eq!(round_trip("text/uri-list", data.clone()), Some(data));
+  }
+
+  // 最大リクエストサイズを超えるデータは INCR で分割して受け渡される
+  #[test]
+  fn test_round_trip_incr_payload() {
+    if !has_display() {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    // 数 MB の text/uri-list
+    let data: Vec<u8> = (0..100_000)
+      .map(|i| format!("file:///tmp/clip-filepaths/incr/{:08}.txt\n", i))
+      .collect::<String>()
+      .into_bytes();
+    assert!(data.len() > 4 * 1024 * 1024);
+
+    let received = round_trip("text/uri-list", data.clone()).expect("No data received");
+    assert_eq!(received.len(), data.len());
+    assert!(received == data);
+  }
+
+  // 遅延レンダリングのデータは要求されたときに生成される
+  #[test]
+  fn test_round_trip_deferred_payload() {
+    if !has_display() {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let calls = Arc::new(AtomicUsize::new(0));
+    let provider: crate::platforms::lazy::Provider = {
+      let calls = Arc::clone(&calls);
+      Arc::new(move |format| {
+        calls.fetch_add(1, Ordering::SeqCst);
+        Ok(Some(format!("rendered {}", format).into_bytes()))
+      })
+    };
+    let formats = vec!["text/plain".to_string(), "image/png".to_string()];
+    let received = round_trip_contents("text/plain", Contents::deferred(&formats, provider));
+    assert_eq!(received, Some(b"rendered text/plain".to_vec()));
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+  }
+
+  // 提供していないターゲットは拒否される
+  #[test]
+  fn test_unknown_target_is_refused() {
+    if !has_display() {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    let name = format!("CLIP_FILEPATHS_TEST_{}_refuse", std::process::id());
+    let conn = XConnection::open().unwrap();
+    let selection = conn.atom(&name);
+    let mut owner =
+      SelectionOwner::acquire_atom(conn, selection, vec![("UTF8_STRING".into(), b"x".to_vec())])
+        .unwrap();
+
+    let reader = thread::spawn(move || {
+      let reader = XConnection::open().unwrap();
+      reader
+        .convert_atom(reader.atom(&name), "image/png", Duration::from_secs(5))
+        .unwrap()
+    });
+    while !reader.is_finished() {
+      owner.process_events(Duration::from_millis(20));
+    }
+    assert!(reader.join().unwrap().is_none());
+  }
+}
@@ -0,0 +1,694 @@
+// Xlib を使って X11 のセレクションを直接扱う実装
+//
+// ヘルパーコマンドでは取得できない情報（セレクションの所有者やタイムスタンプなど）を
+// 読み取るために使う。すべての待機はタイムアウト付きで行う。
+
+use std::ffi::{CStr, CString};
+use std::io::{Error, ErrorKind};
+use std::os::raw::{c_int, c_long, c_uchar, c_ulong};
+use std::ptr;
+use std::sync::{Mutex, Once, OnceLock};
+use std::time::{Duration, Instant};
+
+use x11::{xfixes, xlib};
+
+use super::helper::Selection;
+use crate::platforms::retry;
+use crate::platforms::stream::Sink;
+use crate::platforms::timeout;
+
+// 変換結果を受け取るウィンドウプロパティの名前
+const TRANSFER_PROPERTY: &str = "CLIP_FILEPATHS_SELECTION";
+// サーバー時刻の取得に使うウィンドウプロパティの名前
+const TIMESTAMP_PROPERTY: &str = "CLIP_FILEPATHS_TIMESTAMP";
+
+// XFixesSelectNotify のサブタイプとマスク（Xfixes.h の定義）
+const XFIXES_SELECTION_NOTIFY: c_int = 0;
+const XFIXES_SET_SELECTION_OWNER_NOTIFY_MASK: c_ulong = 1 << 0;
+const XFIXES_SELECTION_WINDOW_DESTROY_NOTIFY_MASK: c_ulong = 1 << 1;
+const XFIXES_SELECTION_CLIENT_CLOSE_NOTIFY_MASK: c_ulong = 1 << 2;
+
+static INIT: Once = Once::new();
+
+// Xlib のエラーハンドラー
+type ErrorHandler =
+  Option<unsafe extern "C" fn(*mut xlib::Display, *mut xlib::XErrorEvent) -> c_int>;
+
+// 差し替える前のエラーハンドラー（Electron / Chromium が設定したものなど）
+static PREVIOUS_ERROR_HANDLER: OnceLock<ErrorHandler> = OnceLock::new();
+
+// このモジュールで開いた Display のアドレス
+static OWN_DISPLAYS: Mutex<Vec<usize>> = Mutex::new(Vec::new());
+
+// エラーハンドラーはプロセス全体で 1 つのため、このモジュールの接続のエラーだけを無視し
+// （Xlib のデフォルトのハンドラーはプロセスを終了させる）、それ以外の接続のエラーは
+// 元のハンドラーに渡す
+unsafe extern "C" fn handle_x_error(
+  display: *mut xlib::Display,
+  event: *mut xlib::XErrorEvent,
+) -> c_int {
+  forward_x_error(
+    display,
+    event,
+    PREVIOUS_ERROR_HANDLER.get().copied().flatten(),
+  )
+}
+
+unsafe fn forward_x_error(
+  display: *mut xlib::Display,
+  event: *mut xlib::XErrorEvent,
+  previous: ErrorHandler,
+) -> c_int {
+  let own = OWN_DISPLAYS
+    .lock()
+    .unwrap_or_else(|e| e.into_inner())
+    .contains(&(display as usize));
+  match previous {
+    Some(previous) if !own => previous(display, event),
+    _ => 0,
+  }
+}
+
+fn init_xlib() {
+  INIT.call_once(|| unsafe {
+    xlib::XInitThreads();
+    let previous = xlib::XSetErrorHandler(Some(handle_x_error));
+    let _ = PREVIOUS_ERROR_HANDLER.set(previous);
+  });
+}
+
+// スレッド間で受け渡すための Display の所有権ラッパー
+struct OwnedDisplay(*mut xlib::Display);
+
+// Display は同時に 1 スレッドからしか使わない（XInitThreads 済み）
+unsafe impl Send for OwnedDisplay {}
+
+impl OwnedDisplay {
+  // エラーハンドラーで区別できるよう、このモジュールの Display として登録する
+  fn new(display: *mut xlib::Display) -> Self {
+    OWN_DISPLAYS
+      .lock()
+      .unwrap_or_else(|e| e.into_inner())
+      .push(display as usize);
+    Self(display)
+  }
+}
+
+impl Drop for OwnedDisplay {
+  fn drop(&mut self) {
+    if !self.0.is_null() {
+      OWN_DISPLAYS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|d| *d != self.0 as usize);
+      unsafe {
+        xlib::XCloseDisplay(self.0);
+      }
+    }
+  }
+}
+
+/// X サーバーへの接続と、セレクションの受け取りに使う非表示ウィンドウ
+pub struct XConnection {
+  display: OwnedDisplay,
+  window: xlib::Window,
+}
+
+/// セレクションの変換結果
+#[derive(Debug, Clone)]
+pub struct SelectionData {
+  /// データの型（`UTF8_STRING`, `ATOM` など）
+  pub type_name: String,
+  /// データ本体。format が 32 の場合は 4 バイトずつのリトルエンディアン
+  pub data: Vec<u8>,
+}
+
+impl SelectionData {
+  /// format が 32 のデータを u32 の列として取り出す
+  pub fn as_u32_items(&self) -> Vec<u32> {
+    self
+      .data
+      .chunks_exact(4)
+      .map(|c| u32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+      .collect()
+  }
+}
+
+impl XConnection {
+  /// DISPLAY 環境変数に従って X サーバーに接続する
+  pub fn open() -> Result<Self, Error> {
+    init_xlib();
+
+    // 応答しない X サーバーで呼び出し元が固まらないよう、接続はタイムアウト付きで行う
+    let display = timeout::run_with_timeout("X11 connection", timeout::timeout(), || {
+      let display = unsafe { xlib::XOpenDisplay(ptr::null()) };
+      if display.is_null() {
+        Err(Error::new(
+          ErrorKind::NotConnected,
+          "Failed to open X display",
+        ))
+      } else {
+        Ok(OwnedDisplay::new(display))
+      }
+    })?;
+
+    let window = unsafe {
+      let root = xlib::XDefaultRootWindow(display.0);
+      let window = xlib::XCreateSimpleWindow(display.0, root, 0, 0, 1, 1, 0, 0, 0);
+      // INCR 転送やプロパティの削除を検知するため PropertyNotify を受け取る
+      xlib::XSelectInput(display.0, window, xlib::PropertyChangeMask);
+      // セレクションの所有者からプロセスを辿れるよう _NET_WM_PID を設定する
+      let pid_name = CString::new("_NET_WM_PID").unwrap_or_default();
+      let pid_atom = xlib::XInternAtom(display.0, pid_name.as_ptr(), xlib::False);
+      let pid = c_ulong::from(std::process::id());
+      xlib::XChangeProperty(
+        display.0,
+        window,
+        pid_atom,
+        xlib::XA_CARDINAL,
+        32,
+        xlib::PropModeReplace,
+        &pid as *const c_ulong as *const c_uchar,
+        1,
+      );
+      window
+    };
+
+    Ok(Self { display, window })
+  }
+
+  pub fn display(&self) -> *mut xlib::Display {
+    self.display.0
+  }
+
+  /// セレクションの受け取りに使う非表示ウィンドウ
+  pub fn window(&self) -> xlib::Window {
+    self.window
+  }
+
+  /// アトムを取得する（存在しなければ作成する）
+  pub fn atom(&self, name: &str) -> xlib::Atom {
+    let name = CString::new(name).unwrap_or_default();
+    unsafe { xlib::XInternAtom(self.display(), name.as_ptr(), xlib::False) }
+  }
+
+  /// アトムの名前を取得する
+  pub fn atom_name(&self, atom: xlib::Atom) -> Option<String> {
+    if atom == 0 {
+      return None;
+    }
+    unsafe {
+      let name = xlib::XGetAtomName(self.display(), atom);
+      if name.is_null() {
+        return None;
+      }
+      let result = CStr::from_ptr(name).to_string_lossy().into_owned();
+      xlib::XFree(name as *mut _);
+      Some(result)
+    }
+  }
+
+  /// セレクションに対応するアトム
+  pub fn selection_atom(&self, selection: Selection) -> xlib::Atom {
+    match selection {
+      Selection::Clipboard => self.atom("CLIPBOARD"),
+      Selection::Primary => xlib::XA_PRIMARY,
+    }
+  }
+
+  /// セレクションの所有者ウィンドウ。所有者がいない場合は 0
+  pub fn owner(&self, selection: Selection) -> xlib::Window {
+    unsafe { xlib::XGetSelectionOwner(self.display(), self.selection_atom(selection)) }
+  }
+
+  /// セレクションの所有者をなくす（空にする）
+  ///
+  /// 現在の所有者には SelectionClear が送られる。
+  pub fn clear_owner(&self, selection: Selection) -> Result<(), Error> {
+    let time = self.server_time(timeout::timeout())?;
+    unsafe {
+      xlib::XSetSelectionOwner(self.display(), self.selection_atom(selection), 0, time);
+      xlib::XSync(self.display(), xlib::False);
+    }
+    Ok(())
+  }
+
+  /// X サーバーの現在時刻を取得する
+  ///
+  /// ICCCM はセレクションの取得に CurrentTime を使わないよう求めているため、
+  /// 自分のウィンドウのプロパティを変更して届く PropertyNotify の時刻を使う。
+  pub fn server_time(&self, timeout: Duration) -> Result<xlib::Time, Error> {
+    let property = self.atom(TIMESTAMP_PROPERTY);
+    unsafe {
+      xlib::XChangeProperty(
+        self.display(),
+        self.window,
+        property,
+        xlib::XA_STRING,
+        8,
+        xlib::PropModeAppend,
+        ptr::null(),
+        0,
+      );
+      xlib::XFlush(self.display());
+    }
+
+    let window = self.window;
+    let event = self.wait_for_event(timeout, "X server time", |e| {
+      e.get_type() == xlib::PropertyNotify
+        && unsafe { e.property.window } == window
+        && unsafe { e.property.atom } == property
+    })?;
+    Ok(unsafe { event.property.time })
+  }
+
+  /// セレクションの所有者が変わったときに XFixesSelectionNotify を受け取るようにする
+  ///
+  /// XFIXES のイベント番号の基準値を返す。XFIXES が使えない場合は None。
+  pub fn watch_owner_changes(&self, selections: &[Selection]) -> Option<c_int> {
+    let event_base = unsafe {
+      let (mut event_base, mut error_base) = (0, 0);
+      if xfixes::XFixesQueryExtension(self.display(), &mut event_base, &mut error_base) == 0 {
+        return None;
+      }
+      // バージョンを通知しないとイベントの選択が受け付けられない
+      let (mut major, minor) = (5, 0);
+      xfixes::XFixesQueryVersion(self.display(), &mut major, &minor);
+      event_base
+    };
+
+    for selection in selections {
+      unsafe {
+        xfixes::XFixesSelectSelectionInput(
+          self.display(),
+          self.window,
+          self.selection_atom(*selection),
+          XFIXES_SET_SELECTION_OWNER_NOTIFY_MASK
+            | XFIXES_SELECTION_WINDOW_DESTROY_NOTIFY_MASK
+            | XFIXES_SELECTION_CLIENT_CLOSE_NOTIFY_MASK,
+        );
+      }
+    }
+    unsafe {
+      xlib::XFlush(self.display());
+    }
+    Some(event_base)
+  }
+
+  /// 拡張機能が利用可能かどうか
+  pub fn has_extension(&self, name: &str) -> bool {
+    let name = CString::new(name).unwrap_or_default();
+    let (mut opcode, mut event_base, mut error_base) = (0, 0, 0);
+    unsafe {
+      xlib::XQueryExtension(
+        self.display(),
+        name.as_ptr(),
+        &mut opcode,
+        &mut event_base,
+        &mut error_base,
+      ) != 0
+    }
+  }
+
+  /// セレクションを指定したターゲットに変換して読み取る
+  ///
+  /// 所有者がいない、または所有者が変換を拒否した場合は None を返す。
+  pub fn convert(
+    &self,
+    selection: Selection,
+    target: &str,
+    timeout: Duration,
+  ) -> Result<Option<SelectionData>, Error> {
+    self.convert_atom(self.selection_atom(selection), target, timeout)
+  }
+
+  /// 任意のセレクションアトムを指定したターゲットに変換して読み取る
+  ///
+  /// データが大きい場合の ICCCM の INCR 転送にも対応する。
+  pub fn convert_atom(
+    &self,
+    selection_atom: xlib::Atom,
+    target: &str,
+    timeout: Duration,
+  ) -> Result<Option<SelectionData>, Error> {
+    let mut data = Vec::new();
+    let type_name = self.convert_atom_to(selection_atom, target, timeout, &mut |chunk| {
+      data.extend_from_slice(chunk);
+      Ok(())
+    })?;
+    Ok(type_name.map(|type_name| SelectionData { type_name, data }))
+  }
+
+  /// セレクションを変換し、受け取ったデータを届いた順に `sink` へ渡す
+  ///
+  /// INCR 転送ではチャンクごとに渡すため、データ全体をメモリに溜めない。
+  /// 変換できた場合はデータの型名を、拒否された場合は None を返す。
+  pub fn convert_to(
+    &self,
+    selection: Selection,
+    target: &str,
+    timeout: Duration,
+    sink: &mut Sink,
+  ) -> Result<Option<String>, Error> {
+    self.convert_atom_to(self.selection_atom(selection), target, timeout, sink)
+  }
+
+  fn convert_atom_to(
+    &self,
+    selection_atom: xlib::Atom,
+    target: &str,
+    timeout: Duration,
+    sink: &mut Sink,
+  ) -> Result<Option<String>, Error> {
+    let target_atom = self.atom(target);
+    let property = self.atom(TRANSFER_PROPERTY);
+
+    unsafe {
+      xlib::XDeleteProperty(self.display(), self.window, property);
+      xlib::XConvertSelection(
+        self.display(),
+        selection_atom,
+        target_atom,
+        property,
+        self.window,
+        xlib::CurrentTime,
+      );
+      xlib::XFlush(self.display());
+    }
+
+    let window = self.window;
+    let event = self.wait_for_event(timeout, &format!("Conversion to {}", target), |e| {
+      e.get_type() == xlib::SelectionNotify
+        && unsafe { e.selection.requestor } == window
+        && unsafe { e.selection.selection } == selection_atom
+    })?;
+
+    let notify = unsafe { event.selection };
+    if notify.property == 0 {
+      return Ok(None);
+    }
+
+    let data = self.read_property(notify.property, true)?;
+    if data.type_name == "INCR" {
+      // プロパティを削除したことが開始の合図になる（read_property で削除済み）
+      return self
+        .receive_incr(notify.property, timeout, target, sink)
+        .map(Some);
+    }
+
+    sink(&data.data)?;
+    Ok(Some(data.type_name))
+  }
+
+  // INCR 転送でチャンクを受け取る
+  //
+  // 所有者は新しいチャンクをプロパティに書き込み、受け取り側はそれを読んで削除する。
+  // 長さ 0 のチャンクが転送の終わりを表す。`timeout` はチャンクごとの待ち時間。
+  fn receive_incr(
+    &self,
+    property: xlib::Atom,
+    timeout: Duration,
+    target: &str,
+    sink: &mut Sink,
+  ) -> Result<String, Error> {
+    let window = self.window;
+    let mut type_name = String::new();
+
+    loop {
+      self.wait_for_event(timeout, &format!("INCR transfer of {}", target), |e| {
+        e.get_type() == xlib::PropertyNotify
+          && unsafe { e.property.window } == window
+          && unsafe { e.property.atom } == property
+          && unsafe { e.property.state } == xlib::PropertyNewValue
+      })?;
+
+      let chunk = self.read_property(property, true)?;
+      if chunk.data.is_empty() {
+        break;
+      }
+      if type_name.is_empty() {
+        type_name = chunk.type_name;
+      }
+      sink(&chunk.data)?;
+    }
+
+    Ok(type_name)
+  }
+
+  /// この接続を使って [`selection_epoch`] と同じ値を取得する
+  pub fn selection_epoch(&self, selection: Selection) -> Result<u64, Error> {
+    let owner = self.owner(selection);
+    if owner == 0 {
+      return Ok(0);
+    }
+
+    let timestamp = self
+      .convert(selection, "TIMESTAMP", timeout::timeout())?
+      .and_then(|data| data.as_u32_items().first().copied())
+      .unwrap_or(0);
+
+    Ok((owner << 32) | u64::from(timestamp))
+  }
+
+  // 受け取り用のウィンドウのプロパティを読み取る
+  fn read_property(&self, property: xlib::Atom, delete: bool) -> Result<SelectionData, Error> {
+    self.read_window_property(self.window, property, delete)
+  }
+
+  /// ウィンドウのプロパティを読み取る
+  ///
+  /// プロパティがない場合は `type_name` が空になる。
+  pub fn read_window_property(
+    &self,
+    window: xlib::Window,
+    property: xlib::Atom,
+    delete: bool,
+  ) -> Result<SelectionData, Error> {
+    let mut data = Vec::new();
+    let mut offset: c_long = 0;
+    let mut type_atom: xlib::Atom = 0;
+    let mut format: c_int = 0;
+
+    loop {
+      let mut nitems: c_ulong = 0;
+      let mut bytes_after: c_ulong = 0;
+      let mut buffer: *mut c_uchar = ptr::null_mut();
+
+      let status = unsafe {
+        xlib::XGetWindowProperty(
+          self.display(),
+          window,
+          property,
+          offset,
+          // 32 ビット単位での長さ
+          c_long::MAX / 4,
+          xlib::False,
+          xlib::AnyPropertyType as xlib::Atom,
+          &mut type_atom,
+          &mut format,
+          &mut nitems,
+          &mut bytes_after,
+          &mut buffer,
+        )
+      };
+
+      if status != xlib::Success as c_int {
+        // 所有者の切り替わりなどで起こるため、読み直せば成功する可能性がある
+        return Err(retry::busy("Failed to read selection property"));
+      }
+
+      if !buffer.is_null() {
+        unsafe {
+          append_items(&mut data, buffer, format, nitems as usize);
+          xlib::XFree(buffer as *mut _);
+        }
+      }
+
+      if bytes_after == 0 || nitems == 0 {
+        break;
+      }
+      // オフセットは 32 ビット単位
+      offset += (nitems as usize * (format as usize / 8) / 4) as c_long;
+    }
+
+    if delete {
+      unsafe {
+        xlib::XDeleteProperty(self.display(), window, property);
+        xlib::XFlush(self.display());
+      }
+    }
+
+    Ok(SelectionData {
+      type_name: self.atom_name(type_atom).unwrap_or_default(),
+      data,
+    })
+  }
+
+  /// 条件に合うイベントが `timeout` の間に届くまで待つ
+  ///
+  /// 条件に合わないイベントは読み捨てる。届かなかった場合は `ErrorKind::TimedOut` を返す。
+  pub fn wait_for_event(
+    &self,
+    timeout: Duration,
+    operation: &str,
+    mut matches: impl FnMut(&xlib::XEvent) -> bool,
+  ) -> Result<xlib::XEvent, Error> {
+    let deadline = Instant::now() + timeout;
+    while let Some(event) = self.next_event(deadline) {
+      if matches(&event) {
+        return Ok(event);
+      }
+    }
+    Err(timeout::timed_out(operation, timeout))
+  }
+
+  /// 次のイベントを取得する。期限までに届かなければ None
+  pub fn next_event(&self, deadline: Instant) -> Option<xlib::XEvent> {
+    loop {
+      unsafe {
+        if xlib::XPending(self.display()) > 0 {
+          let mut event: xlib::XEvent = std::mem::zeroed();
+          xlib::XNextEvent(self.display(), &mut event);
+          return Some(event);
+        }
+      }
+
+      let now = Instant::now();
+      if now >= deadline {
+        return None;
+      }
+      self.poll_readable(deadline - now);
+    }
+  }
+
+  // X サーバーからのデータが届くか、指定時間が経過するまで待つ
+  fn poll_readable(&self, wait: Duration) {
+    let mut fds = libc::pollfd {
+      fd: unsafe { xlib::XConnectionNumber(self.display()) },
+      events: libc::POLLIN,
+      revents: 0,
+    };
+    let wait_ms = wait.as_millis().clamp(1, c_int::MAX as u128) as c_int;
+    unsafe {
+      libc::poll(&mut fds, 1, wait_ms);
+    }
+  }
+}
+
+impl std::fmt::Debug for XConnection {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("XConnection")
+      .field("window", &self.window)
+      .finish_non_exhaustive()
+  }
+}
+
+impl Drop for XConnection {
+  fn drop(&mut self) {
+    unsafe {
+      xlib::XDestroyWindow(self.display(), self.window);
+    }
+  }
+}
+
+// Xlib のプロパティデータをバイト列に追加する
+// format 32 のデータは Xlib 上では long の配列になっているため 4 バイトに詰め直す
+unsafe fn append_items(out: &mut Vec<u8>, buffer: *const c_uchar, format: c_int, nitems: usize) {
+  match format {
+    8 => out.extend_from_slice(std::slice::from_raw_parts(buffer, nitems)),
+    16 => {
+      let items = std::slice::from_raw_parts(buffer as *const u16, nitems);
+      for item in items {
+        out.extend_from_slice(&item.to_le_bytes());
+      }
+    }
+    32 => {
+      let items = std::slice::from_raw_parts(buffer as *const c_ulong, nitems);
+      for item in items {
+        out.extend_from_slice(&(*item as u32).to_le_bytes());
+      }
+    }
+    _ => {}
+  }
+}
+
+/// XFixesSelectionNotify イベントであれば、セレクションアトムと新しい所有者を返す
+///
+/// `event_base` は [`XConnection::watch_owner_changes`] が返した値。
+pub fn owner_change(event: &xlib::XEvent, event_base: c_int) -> Option<(xlib::Atom, xlib::Window)> {
+  if event.get_type() != event_base + XFIXES_SELECTION_NOTIFY {
+    return None;
+  }
+  let notify =
+    unsafe { &*(event as *const xlib::XEvent as *const xfixes::XFixesSelectionNotifyEvent) };
+  Some((notify.selection, notify.owner))
+}
+
+/// セレクションの所有期間を識別する値
+///
+/// 所有者ウィンドウと、所有者が `TIMESTAMP` ターゲットで返す取得時刻を組み合わせる。
+/// 所有者が変わるか、同じ所有者が再取得すると値が変わる。所有者がいない場合は 0。
+pub fn selection_epoch(selection: Selection) -> Result<u64, Error> {
+  XConnection::open()?.selection_epoch(selection)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // format 32 のデータは 4 バイト単位に詰め直される
+  #[test]
+  fn test_append_items_format32() {
+    let items: [c_ulong; 2] = [0x1234_5678, 0xdead_beef];
+    let mut out = Vec::new();
+    unsafe { append_items(&mut out, items.as_ptr() as *const c_uchar, 32, 2) };
+    let data = SelectionData {
+      type_name: "TIMESTAMP".to_string(),
+      data: out,
+    };
+    assert_eq!(data.as_u32_items(), vec![0x1234_5678, 0xdead_beef]);
+  }
+
+  // format 8 のデータはそのままコピーされる
+  #[test]
+  fn test_append_items_format8() {
+    let bytes = b"file:///tmp/a.txt";
+    let mut out = Vec::new();
+    unsafe { append_items(&mut out, bytes.as_ptr(), 8, bytes.len()) };
+    assert_eq!(out, bytes);
+  }
+
+  // このモジュールの接続のエラーは無視し、それ以外の接続のエラーは元のハンドラーに渡す
+  #[test]
+  fn test_forward_x_error() {
+    unsafe extern "C" fn previous(
+      _display: *mut xlib::Display,
+      _event: *mut xlib::XErrorEvent,
+    ) -> c_int {
+      42
+    }
+
+    // 実際には開いていない、区別のためだけのアドレス
+    let own = 0x10 as *mut xlib::Display;
+    let other = 0x20 as *mut xlib::Display;
+    OWN_DISPLAYS.lock().unwrap().push(own as usize);
+    let mut event: xlib::XErrorEvent = unsafe { std::mem::zeroed() };
+
+    unsafe {
+      assert_eq!(forward_x_error(own, &mut event, Some(previous)), 0);
+      assert_eq!(forward_x_error(other, &mut event, Some(previous)), 42);
+      assert_eq!(forward_x_error(other, &mut event, None), 0);
+    }
+    OWN_DISPLAYS.lock().unwrap().retain(|d| *d != own as usize);
+  }
+
+  // X サーバーがある環境では所有期間を取得できる
+  #[test]
+  fn test_selection_epoch() {
+    if std::env::var("DISPLAY").map_or(true, |d| d.is_empty()) {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+    assert!(selection_epoch(Selection::Clipboard).is_ok());
+  }
+}
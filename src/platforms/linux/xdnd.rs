@@ -0,0 +1,947 @@
+// XDND プロトコルによるドラッグ＆ドロップの送信元の実装
+//
+// XdndSelection を所有してクリップボードと同じ形式のデータを提供し、ポインターの下にある
+// XdndAware なウィンドウへ XdndEnter / XdndPosition / XdndLeave / XdndDrop を送る。
+// プロトコルの状態遷移（DragSource）は X サーバーに依存しない形にしてテストできるようにしている。
+//
+// 参考: https://freedesktop.org/wiki/Specifications/XDND/
+
+use std::io::{Error, ErrorKind};
+use std::os::raw::{c_int, c_long, c_uchar, c_uint};
+use std::time::{Duration, Instant};
+
+use x11::xlib;
+
+use super::x11_owner::{OwnerState, SelectionOwner};
+use super::x11_selection::XConnection;
+use crate::platforms::retry::{self, RetryPolicy};
+use crate::platforms::timeout;
+
+// 対応しているプロトコルのバージョン
+const XDND_VERSION: u32 = 5;
+// XdndEnter で型の一覧を直接渡せる数。超える場合は XdndTypeList を使う
+const ENTER_TYPES: usize = 3;
+// イベントを待つ間隔
+const EVENT_WAIT: Duration = Duration::from_millis(20);
+
+/// ドロップ先のウィンドウ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DropTarget {
+  /// XdndAware を持つウィンドウ
+  pub window: xlib::Window,
+  /// メッセージの送り先（XdndProxy があればそのウィンドウ）
+  pub proxy: xlib::Window,
+  /// 使用するプロトコルのバージョン
+  pub version: u32,
+}
+
+/// 状態遷移への入力
+#[derive(Debug, Clone, Copy)]
+pub enum DragInput {
+  /// ポインターが移動した（root ウィンドウの座標）
+  Motion {
+    target: Option<DropTarget>,
+    x: i32,
+    y: i32,
+    time: xlib::Time,
+  },
+  /// ドロップ先から XdndStatus を受け取った
+  Status {
+    window: xlib::Window,
+    accept: bool,
+    action: xlib::Atom,
+  },
+  /// ドロップ先から XdndFinished を受け取った
+  Finished {
+    window: xlib::Window,
+    accepted: bool,
+    action: xlib::Atom,
+  },
+  /// マウスボタンが離された
+  Release { time: xlib::Time },
+  /// Escape などで中止された
+  Cancel,
+}
+
+/// ドロップ先へ送るメッセージ
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragMessage {
+  Enter(DropTarget),
+  Position {
+    target: DropTarget,
+    x: i32,
+    y: i32,
+    time: xlib::Time,
+  },
+  Leave(DropTarget),
+  Drop {
+    target: DropTarget,
+    time: xlib::Time,
+  },
+}
+
+/// ドラッグの結果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DragOutcome {
+  /// ドロップされた。`action` はドロップ先が実行した操作（`XdndActionCopy` など）
+  Dropped { action: xlib::Atom },
+  /// ドロップ先のない場所で離されたか、ドロップ先が受け付けなかった
+  Rejected,
+  /// 中止された
+  Cancelled,
+}
+
+// ドロップ先のウィンドウの上にあるときの状態
+#[derive(Debug, Clone, Copy)]
+struct Over {
+  target: DropTarget,
+  // XdndPosition を送り、XdndStatus を待っている
+  awaiting_status: bool,
+  // XdndStatus を待つ間に移動した位置
+  pending: Option<(i32, i32, xlib::Time)>,
+  accept: bool,
+  action: xlib::Atom,
+  // XdndStatus を待つ間にボタンが離された時刻
+  released: Option<xlib::Time>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum State {
+  // ドロップ先のウィンドウの上にない
+  Idle,
+  Over(Over),
+  // XdndDrop を送り、XdndFinished を待っている
+  Dropping {
+    target: DropTarget,
+    action: xlib::Atom,
+  },
+  Done(DragOutcome),
+}
+
+/// XDND の送信元の状態遷移
+#[derive(Debug)]
+pub struct DragSource {
+  state: State,
+}
+
+impl Default for DragSource {
+  fn default() -> Self {
+    Self { state: State::Idle }
+  }
+}
+
+impl DragSource {
+  /// ドラッグが終わっていれば結果を返す
+  pub fn outcome(&self) -> Option<DragOutcome> {
+    match self.state {
+      State::Done(outcome) => Some(outcome),
+      _ => None,
+    }
+  }
+
+  /// ドロップ後に XdndFinished を待っているかどうか
+  pub fn is_dropping(&self) -> bool {
+    matches!(self.state, State::Dropping { .. })
+  }
+
+  /// ボタンが離された後、ドロップ先の XdndStatus を待っているかどうか
+  pub fn is_releasing(&self) -> bool {
+    matches!(
+      self.state,
+      State::Over(Over {
+        released: Some(_),
+        ..
+      })
+    )
+  }
+
+  /// 入力を処理し、ドロップ先へ送るメッセージを返す
+  pub fn handle(&mut self, input: DragInput) -> Vec<DragMessage> {
+    let mut messages = Vec::new();
+    self.state = match (self.state, input) {
+      (State::Done(_), _) => self.state,
+
+      (State::Idle, DragInput::Motion { target, x, y, time }) => {
+        enter(target, x, y, time, &mut messages)
+      }
+      (State::Over(over), DragInput::Motion { .. }) if over.released.is_some() => self.state,
+      (State::Over(mut over), DragInput::Motion { target, x, y, time }) => {
+        if target.map(|t| t.window) == Some(over.target.window) {
+          if over.awaiting_status {
+            over.pending = Some((x, y, time));
+          } else {
+            messages.push(position(over.target, x, y, time));
+            over.awaiting_status = true;
+          }
+          State::Over(over)
+        } else {
+          messages.push(DragMessage::Leave(over.target));
+          enter(target, x, y, time, &mut messages)
+        }
+      }
+
+      (
+        State::Over(mut over),
+        DragInput::Status {
+          window,
+          accept,
+          action,
+        },
+      ) if window == over.target.window => {
+        over.awaiting_status = false;
+        over.accept = accept;
+        over.action = action;
+        if let Some(time) = over.released {
+          finish_release(over, time, &mut messages)
+        } else {
+          if let Some((x, y, time)) = over.pending.take() {
+            messages.push(position(over.target, x, y, time));
+            over.awaiting_status = true;
+          }
+          State::Over(over)
+        }
+      }
+
+      (State::Idle, DragInput::Release { .. }) => State::Done(DragOutcome::Rejected),
+      (State::Over(mut over), DragInput::Release { time }) => {
+        if over.awaiting_status {
+          // 最後の位置に対する XdndStatus を待ってから決める
+          over.released = Some(time);
+          State::Over(over)
+        } else {
+          finish_release(over, time, &mut messages)
+        }
+      }
+
+      (
+        State::Dropping { target, action },
+        DragInput::Finished {
+          window,
+          accepted,
+          action: finished_action,
+        },
+      ) if window == target.window => {
+        // バージョン 5 より前の XdndFinished には結果が含まれない
+        if target.version < 5 {
+          State::Done(DragOutcome::Dropped { action })
+        } else if accepted {
+          State::Done(DragOutcome::Dropped {
+            action: finished_action,
+          })
+        } else {
+          State::Done(DragOutcome::Rejected)
+        }
+      }
+
+      (State::Over(Over { target, .. }) | State::Dropping { target, .. }, DragInput::Cancel) => {
+        messages.push(DragMessage::Leave(target));
+        State::Done(DragOutcome::Cancelled)
+      }
+      (_, DragInput::Cancel) => State::Done(DragOutcome::Cancelled),
+
+      (state, _) => state,
+    };
+    messages
+  }
+}
+
+// ドロップ先に入る。ドロップ先がなければ Idle
+fn enter(
+  target: Option<DropTarget>,
+  x: i32,
+  y: i32,
+  time: xlib::Time,
+  messages: &mut Vec<DragMessage>,
+) -> State {
+  let Some(target) = target else {
+    return State::Idle;
+  };
+  messages.push(DragMessage::Enter(target));
+  messages.push(position(target, x, y, time));
+  State::Over(Over {
+    target,
+    awaiting_status: true,
+    pending: None,
+    accept: false,
+    action: 0,
+    released: None,
+  })
+}
+
+fn position(target: DropTarget, x: i32, y: i32, time: xlib::Time) -> DragMessage {
+  DragMessage::Position { target, x, y, time }
+}
+
+// ボタンが離されたとき、受け付けられていればドロップし、そうでなければ離れる
+fn finish_release(over: Over, time: xlib::Time, messages: &mut Vec<DragMessage>) -> State {
+  if over.accept {
+    messages.push(DragMessage::Drop {
+      target: over.target,
+      time,
+    });
+    State::Dropping {
+      target: over.target,
+      action: over.action,
+    }
+  } else {
+    messages.push(DragMessage::Leave(over.target));
+    State::Done(DragOutcome::Rejected)
+  }
+}
+
+/// XdndSelection を所有してドラッグを行う
+pub struct Drag {
+  owner: SelectionOwner,
+  source: DragSource,
+  types: Vec<xlib::Atom>,
+  // ボタンが離された時刻（ドロップ先の応答を待つ時間の上限に使う）
+  released_at: Option<Instant>,
+}
+
+impl Drag {
+  /// XdndSelection を取得し、`payload`（ターゲット名とデータの組）を提供する
+  pub fn start(payload: Vec<(String, Vec<u8>)>) -> Result<Self, Error> {
+    let conn = XConnection::open()?;
+    let types: Vec<xlib::Atom> = payload.iter().map(|(t, _)| conn.atom(t)).collect();
+    let selection = conn.atom("XdndSelection");
+
+    // XdndEnter で渡しきれない型は XdndTypeList プロパティで知らせる
+    let type_list = conn.atom("XdndTypeList");
+    unsafe {
+      xlib::XChangeProperty(
+        conn.display(),
+        conn.window(),
+        type_list,
+        xlib::XA_ATOM,
+        32,
+        xlib::PropModeReplace,
+        types.as_ptr() as *const c_uchar,
+        types.len() as c_int,
+      );
+    }
+
+    let owner = SelectionOwner::acquire_atom(conn, selection, payload)?;
+    Ok(Self {
+      owner,
+      source: DragSource::default(),
+      types,
+      released_at: None,
+    })
+  }
+
+  /// XDND のメッセージで送信元として使うウィンドウ
+  pub fn window(&self) -> xlib::Window {
+    self.owner.connection().window()
+  }
+
+  /// ドラッグが終わっていれば結果を返す
+  pub fn outcome(&self) -> Option<DragOutcome> {
+    self.source.outcome()
+  }
+
+  /// ポインターが root ウィンドウの座標 (`x`, `y`) に移動した
+  pub fn motion(&mut self, x: i32, y: i32, time: xlib::Time) {
+    let target = self.find_target(x, y);
+    self.handle(DragInput::Motion { target, x, y, time });
+  }
+
+  /// マウスボタンが離された
+  pub fn release(&mut self, time: xlib::Time) {
+    self.handle(DragInput::Release { time });
+  }
+
+  /// ドラッグを中止する
+  pub fn cancel(&mut self) {
+    self.handle(DragInput::Cancel);
+  }
+
+  /// `wait` の間、ドロップ先からのメッセージとデータの要求を処理する
+  ///
+  /// セレクション以外のイベント（ポインターのイベントなど）は `other` に渡す。
+  pub fn process_events(&mut self, wait: Duration, mut other: impl FnMut(&xlib::XEvent)) {
+    let mut inputs = Vec::new();
+    let conn_window = self.window();
+    let status = self.owner.connection().atom("XdndStatus");
+    let finished = self.owner.connection().atom("XdndFinished");
+
+    let state = self.owner.process_events_with(wait, |event| {
+      if event.get_type() != xlib::ClientMessage {
+        other(event);
+        return;
+      }
+      let message = unsafe { event.client_message };
+      if message.window != conn_window {
+        return;
+      }
+      let data = &message.data;
+      let window = data.get_long(0) as xlib::Window;
+      if message.message_type == status {
+        inputs.push(DragInput::Status {
+          window,
+          accept: data.get_long(1) & 1 != 0,
+          action: data.get_long(4) as xlib::Atom,
+        });
+      } else if message.message_type == finished {
+        inputs.push(DragInput::Finished {
+          window,
+          accepted: data.get_long(1) & 1 != 0,
+          action: data.get_long(2) as xlib::Atom,
+        });
+      }
+    });
+    for input in inputs {
+      self.handle(input);
+    }
+
+    // 他のクライアントに XdndSelection を奪われた、またはボタンを離した後に
+    // ドロップ先が応答しない（中止すると XdndLeave を送る）
+    let stalled = self
+      .released_at
+      .is_some_and(|at| at.elapsed() >= timeout::timeout());
+    if self.outcome().is_none() && (state == OwnerState::Lost || stalled) {
+      self.cancel();
+    }
+  }
+
+  /// 操作を表すアトムの名前（`XdndActionCopy` など）
+  pub fn action_name(&self, action: xlib::Atom) -> Option<String> {
+    self.owner.connection().atom_name(action)
+  }
+
+  fn handle(&mut self, input: DragInput) {
+    for message in self.source.handle(input) {
+      self.send(message);
+    }
+    let released = self.source.is_releasing() || self.source.is_dropping();
+    if released && self.released_at.is_none() {
+      self.released_at = Some(Instant::now());
+    }
+  }
+
+  // ドロップ先へ ClientMessage を送る
+  fn send(&self, message: DragMessage) {
+    let conn = self.owner.connection();
+    let source = conn.window() as c_long;
+    let (target, name, longs) = match message {
+      DragMessage::Enter(target) => {
+        let mut longs = [source, 0, 0, 0, 0];
+        longs[1] = ((target.version.min(XDND_VERSION) as c_long) << 24)
+          | c_long::from(self.types.len() > ENTER_TYPES);
+        for (i, atom) in self.types.iter().take(ENTER_TYPES).enumerate() {
+          longs[2 + i] = *atom as c_long;
+        }
+        (target, "XdndEnter", longs)
+      }
+      DragMessage::Position { target, x, y, time } => {
+        let position = ((x as c_long & 0xffff) << 16) | (y as c_long & 0xffff);
+        let action = conn.atom("XdndActionCopy") as c_long;
+        (
+          target,
+          "XdndPosition",
+          [source, 0, position, time as c_long, action],
+        )
+      }
+      DragMessage::Leave(target) => (target, "XdndLeave", [source, 0, 0, 0, 0]),
+      DragMessage::Drop { target, time } => (target, "XdndDrop", [source, 0, time as c_long, 0, 0]),
+    };
+
+    let mut data = xlib::ClientMessageData::new();
+    for (i, value) in longs.iter().enumerate() {
+      data.set_long(i, *value);
+    }
+    let mut event = xlib::XEvent {
+      client_message: xlib::XClientMessageEvent {
+        type_: xlib::ClientMessage,
+        serial: 0,
+        send_event: xlib::True,
+        display: conn.display(),
+        window: target.window,
+        message_type: conn.atom(name),
+        format: 32,
+        data,
+      },
+    };
+    unsafe {
+      xlib::XSendEvent(
+        conn.display(),
+        target.proxy,
+        xlib::False,
+        xlib::NoEventMask,
+        &mut event,
+      );
+      xlib::XFlush(conn.display());
+    }
+  }
+
+  // root ウィンドウの座標にある XdndAware なウィンドウを探す
+  fn find_target(&self, x: i32, y: i32) -> Option<DropTarget> {
+    let conn = self.owner.connection();
+    let display = conn.display();
+    let root = unsafe { xlib::XDefaultRootWindow(display) };
+
+    let mut window = root;
+    loop {
+      let mut child: xlib::Window = 0;
+      let (mut child_x, mut child_y) = (0, 0);
+      let found = unsafe {
+        xlib::XTranslateCoordinates(
+          display,
+          root,
+          window,
+          x,
+          y,
+          &mut child_x,
+          &mut child_y,
+          &mut child,
+        )
+      };
+      if found == 0 || child == 0 || child == conn.window() {
+        return None;
+      }
+      window = child;
+      if let Some(version) = self.xdnd_version(window) {
+        return Some(DropTarget {
+          window,
+          proxy: self.proxy(window).unwrap_or(window),
+          version,
+        });
+      }
+    }
+  }
+
+  // XdndAware プロパティのバージョン。対応していないウィンドウなら None
+  fn xdnd_version(&self, window: xlib::Window) -> Option<u32> {
+    let conn = self.owner.connection();
+    let aware = conn
+      .read_window_property(window, conn.atom("XdndAware"), false)
+      .ok()?;
+    let version = *aware.as_u32_items().first()?;
+    // バージョン 3 より前のプロトコルには対応しない
+    (version >= 3).then_some(version.min(XDND_VERSION))
+  }
+
+  // XdndProxy が指すウィンドウ。自身を指す XdndProxy を持つ場合のみ有効
+  fn proxy(&self, window: xlib::Window) -> Option<xlib::Window> {
+    let conn = self.owner.connection();
+    let property = conn.atom("XdndProxy");
+    let read = |w: xlib::Window| -> Option<xlib::Window> {
+      let data = conn.read_window_property(w, property, false).ok()?;
+      data.as_u32_items().first().map(|p| *p as xlib::Window)
+    };
+    let proxy = read(window)?;
+    (read(proxy) == Some(proxy)).then_some(proxy)
+  }
+}
+
+/// `origin` ウィンドウからファイルのドラッグを開始し、終わるまで待つ
+///
+/// マウスボタンが押されている間に呼び出す。ポインターを `origin` でグラブし、
+/// ボタンが離されるとドロップする。Escape キーで中止できる。
+///
+/// グラブは別の接続から行うため、他のクライアント（Chromium 自身のドラッグなど）が
+/// ポインターをグラブしている間は失敗する。解放されるまでクリップボードのタイムアウトの
+/// 間だけ再試行する。
+pub fn run_drag(
+  origin: xlib::Window,
+  payload: Vec<(String, Vec<u8>)>,
+) -> Result<(DragOutcome, Option<String>), Error> {
+  let mut drag = Drag::start(payload)?;
+  let display = drag.owner.connection().display();
+
+  let policy = RetryPolicy {
+    attempts: u32::MAX,
+    backoff: EVENT_WAIT,
+    deadline: timeout::timeout(),
+  };
+  retry::retry_with(&policy, "Grabbing the pointer", || {
+    let grabbed = unsafe {
+      xlib::XGrabPointer(
+        display,
+        origin,
+        xlib::False,
+        (xlib::PointerMotionMask | xlib::ButtonReleaseMask) as c_uint,
+        xlib::GrabModeAsync,
+        xlib::GrabModeAsync,
+        0,
+        0,
+        xlib::CurrentTime,
+      )
+    };
+    let message = format!(
+      "Failed to grab the pointer for dragging (status {})",
+      grabbed
+    );
+    match grabbed {
+      xlib::GrabSuccess => Ok(()),
+      // 他のクライアントのグラブが解放されれば成功する
+      xlib::AlreadyGrabbed | xlib::GrabFrozen => Err(retry::busy(message)),
+      _ => Err(Error::new(ErrorKind::ResourceBusy, message)),
+    }
+  })?;
+  unsafe {
+    xlib::XGrabKeyboard(
+      display,
+      origin,
+      xlib::False,
+      xlib::GrabModeAsync,
+      xlib::GrabModeAsync,
+      xlib::CurrentTime,
+    );
+  }
+  let escape = unsafe { xlib::XKeysymToKeycode(display, x11::keysym::XK_Escape as xlib::KeySym) };
+
+  while drag.outcome().is_none() {
+    let mut pointer = Vec::new();
+    drag.process_events(EVENT_WAIT, |event| pointer.push(*event));
+    for event in pointer {
+      match event.get_type() {
+        xlib::MotionNotify => {
+          let motion = unsafe { event.motion };
+          drag.motion(motion.x_root, motion.y_root, motion.time);
+        }
+        xlib::ButtonRelease => {
+          let button = unsafe { event.button };
+          drag.release(button.time);
+        }
+        xlib::KeyPress => {
+          let key = unsafe { event.key };
+          if key.keycode == c_uint::from(escape) {
+            drag.cancel();
+          }
+        }
+        _ => {}
+      }
+    }
+    // ボタンが離されたらポインターを解放し、ドロップ先の応答を待つ
+    if drag.source.is_dropping() || drag.source.is_releasing() {
+      ungrab(display);
+    }
+  }
+  ungrab(display);
+
+  let outcome = drag.outcome().unwrap_or(DragOutcome::Cancelled);
+  let action = match outcome {
+    DragOutcome::Dropped { action } => drag.action_name(action),
+    _ => None,
+  };
+  Ok((outcome, action))
+}
+
+fn ungrab(display: *mut xlib::Display) {
+  unsafe {
+    xlib::XUngrabPointer(display, xlib::CurrentTime);
+    xlib::XUngrabKeyboard(display, xlib::CurrentTime);
+    xlib::XFlush(display);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::thread;
+
+  const COPY: xlib::Atom = 100;
+
+  fn target(window: xlib::Window) -> DropTarget {
+    DropTarget {
+      window,
+      proxy: window,
+      version: 5,
+    }
+  }
+
+  fn motion(target: Option<DropTarget>, x: i32) -> DragInput {
+    DragInput::Motion {
+      target,
+      x,
+      y: 0,
+      time: 0,
+    }
+  }
+
+  fn status(window: xlib::Window, accept: bool) -> DragInput {
+    DragInput::Status {
+      window,
+      accept,
+      action: COPY,
+    }
+  }
+
+  // 受け付けられたドロップ先で離すと XdndDrop を送り、XdndFinished で完了する
+  #[test]
+  fn test_drag_source_drop() {
+    let t = target(1);
+    let mut source = DragSource::default();
+    assert!(source.handle(motion(None, 0)).is_empty());
+    assert_eq!(
+      source.handle(motion(Some(t), 1)),
+      vec![
+        DragMessage::Enter(t),
+        DragMessage::Position {
+          target: t,
+          x: 1,
+          y: 0,
+          time: 0
+        }
+      ]
+    );
+    // XdndStatus を待つ間の移動はまとめて送る
+    assert!(source.handle(motion(Some(t), 2)).is_empty());
+    assert!(source.handle(motion(Some(t), 3)).is_empty());
+    assert_eq!(
+      source.handle(status(1, true)),
+      vec![DragMessage::Position {
+        target: t,
+        x: 3,
+        y: 0,
+        time: 0
+      }]
+    );
+    assert!(source.handle(status(1, true)).is_empty());
+
+    assert_eq!(
+      source.handle(DragInput::Release { time: 9 }),
+      vec![DragMessage::Drop { target: t, time: 9 }]
+    );
+    assert!(source.is_dropping());
+    // 別のウィンドウからの XdndFinished は無視する
+    source.handle(DragInput::Finished {
+      window: 2,
+      accepted: true,
+      action: COPY,
+    });
+    assert_eq!(source.outcome(), None);
+    source.handle(DragInput::Finished {
+      window: 1,
+      accepted: true,
+      action: COPY,
+    });
+    assert_eq!(
+      source.outcome(),
+      Some(DragOutcome::Dropped { action: COPY })
+    );
+  }
+
+  // ドロップ先が変わると XdndLeave を送ってから入り直す
+  #[test]
+  fn test_drag_source_changes_target() {
+    let (a, b) = (target(1), target(2));
+    let mut source = DragSource::default();
+    source.handle(motion(Some(a), 0));
+    let messages = source.handle(motion(Some(b), 1));
+    assert_eq!(messages[0], DragMessage::Leave(a));
+    assert_eq!(messages[1], DragMessage::Enter(b));
+    assert_eq!(source.handle(motion(None, 2)), vec![DragMessage::Leave(b)]);
+    assert_eq!(source.handle(DragInput::Release { time: 0 }), vec![]);
+    assert_eq!(source.outcome(), Some(DragOutcome::Rejected));
+  }
+
+  // XdndStatus を待つ間に離された場合は、応答を見てから決める
+  #[test]
+  fn test_drag_source_release_while_awaiting_status() {
+    let t = target(1);
+    let mut source = DragSource::default();
+    source.handle(motion(Some(t), 0));
+    assert!(source.handle(DragInput::Release { time: 5 }).is_empty());
+    assert!(source.is_releasing());
+    // 離した後の移動は無視する
+    assert!(source.handle(motion(None, 1)).is_empty());
+    assert_eq!(source.handle(status(1, false)), vec![DragMessage::Leave(t)]);
+    assert_eq!(source.outcome(), Some(DragOutcome::Rejected));
+  }
+
+  // 中止すると XdndLeave を送り、ドロップ先が拒否すれば Rejected になる
+  #[test]
+  fn test_drag_source_cancel_and_refused_finish() {
+    let t = target(1);
+    let mut source = DragSource::default();
+    source.handle(motion(Some(t), 0));
+    assert_eq!(
+      source.handle(DragInput::Cancel),
+      vec![DragMessage::Leave(t)]
+    );
+    assert_eq!(source.outcome(), Some(DragOutcome::Cancelled));
+
+    let mut source = DragSource::default();
+    source.handle(motion(Some(t), 0));
+    source.handle(status(1, true));
+    source.handle(DragInput::Release { time: 0 });
+    source.handle(DragInput::Finished {
+      window: 1,
+      accepted: false,
+      action: 0,
+    });
+    assert_eq!(source.outcome(), Some(DragOutcome::Rejected));
+
+    // XdndFinished を待つ間に中止した場合も XdndLeave を送る
+    let mut source = DragSource::default();
+    source.handle(motion(Some(t), 0));
+    source.handle(status(1, true));
+    source.handle(DragInput::Release { time: 0 });
+    assert_eq!(
+      source.handle(DragInput::Cancel),
+      vec![DragMessage::Leave(t)]
+    );
+    assert_eq!(source.outcome(), Some(DragOutcome::Cancelled));
+  }
+
+  // バージョン 4 以前の XdndFinished は結果を含まないため、XdndStatus の操作を使う
+  #[test]
+  fn test_drag_source_old_version_finish() {
+    let t = DropTarget {
+      version: 4,
+      ..target(1)
+    };
+    let mut source = DragSource::default();
+    source.handle(motion(Some(t), 0));
+    source.handle(status(1, true));
+    source.handle(DragInput::Release { time: 0 });
+    source.handle(DragInput::Finished {
+      window: 1,
+      accepted: false,
+      action: 0,
+    });
+    assert_eq!(
+      source.outcome(),
+      Some(DragOutcome::Dropped { action: COPY })
+    );
+  }
+
+  // 偽のドロップ先ウィンドウに対してドラッグし、text/uri-list を受け渡す
+  #[test]
+  fn test_drag_to_fake_drop_target() {
+    if std::env::var("DISPLAY").map_or(true, |d| d.is_empty()) {
+      println!("⚠️ DISPLAY が設定されていないためスキップ");
+      return;
+    }
+
+    let conn = XConnection::open().unwrap();
+    let display = conn.display();
+    let target_window = unsafe {
+      let root = xlib::XDefaultRootWindow(display);
+      let window = xlib::XCreateSimpleWindow(display, root, 0, 0, 400, 400, 0, 0, 0);
+      let version: c_long = 5;
+      xlib::XChangeProperty(
+        display,
+        window,
+        conn.atom("XdndAware"),
+        xlib::XA_ATOM,
+        32,
+        xlib::PropModeReplace,
+        &version as *const c_long as *const c_uchar,
+        1,
+      );
+      xlib::XMapRaised(display, window);
+      xlib::XSync(display, xlib::False);
+      window
+    };
+
+    // ドロップ先: XdndPosition を受け付け、XdndDrop でデータを読み取って XdndFinished を返す
+    let drop_target = thread::spawn(move || {
+      let send = |to: xlib::Window, name: &str, longs: [c_long; 5]| {
+        let mut data = xlib::ClientMessageData::new();
+        for (i, v) in longs.iter().enumerate() {
+          data.set_long(i, *v);
+        }
+        let mut event = xlib::XEvent {
+          client_message: xlib::XClientMessageEvent {
+            type_: xlib::ClientMessage,
+            serial: 0,
+            send_event: xlib::True,
+            display: conn.display(),
+            window: to,
+            message_type: conn.atom(name),
+            format: 32,
+            data,
+          },
+        };
+        unsafe {
+          xlib::XSendEvent(
+            conn.display(),
+            to,
+            xlib::False,
+            xlib::NoEventMask,
+            &mut event,
+          );
+          xlib::XFlush(conn.display());
+        }
+      };
+      let copy = conn.atom("XdndActionCopy") as c_long;
+      let deadline = Instant::now() + Duration::from_secs(10);
+      let mut entered_types = Vec::new();
+      while let Some(event) = conn.next_event(deadline) {
+        if event.get_type() != xlib::ClientMessage {
+          continue;
+        }
+        let message = unsafe { event.client_message };
+        let source = message.data.get_long(0) as xlib::Window;
+        let name = conn.atom_name(message.message_type).unwrap_or_default();
+        match name.as_str() {
+          "XdndEnter" => {
+            entered_types = (2..5)
+              .map(|i| message.data.get_long(i) as xlib::Atom)
+              .filter(|a| *a != 0)
+              .filter_map(|a| conn.atom_name(a))
+              .collect();
+          }
+          "XdndPosition" => {
+            send(
+              source,
+              "XdndStatus",
+              [target_window as c_long, 1, 0, 0, copy],
+            );
+          }
+          "XdndDrop" => {
+            let data = conn
+              .convert_atom(
+                conn.atom("XdndSelection"),
+                "text/uri-list",
+                Duration::from_secs(5),
+              )
+              .unwrap()
+              .map(|d| d.data);
+            send(
+              source,
+              "XdndFinished",
+              [target_window as c_long, 1, copy, 0, 0],
+            );
+            return (entered_types, data);
+          }
+          _ => {}
+        }
+      }
+      (entered_types, None)
+    });
+
+    // 空白を含むパスは text/uri-list ではパーセントエンコードされる
+    let path = std::env::temp_dir().join("clip filepaths xdnd test.txt");
+    std::fs::write(&path, b"xdnd").unwrap();
+    let payload =
+      super::super::payload::file_paths_payload(&[path.to_string_lossy().into_owned()], None)
+        .unwrap();
+    let canonical = path.canonicalize().unwrap();
+    let expected = format!("file://{}", canonical.to_string_lossy().replace(' ', "%20"));
+
+    let mut drag = Drag::start(payload).unwrap();
+    drag.motion(50, 50, xlib::CurrentTime);
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while drag.outcome().is_none() && Instant::now() < deadline {
+      drag.process_events(EVENT_WAIT, |_| {});
+      if !drag.source.is_dropping() && !drag.source.is_releasing() {
+        drag.release(xlib::CurrentTime);
+      }
+    }
+
+    let (types, received) = drop_target.join().unwrap();
+    assert!(matches!(drag.outcome(), Some(DragOutcome::Dropped { .. })));
+    if let Some(DragOutcome::Dropped { action }) = drag.outcome() {
+      assert_eq!(drag.action_name(action).as_deref(), Some("XdndActionCopy"));
+    }
+    assert_eq!(types[0], "text/uri-list");
+    assert_eq!(
+      received.map(|data| String::from_utf8_lossy(&data).into_owned()),
+      Some(expected)
+    );
+    let _ = std::fs::remove_file(path);
+  }
+}
@@ -1,11 +1,25 @@
 #![cfg(target_os = "macos")]
 
-use cocoa::base::id;
+use cocoa::base::{id, BOOL, NO};
+use objc::declare::ClassDecl;
+use objc::runtime::{Class, Object, Protocol, Sel};
 use objc::{class, msg_send, sel, sel_impl};
+use std::ffi::c_void;
 use std::io::{Error, ErrorKind};
 use std::path::Path;
+use std::ptr;
+use std::sync::Once;
 
 use super::wrapper::*;
+use crate::platforms::file_entry::FileEntry;
+use crate::platforms::lazy::Provider;
+use crate::platforms::owner_info::{OwnerInfo, SOURCE_MARKER_FORMAT};
+use crate::platforms::ownership::SequenceOwnership;
+use crate::platforms::retry;
+use crate::platforms::stream::{self, Sink};
+use crate::platforms::text_encoding::DecodedText;
+use crate::platforms::text_representation::PathText;
+use crate::platforms::Selection;
 
 /// クリップボード操作のトレイト定義
 #[cfg(test)]
@@ -87,7 +101,25 @@ impl ClipboardOperations for MockClipboard {
 }
 
 /// ファイルパスをクリップボードにコピーする
-pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
+///
+/// `source` を指定した場合は、書き込み元を示す `org.nspasteboard.source` も書き込む。
+/// `text` を指定した場合は、その形式のテキストを public.utf8-plain-text として書き込む。
+/// ペーストボードへの書き込みが一時的に失敗した場合は、再試行のポリシーに従って書き直す。
+pub fn write_clipboard_file_paths(
+  paths: &[String],
+  source: Option<&str>,
+  text: Option<&PathText>,
+) -> Result<(), Error> {
+  retry::retry("Writing file paths to the pasteboard", || {
+    write_file_paths_once(paths, source, text)
+  })
+}
+
+fn write_file_paths_once(
+  paths: &[String],
+  source: Option<&str>,
+  text: Option<&PathText>,
+) -> Result<(), Error> {
   // AutoreleasePoolを作成
   let _pool = AutoreleasePool::new()?;
 
@@ -106,6 +138,7 @@ pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
 
   // ファイルURLの配列を作成
   let mut urls = Vec::new();
+  let mut canonical_paths = Vec::new();
   let mut errors = Vec::new();
 
   for path_str in paths {
@@ -115,6 +148,7 @@ pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
         if let Some(s) = abs_path.to_str() {
           if let Some(obj_url) = ObjcUrl::from_path(s) {
             urls.push(obj_url.as_id());
+            canonical_paths.push(s.to_string());
           } else {
             errors.push(format!("Failed to create NSURL for path: {}", s));
           }
@@ -156,10 +190,7 @@ pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
   // タイプを宣言
   let declared = pasteboard.declare_types(&types_array);
   if !declared {
-    return Err(Error::new(
-      ErrorKind::Other,
-      "Failed to declare pasteboard types",
-    ));
+    return Err(retry::busy("Failed to declare pasteboard types"));
   }
 
   // URLの配列を作成
@@ -170,18 +201,272 @@ pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
   let success = pasteboard.write_objects(&urls_array);
 
   if success {
+    if let Some(text) = text.and_then(|text| text.render(&canonical_paths)) {
+      write_text(&pasteboard, &text)?;
+    }
+    if let Some(source) = source {
+      write_source_marker(&pasteboard, source)?;
+    }
     println!("Copied {} files to clipboard on macOS", urls.len());
     Ok(())
+  } else {
+    Err(retry::busy(
+      "Failed to write file URLs to pasteboard (writeObjects failed)",
+    ))
+  }
+}
+
+/// テキストをクリップボードにコピーする（public.utf8-plain-text）
+///
+/// `paths` が空でなければ、そのファイル URL（public.file-url）も書き込む。
+pub fn write_clipboard_text(text: &str, paths: &[String]) -> Result<(), Error> {
+  retry::retry("Writing text to the pasteboard", || {
+    write_text_once(text, paths)
+  })
+}
+
+fn write_text_once(text: &str, paths: &[String]) -> Result<(), Error> {
+  // AutoreleasePoolを作成
+  let _pool = AutoreleasePool::new()?;
+
+  // Pasteboardを取得
+  let pasteboard = Pasteboard::general()
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+  let text_type = ObjcString::from_str("public.utf8-plain-text")
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create NSString for text type"))?;
+  let string = ObjcString::from_str(text).ok_or_else(|| {
+    Error::new(
+      ErrorKind::InvalidInput,
+      "Text must not contain NUL characters",
+    )
+  })?;
+  let types_array = ObjcArray::with_object(text_type.as_id())
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create types array"))?;
+
+  // 内容をクリア
+  pasteboard.clear_contents();
+
+  // ファイル URL を先に書き込み、最初の項目にテキストを追加する
+  if !paths.is_empty() {
+    let urls: Vec<id> = paths
+      .iter()
+      .filter_map(|path| ObjcUrl::from_path(path))
+      .map(|url| url.as_id())
+      .collect();
+    let urls_array = ObjcArray::from_vec(&urls)
+      .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create URLs array"))?;
+    if !pasteboard.write_objects(&urls_array) {
+      return Err(retry::busy(
+        "Failed to write file URLs to pasteboard (writeObjects failed)",
+      ));
+    }
+    pasteboard.add_types(&types_array);
+  } else if !pasteboard.declare_types(&types_array) {
+    return Err(retry::busy("Failed to declare pasteboard types"));
+  }
+
+  if pasteboard.set_string_for_type(&string, &text_type) {
+    println!("Copied text to clipboard on macOS");
+    Ok(())
+  } else {
+    Err(retry::busy("Failed to write text to pasteboard"))
+  }
+}
+
+// ファイル URL と一緒にテキストを書き込む
+fn write_text(pasteboard: &Pasteboard, text: &str) -> Result<(), Error> {
+  let text_type = ObjcString::from_str("public.utf8-plain-text")
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create NSString for text type"))?;
+  let string = ObjcString::from_str(text)
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create NSString for text"))?;
+  let types_array = ObjcArray::with_object(text_type.as_id())
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create types array"))?;
+
+  pasteboard.add_types(&types_array);
+  if pasteboard.set_string_for_type(&string, &text_type) {
+    Ok(())
   } else {
     Err(Error::new(
       ErrorKind::Other,
-      "Failed to write file URLs to pasteboard (writeObjects failed)",
+      "Failed to write text to pasteboard",
     ))
   }
 }
 
+// 書き込み元を示す印（nspasteboard.org の慣習ではバンドル ID）を書き込む
+fn write_source_marker(pasteboard: &Pasteboard, source: &str) -> Result<(), Error> {
+  let marker_type = ObjcString::from_str(SOURCE_MARKER_FORMAT).ok_or_else(|| {
+    Error::new(
+      ErrorKind::Other,
+      "Failed to create NSString for source marker type",
+    )
+  })?;
+  let marker = ObjcString::from_str(source).ok_or_else(|| {
+    Error::new(
+      ErrorKind::InvalidInput,
+      "Source marker must not contain NUL characters",
+    )
+  })?;
+  let types_array = ObjcArray::with_object(marker_type.as_id())
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create types array"))?;
+
+  pasteboard.add_types(&types_array);
+  if pasteboard.set_string_for_type(&marker, &marker_type) {
+    Ok(())
+  } else {
+    Err(Error::new(
+      ErrorKind::Other,
+      "Failed to write source marker to pasteboard",
+    ))
+  }
+}
+
+// 遅延レンダリングでデータを提供するクラスの名前
+const LAZY_PROVIDER_CLASS: &str = "ClipFilepathsLazyProvider";
+
+/// 要求されたときに `provider` でデータを生成するようにクリップボードに書き込む
+///
+/// NSPasteboardItemDataProvider を使う。データの要求はメインスレッドの
+/// RunLoop で処理されるため、RunLoop が動いているアプリ（Electron など）でのみ提供できる。
+pub fn write_clipboard_lazy(formats: &[String], provider: Provider) -> Result<(), Error> {
+  // AutoreleasePoolを作成
+  let _pool = AutoreleasePool::new()?;
+
+  // Pasteboardを取得
+  let pasteboard = Pasteboard::general()
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+  let types = formats
+    .iter()
+    .map(|format| {
+      ObjcString::from_str(format).ok_or_else(|| {
+        Error::new(
+          ErrorKind::Other,
+          format!("Failed to create NSString for type: {}", format),
+        )
+      })
+    })
+    .collect::<Result<Vec<_>, Error>>()?;
+  let type_ids: Vec<id> = types.iter().map(|t| t.as_id()).collect();
+  let types_array = ObjcArray::from_vec(&type_ids)
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to create types array"))?;
+
+  unsafe {
+    // データ提供オブジェクトは pasteboardFinishedWithDataProvider: で解放する
+    let data_provider: id = msg_send![lazy_provider_class(), new];
+    (*data_provider)
+      .set_ivar::<*mut c_void>("provider", Box::into_raw(Box::new(provider)) as *mut c_void);
+
+    let item: id = msg_send![class!(NSPasteboardItem), new];
+    let accepted: BOOL =
+      msg_send![item, setDataProvider: data_provider forTypes: types_array.as_id()];
+    let items = ObjcArray::with_object(item);
+    let _: () = msg_send![item, release];
+    if accepted == NO {
+      release_lazy_provider(&mut *data_provider);
+      return Err(Error::new(
+        ErrorKind::Other,
+        "Failed to set pasteboard data provider",
+      ));
+    }
+    let Some(items) = items else {
+      release_lazy_provider(&mut *data_provider);
+      return Err(Error::new(ErrorKind::Other, "Failed to create items array"));
+    };
+
+    // クリップボードに書き込み
+    // 書き込めなかった項目には pasteboardFinishedWithDataProvider: が呼ばれないため、ここで解放する
+    pasteboard.clear_contents();
+    if !pasteboard.write_objects(&items) {
+      release_lazy_provider(&mut *data_provider);
+      return Err(Error::new(
+        ErrorKind::Other,
+        "Failed to write pasteboard item (writeObjects failed)",
+      ));
+    }
+  }
+
+  println!(
+    "Registered {} lazy formats to clipboard on macOS",
+    formats.len()
+  );
+  Ok(())
+}
+
+// NSPasteboardItemDataProvider を実装したクラスを登録して取得する
+fn lazy_provider_class() -> &'static Class {
+  static REGISTER: Once = Once::new();
+  REGISTER.call_once(|| {
+    let mut decl = ClassDecl::new(LAZY_PROVIDER_CLASS, class!(NSObject))
+      .expect("Failed to declare lazy provider class");
+    decl.add_ivar::<*mut c_void>("provider");
+    if let Some(protocol) = Protocol::get("NSPasteboardItemDataProvider") {
+      decl.add_protocol(protocol);
+    }
+    unsafe {
+      decl.add_method(
+        sel!(pasteboard:item:provideDataForType:),
+        provide_data as extern "C" fn(&mut Object, Sel, id, id, id),
+      );
+      decl.add_method(
+        sel!(pasteboardFinishedWithDataProvider:),
+        finished_with_data_provider as extern "C" fn(&mut Object, Sel, id),
+      );
+    }
+    decl.register();
+  });
+  Class::get(LAZY_PROVIDER_CLASS).expect("Lazy provider class is registered")
+}
+
+// 貼り付け先がデータを要求したときに呼ばれる
+extern "C" fn provide_data(this: &mut Object, _sel: Sel, _pasteboard: id, item: id, data_type: id) {
+  unsafe {
+    let provider = *this.get_ivar::<*mut c_void>("provider") as *const Provider;
+    if provider.is_null() {
+      return;
+    }
+    let Some(format) = ObjcString::from_id(data_type).and_then(|t| t.to_rust_string()) else {
+      return;
+    };
+
+    match (*provider)(&format) {
+      Ok(Some(data)) => {
+        let data: id = msg_send![class!(NSData), dataWithBytes: data.as_ptr() as *const c_void length: data.len()];
+        let _: BOOL = msg_send![item, setData: data forType: data_type];
+      }
+      // 生成できなかった場合はデータを設定せず、貼り付け先には提供しないフォーマットになる
+      // （OS からの呼び出しのため、エラーを返す先がない）
+      Ok(None) | Err(_) => {}
+    }
+  }
+}
+
+// ペーストボードがデータ提供オブジェクトを必要としなくなったときに呼ばれる
+extern "C" fn finished_with_data_provider(this: &mut Object, _sel: Sel, _pasteboard: id) {
+  unsafe { release_lazy_provider(this) }
+}
+
+// provider を破棄してデータ提供オブジェクトを解放する
+unsafe fn release_lazy_provider(this: &mut Object) {
+  let provider = *this.get_ivar::<*mut c_void>("provider") as *mut Provider;
+  if provider.is_null() {
+    return;
+  }
+  this.set_ivar::<*mut c_void>("provider", ptr::null_mut());
+  drop(Box::from_raw(provider));
+  let _: () = msg_send![this, release];
+}
+
 /// クリップボードからテキストを読み取る
 pub fn read_clipboard_text() -> Result<String, Error> {
+  read_clipboard_text_decoded().map(|decoded| decoded.text)
+}
+
+/// クリップボードのテキストを、読み取ったペーストボードタイプとともに読み取る
+///
+/// NSString から UTF-8 で取り出すため、文字コードは常に UTF-8 になる。
+pub fn read_clipboard_text_decoded() -> Result<DecodedText, Error> {
   // AutoreleasePoolを作成
   let _pool = AutoreleasePool::new()?;
 
@@ -189,6 +474,13 @@ pub fn read_clipboard_text() -> Result<String, Error> {
   let pasteboard = Pasteboard::general()
     .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
 
+  let decoded = |text: String, type_name: String| DecodedText {
+    text,
+    source_format: type_name,
+    charset: "UTF-8".to_string(),
+    was_lossy: false,
+  };
+
   // サポートするテキストタイプの優先順位リスト
   let supported_text_types = [
     "public.utf8-plain-text",
@@ -208,7 +500,7 @@ pub fn read_clipboard_text() -> Result<String, Error> {
     if let Some(text_obj) = pasteboard.string_for_type(&type_str) {
       if let Some(rust_str) = text_obj.to_rust_string() {
         if !rust_str.is_empty() {
-          return Ok(rust_str);
+          return Ok(decoded(rust_str, type_name.to_string()));
         }
       }
     }
@@ -222,7 +514,10 @@ pub fn read_clipboard_text() -> Result<String, Error> {
         if let Some(text_obj) = pasteboard.string_for_type_id(type_id) {
           if let Some(rust_str) = text_obj.to_rust_string() {
             if !rust_str.is_empty() {
-              return Ok(rust_str);
+              let type_name = ObjcString::from_id(type_id)
+                .and_then(|s| s.to_rust_string())
+                .unwrap_or_default();
+              return Ok(decoded(rust_str, type_name));
             }
           }
         }
@@ -327,8 +622,48 @@ pub fn read_clipboard_raw() -> Result<Vec<u8>, Error> {
   ))
 }
 
+/// 指定したタイプ（`public.png` など）のデータを分割して読み取る
+pub fn read_clipboard_format(format: &str, sink: &mut Sink) -> Result<u64, Error> {
+  // AutoreleasePoolを作成
+  let _pool = AutoreleasePool::new()?;
+
+  // Pasteboardを取得
+  let pasteboard = Pasteboard::general()
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+  let type_str = ObjcString::from_str(format).ok_or_else(|| {
+    Error::new(
+      ErrorKind::InvalidInput,
+      format!("Invalid pasteboard type: {}", format),
+    )
+  })?;
+
+  let data = pasteboard.data_for_type(type_str.as_id()).ok_or_else(|| {
+    Error::new(
+      ErrorKind::NotFound,
+      format!("Format {} is not available in clipboard", format),
+    )
+  })?;
+
+  // NSData のバッファから直接分割して渡す（全体をコピーしない）
+  match ObjcData::from_id(data).as_slice() {
+    Some(bytes) => stream::feed_chunks(bytes, sink),
+    None => Ok(0),
+  }
+}
+
 /// クリップボードからファイルパスを読み取る
 pub fn read_clipboard_file_paths() -> Result<Vec<String>, Error> {
+  Ok(
+    read_clipboard_file_entries()?
+      .into_iter()
+      .filter_map(|entry| entry.path)
+      .collect(),
+  )
+}
+
+/// クリップボードの URL を読み取る（ファイル URL 以外も含む）
+pub fn read_clipboard_file_entries() -> Result<Vec<FileEntry>, Error> {
   // AutoreleasePoolを作成
   let _pool = AutoreleasePool::new()?;
 
@@ -346,7 +681,7 @@ pub fn read_clipboard_file_paths() -> Result<Vec<String>, Error> {
   // URLオブジェクトを読み取る
   let file_urls = pasteboard.read_objects_for_classes(&classes_array);
 
-  let mut paths = Vec::new();
+  let mut entries = Vec::new();
 
   if let Some(urls) = file_urls {
     let count = urls.count();
@@ -354,16 +689,120 @@ pub fn read_clipboard_file_paths() -> Result<Vec<String>, Error> {
     for i in 0..count {
       if let Some(url_id) = urls.object_at_index(i) {
         let url = ObjcUrl { url: url_id };
+        let Some(uri) = url.absolute_string() else {
+          continue;
+        };
 
+        // ファイル URL のパスは NSURL から取得する（ファイル参照 URL なども解決される）
+        let mut entry = FileEntry::from_uri(&uri);
         if url.is_file_url() {
-          if let Some(path) = url.get_path() {
-            paths.push(path);
-          }
+          entry.path = url.get_path();
+          entry.is_local = entry.path.is_some();
         }
+        entries.push(entry);
       }
     }
   }
 
   // 空の場合でも空配列を返す（エラーにしない）
-  Ok(paths)
+  Ok(entries)
+}
+
+/// クリップボードの所有期間を識別する値を取得する
+/// NSPasteboard の changeCount を使う
+pub fn clipboard_epoch() -> Result<u64, Error> {
+  // AutoreleasePoolを作成
+  let _pool = AutoreleasePool::new()?;
+
+  // Pasteboardを取得
+  let pasteboard = Pasteboard::general()
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+  Ok(pasteboard.change_count() as u64)
+}
+
+/// クリップボードに書き込んだアプリケーションの情報を取得する
+///
+/// macOS には所有者を知る API がないため、`org.nspasteboard.source` の印を読み、
+/// それがバンドル ID であれば実行中のアプリケーションからプロセスを求める。
+pub fn clipboard_owner_info() -> Result<OwnerInfo, Error> {
+  // AutoreleasePoolを作成
+  let _pool = AutoreleasePool::new()?;
+
+  // Pasteboardを取得
+  let pasteboard = Pasteboard::general()
+    .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+  let marker_type = ObjcString::from_str(SOURCE_MARKER_FORMAT).ok_or_else(|| {
+    Error::new(
+      ErrorKind::Other,
+      "Failed to create NSString for source marker type",
+    )
+  })?;
+
+  let mut info = OwnerInfo::default();
+  let Some(marker) = pasteboard.string_for_type(&marker_type) else {
+    return Ok(info);
+  };
+  info.source = marker
+    .to_rust_string()
+    .and_then(|s| crate::platforms::owner_info::parse_source_marker(s.as_bytes()));
+
+  // 印は空文字列（アプリケーション不明）の場合もある
+  if info.source.is_none() {
+    return Ok(info);
+  }
+  let apps: id = unsafe {
+    msg_send![
+        class!(NSRunningApplication),
+        runningApplicationsWithBundleIdentifier:marker.as_id()
+    ]
+  };
+  let app: id = unsafe { msg_send![apps, firstObject] };
+  if app != cocoa::base::nil {
+    let pid: i32 = unsafe { msg_send![app, processIdentifier] };
+    info.pid = u32::try_from(pid).ok();
+    let url: id = unsafe { msg_send![app, executableURL] };
+    if url != cocoa::base::nil {
+      info.executable = (ObjcUrl { url }).get_path();
+    }
+  }
+  Ok(info)
+}
+
+/// 書き込んだ内容の所有状態（changeCount で判定する）
+pub type Ownership = SequenceOwnership;
+
+/// 書き込みを実行し、その内容の所有状態を返す
+pub fn track_ownership(
+  selection: Selection,
+  write: impl FnOnce() -> Result<(), Error>,
+) -> Result<Ownership, Error> {
+  SequenceOwnership::track(selection, clipboard_epoch, write)
+}
+
+/// クリップボードを空にし、空になった状態の所有状態を返す
+pub fn clear_clipboard(selection: Selection) -> Result<Ownership, Error> {
+  SequenceOwnership::track(selection, clipboard_epoch, || {
+    // AutoreleasePoolを作成
+    let _pool = AutoreleasePool::new()?;
+
+    // Pasteboardを取得
+    let pasteboard = Pasteboard::general()
+      .ok_or_else(|| Error::new(ErrorKind::Other, "Failed to get general pasteboard"))?;
+
+    pasteboard.clear_contents();
+    Ok(())
+  })
+}
+
+/// クリップボードが変更されるたびに増加する値を取得する
+pub fn clipboard_change_count(selection: Selection) -> Result<u64, Error> {
+  if selection != Selection::Clipboard {
+    return Err(Error::new(
+      ErrorKind::Unsupported,
+      "Only the clipboard selection is available on macOS",
+    ));
+  }
+  clipboard_epoch()
 }
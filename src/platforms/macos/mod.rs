@@ -5,7 +5,10 @@ mod wrapper;
 
 // 公開API
 pub use api::{
-  read_clipboard_file_paths, read_clipboard_raw, read_clipboard_text, write_clipboard_file_paths,
+  clear_clipboard, clipboard_change_count, clipboard_epoch, clipboard_owner_info,
+  read_clipboard_file_entries, read_clipboard_file_paths, read_clipboard_format,
+  read_clipboard_raw, read_clipboard_text, read_clipboard_text_decoded, track_ownership,
+  write_clipboard_file_paths, write_clipboard_lazy, write_clipboard_text, Ownership,
 };
 
 // テスト用の公開API
@@ -111,7 +111,7 @@ fn test_read_clipboard_file_paths() {
 
   // ファイルパスをクリップボードにコピー
   let path_str = test_file_path.to_string_lossy().to_string();
-  let copy_result = write_clipboard_file_paths(&[path_str]);
+  let copy_result = write_clipboard_file_paths(&[path_str], None, None);
 
   match copy_result {
     Ok(_) => {
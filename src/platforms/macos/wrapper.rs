@@ -2,7 +2,7 @@
 
 use cocoa::appkit::NSPasteboard;
 use cocoa::base::{id, nil};
-use cocoa::foundation::NSUInteger;
+use cocoa::foundation::{NSInteger, NSUInteger};
 use objc::{class, msg_send, sel, sel_impl};
 use std::ffi::c_char;
 use std::io::{Error, ErrorKind};
@@ -54,9 +54,10 @@ pub struct ObjcString {
 impl ObjcString {
   /// Rust文字列からNSStringを作成
   pub fn from_str(s: &str) -> Option<Self> {
+    // initWithUTF8String は NUL 終端の文字列を要求する
+    let c_string = std::ffi::CString::new(s).ok()?;
     let alloc: id = unsafe { msg_send![class!(NSString), alloc] };
-    let ns_string: id =
-      unsafe { msg_send![alloc, initWithUTF8String: s.as_ptr() as *const c_char] };
+    let ns_string: id = unsafe { msg_send![alloc, initWithUTF8String: c_string.as_ptr()] };
 
     if ns_string != nil {
       Some(Self { ns_string })
@@ -65,6 +66,15 @@ impl ObjcString {
     }
   }
 
+  /// idからObjcStringを作成
+  pub fn from_id(ns_string: id) -> Option<Self> {
+    if ns_string != nil {
+      Some(Self { ns_string })
+    } else {
+      None
+    }
+  }
+
   /// NSStringオブジェクトをidとして取得
   pub fn as_id(&self) -> id {
     self.ns_string
@@ -134,6 +144,12 @@ impl ObjcUrl {
     }
   }
 
+  /// URLの文字列（absoluteString）を取得
+  pub fn absolute_string(&self) -> Option<String> {
+    let string: id = unsafe { msg_send![self.url, absoluteString] };
+    ObjcString::from_id(string)?.to_rust_string()
+  }
+
   /// NSURLオブジェクトをidとして取得
   pub fn as_id(&self) -> id {
     self.url
@@ -229,6 +245,16 @@ impl Pasteboard {
     unsafe { msg_send![self.pasteboard, declareTypes:types_array.as_id() owner:nil] }
   }
 
+  /// 宣言済みのタイプにタイプを追加する
+  pub fn add_types(&self, types_array: &ObjcArray) -> NSInteger {
+    unsafe { msg_send![self.pasteboard, addTypes:types_array.as_id() owner:nil] }
+  }
+
+  /// 特定タイプの文字列を書き込む
+  pub fn set_string_for_type(&self, string: &ObjcString, type_string: &ObjcString) -> bool {
+    unsafe { msg_send![self.pasteboard, setString:string.as_id() forType:type_string.as_id()] }
+  }
+
   /// ペーストボードにオブジェクトを書き込む
   pub fn write_objects(&self, objects_array: &ObjcArray) -> bool {
     unsafe { msg_send![self.pasteboard, writeObjects:objects_array.as_id()] }
@@ -295,6 +321,11 @@ impl Pasteboard {
     }
   }
 
+  /// ペーストボードの内容が変わるたびに増える変更回数を取得
+  pub fn change_count(&self) -> NSInteger {
+    unsafe { msg_send![self.pasteboard, changeCount] }
+  }
+
   /// NSPasteboardオブジェクトをidとして取得
   #[allow(dead_code)]
   pub fn as_id(&self) -> id {
@@ -318,6 +349,18 @@ impl ObjcData {
     unsafe { msg_send![self.data, length] }
   }
 
+  /// NSDataのバッファをコピーせずにスライスとして参照
+  pub fn as_slice(&self) -> Option<&[u8]> {
+    let length = self.length();
+    let bytes: *const u8 = unsafe { msg_send![self.data, bytes] };
+
+    if bytes.is_null() {
+      return None;
+    }
+
+    unsafe { Some(std::slice::from_raw_parts(bytes, length as usize)) }
+  }
+
   /// データをバイト配列に変換
   pub fn to_bytes(&self) -> Option<Vec<u8>> {
     let length = self.length();
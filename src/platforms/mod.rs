@@ -7,8 +7,96 @@ pub mod macos;
 #[cfg(target_os = "linux")]
 pub mod linux;
 
+// 全プラットフォーム共通のタイムアウト設定
+pub mod timeout;
+
+// 一時的な失敗に対する再試行のポリシー
+pub mod retry;
+
+// データを分割して受け渡すための共通処理
+pub mod stream;
+
+// 書き込んだ内容の所有状態の追跡
+pub mod ownership;
+
+// 要求されたときにデータを生成する書き込み
+pub mod lazy;
+
+// クリップボードの所有者の情報
+pub mod owner_info;
+
+// Windows のパスの正規化（OS に依存しない文字列処理）
+pub mod windows_path;
+
+// CF_HDROP（DROPFILES）のエンコードとデコード（OS に依存しないバイト列処理）
+pub mod dropfiles;
+
+// クリップボードのテキストの文字コードの判定とデコード（OS に依存しないバイト列処理）
+pub mod text_encoding;
+
+// ファイルパスと一緒に書き込むテキストの形式（OS に依存しない文字列処理）
+pub mod text_representation;
+
+// 基準のディレクトリからの相対パス（OS に依存しない文字列処理）
+pub mod relative_path;
+
+// WSL・SSH などのパスの対応表による変換（OS に依存しない文字列処理）
+pub mod path_mapping;
+
+// クリップボードから読み取ったファイルの URI とパス（OS に依存しない文字列処理）
+pub mod file_entry;
+
+/// 操作対象のセレクション
+///
+/// `Primary` は X11 にのみ存在し、他のプラットフォームでは `ErrorKind::Unsupported` になる。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Selection {
+  /// `CLIPBOARD` セレクション（Ctrl+C / Ctrl+V）
+  Clipboard,
+  /// `PRIMARY` セレクション（選択して中クリック）
+  Primary,
+}
+
+impl Selection {
+  /// JavaScript 側から渡される名前（`"clipboard"`, `"primary"`）から変換する
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "clipboard" => Some(Selection::Clipboard),
+      "primary" => Some(Selection::Primary),
+      _ => None,
+    }
+  }
+}
+
 // 各プラットフォームモジュールで以下の関数を実装する必要があります:
-// - write_clipboard_file_paths(&[String]) -> Result<(), Error>
+// - write_clipboard_file_paths(&[String], Option<&str>, Option<&PathText>) -> Result<(), Error>
+//   2 番目の引数は書き込み元を示す印（owner_info::SOURCE_MARKER_FORMAT）の内容
+//   3 番目の引数は一緒に書き込むテキストの形式（None ならそのプラットフォームの従来の内容）
+// - write_clipboard_text(&str, &[String]) -> Result<(), Error>
+//   2 番目の引数（絶対パス）が空でなければ、その一覧も URI のリストとして書き込む
 // - read_clipboard_text() -> Result<String, Error>
+// - read_clipboard_text_decoded() -> Result<text_encoding::DecodedText, Error>
+//   テキストを読み取った形式と文字コード、変換できない部分があったかどうかも返す
 // - read_clipboard_raw() -> Result<Vec<u8>, Error>
 // - read_clipboard_file_paths() -> Result<Vec<String>, Error>
+// - read_clipboard_file_entries() -> Result<Vec<file_entry::FileEntry>, Error>
+//   file:// 以外の URI も含め、URI とローカルで開けるパス（分かる場合）の組を返す
+// - clipboard_epoch() -> Result<u64, Error>
+//   クリップボードの所有者が変わると値が変わる（スナップショットの一貫性確認に使う）
+// - clipboard_change_count(Selection) -> Result<u64, Error>
+//   クリップボードが変更されるたびに増加する値
+// - read_clipboard_format(&str, &mut stream::Sink) -> Result<u64, Error>
+//   指定した形式のデータを分割して読み取り、合計バイト数を返す
+// - track_ownership(Selection, write) -> Result<Ownership, Error>
+//   書き込みを実行し、その内容の所有状態（is_owner / wait_until_lost）を返す
+// - clear_clipboard(Selection) -> Result<Ownership, Error>
+//   セレクションを空にし、空になった状態の所有状態を返す（空の一覧の書き込みも同じ扱い）
+// - write_clipboard_lazy(&[String], lazy::Provider) -> Result<(), Error>
+//   フォーマットだけを登録し、貼り付け先が要求したときに provider でデータを生成する
+// - start_file_drag(u64, &[String]) -> Result<FileDragResult, Error>（Linux のみ）
+//   ウィンドウから XDND でファイルをドラッグし、終わるまで待つ
+// - clipboard_owner_info() -> Result<owner_info::OwnerInfo, Error>
+//   クリップボードの所有者のウィンドウ・プロセス・書き込み元の印を取得する
+//
+// クリップボードが他のプロセスにロックされているなどの一時的な失敗は retry::busy の
+// エラーにして、その操作を retry::retry で囲む（ポリシーは呼び出し側が with_policy で指定する）
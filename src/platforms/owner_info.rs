@@ -0,0 +1,53 @@
+// クリップボードに書き込んだアプリケーションの情報
+//
+// X11 と Windows では所有者のウィンドウからプロセスを辿れる。macOS には
+// 所有者を知る API がないため、書き込んだアプリケーションが自分で追加する
+// `org.nspasteboard.source` の印（nspasteboard.org の慣習）を読む。
+// この印は他のプラットフォームでも同じ名前のフォーマットとして読み書きする。
+
+/// 書き込んだアプリケーションの識別子（macOS ではバンドル ID）を入れるフォーマット
+pub const SOURCE_MARKER_FORMAT: &str = "org.nspasteboard.source";
+
+/// クリップボードの所有者の情報
+///
+/// 取得できなかった項目は None になる。
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OwnerInfo {
+  /// 所有者のウィンドウ（X11 のウィンドウ ID、Windows の HWND）
+  pub window: Option<u64>,
+  /// 所有者のプロセス ID
+  pub pid: Option<u32>,
+  /// 所有者の実行ファイルのパス
+  pub executable: Option<String>,
+  /// `org.nspasteboard.source` の印の内容
+  pub source: Option<String>,
+}
+
+/// 読み取った印のデータを文字列にする
+///
+/// 前後の空白と NUL 終端を取り除き、空の場合は None を返す。
+pub fn parse_source_marker(data: &[u8]) -> Option<String> {
+  let text = String::from_utf8_lossy(data);
+  let text = text.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+  (!text.is_empty()).then(|| text.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // NUL 終端や改行は取り除き、空の印は無視する
+  #[test]
+  fn test_parse_source_marker() {
+    assert_eq!(
+      parse_source_marker(b"com.example.app\0"),
+      Some("com.example.app".to_string())
+    );
+    assert_eq!(
+      parse_source_marker(b" com.example.app\n"),
+      Some("com.example.app".to_string())
+    );
+    assert_eq!(parse_source_marker(b""), None);
+    assert_eq!(parse_source_marker(b"\0"), None);
+  }
+}
@@ -0,0 +1,131 @@
+// 書き込んだ内容の所有状態を追跡するための共通処理
+//
+// X11 / Wayland ではセレクションの所有者やヘルパーのプロセスから判定できるが、
+// Windows と macOS では所有者を直接知る方法がないため、書き込み直後の
+// シーケンス番号（changeCount）から変わったかどうかで判定する。
+
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+use super::Selection;
+
+/// 所有状態を確認する間隔
+pub const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// 所有を失うか `stop` が true になるまで `is_owner` を繰り返し確認する
+///
+/// 所有を失った場合は true、打ち切られた場合は false を返す。
+pub fn poll_until_lost(
+  mut is_owner: impl FnMut() -> Result<bool, Error>,
+  stop: &AtomicBool,
+  interval: Duration,
+) -> Result<bool, Error> {
+  loop {
+    if stop.load(Ordering::SeqCst) {
+      return Ok(false);
+    }
+    if !is_owner()? {
+      return Ok(true);
+    }
+    thread::sleep(interval);
+  }
+}
+
+/// シーケンス番号による所有状態
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+#[derive(Debug)]
+pub struct SequenceOwnership {
+  sequence: u64,
+  current: fn() -> Result<u64, Error>,
+}
+
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+impl SequenceOwnership {
+  /// `write` を実行し、直後のシーケンス番号を記録する
+  pub fn track(
+    selection: Selection,
+    current: fn() -> Result<u64, Error>,
+    write: impl FnOnce() -> Result<(), Error>,
+  ) -> Result<Self, Error> {
+    if selection != Selection::Clipboard {
+      return Err(Error::new(
+        ErrorKind::Unsupported,
+        "Only the clipboard selection is available on this platform",
+      ));
+    }
+    write()?;
+    Ok(Self {
+      sequence: current()?,
+      current,
+    })
+  }
+
+  /// 書き込んだ内容がまだクリップボードにあるかどうか
+  pub fn is_owner(&self) -> Result<bool, Error> {
+    Ok((self.current)()? == self.sequence)
+  }
+
+  /// 所有を失うまで待つ（[`poll_until_lost`] を参照）
+  pub fn wait_until_lost(&self, stop: &AtomicBool) -> Result<bool, Error> {
+    poll_until_lost(|| self.is_owner(), stop, POLL_INTERVAL)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::sync::atomic::AtomicU64;
+
+  static SEQUENCE: AtomicU64 = AtomicU64::new(1);
+
+  fn current_sequence() -> Result<u64, Error> {
+    Ok(SEQUENCE.load(Ordering::SeqCst))
+  }
+
+  // 書き込み後にシーケンス番号が変わると所有を失ったと判定される
+  #[test]
+  fn test_sequence_ownership() {
+    let ownership = SequenceOwnership::track(Selection::Clipboard, current_sequence, || {
+      SEQUENCE.fetch_add(1, Ordering::SeqCst);
+      Ok(())
+    })
+    .unwrap();
+    assert!(ownership.is_owner().unwrap());
+
+    SEQUENCE.fetch_add(1, Ordering::SeqCst);
+    assert!(!ownership.is_owner().unwrap());
+    let stop = AtomicBool::new(false);
+    assert!(ownership.wait_until_lost(&stop).unwrap());
+  }
+
+  // PRIMARY は追跡できない
+  #[test]
+  fn test_sequence_ownership_rejects_primary() {
+    let err =
+      SequenceOwnership::track(Selection::Primary, current_sequence, || Ok(())).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Unsupported);
+  }
+
+  // 打ち切られた場合は false を返す
+  #[test]
+  fn test_poll_until_lost_stops() {
+    let stop = AtomicBool::new(true);
+    assert!(!poll_until_lost(|| Ok(true), &stop, Duration::from_millis(1)).unwrap());
+
+    let stop = AtomicBool::new(false);
+    let mut checks = 0;
+    let lost = poll_until_lost(
+      || {
+        checks += 1;
+        Ok(checks < 3)
+      },
+      &stop,
+      Duration::from_millis(1),
+    )
+    .unwrap();
+    assert!(lost);
+    assert_eq!(checks, 3);
+  }
+}
@@ -0,0 +1,343 @@
+// パスの対応表による変換（WSL・SSH などのリモートのパス）
+//
+// WSL の `/mnt/c/...` を `C:\...` に、`/home/...` を `\\wsl$\<distro>\home\...` に、
+// sshfs のマウントを `sftp://` の URI にするように、パスの先頭部分を置き換える。
+// 対応表の `from` / `to` には POSIX のパス、Windows のパス（ドライブや UNC）、
+// URI（`scheme://authority/path`）のどれでも書けるため、逆向きの変換も同じ仕組みでできる。
+// ファイルシステムにはアクセスせず、文字列だけで変換するため、どのプラットフォームでもテストできる。
+
+use crate::platforms::windows_path;
+
+/// パスの先頭部分の対応
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathMapping {
+  /// 置き換える先頭部分（ディレクトリ）
+  pub from: String,
+  /// 置き換えた後の先頭部分
+  pub to: String,
+}
+
+impl PathMapping {
+  pub fn new(from: impl Into<String>, to: impl Into<String>) -> Self {
+    Self {
+      from: from.into(),
+      to: to.into(),
+    }
+  }
+}
+
+/// WSL のディストリビューション `distro` の中のパスを、Windows から開けるパスにする対応表
+///
+/// `/mnt/<ドライブ>/` はそのドライブに、それ以外は `\\wsl$\<distro>\` の下になる。
+pub fn wsl_mappings(distro: &str) -> Vec<PathMapping> {
+  let mut mappings: Vec<PathMapping> = ('a'..='z')
+    .map(|drive| {
+      PathMapping::new(
+        format!("/mnt/{}/", drive),
+        format!("{}:\\", drive.to_ascii_uppercase()),
+      )
+    })
+    .collect();
+  mappings.push(PathMapping::new("/", format!(r"\\wsl$\{}\", distro)));
+  mappings
+}
+
+/// `path` を対応表で変換する
+///
+/// `from` に要素単位で一致するもののうち、最も長いもの（同じ長さなら先に書かれたもの）を使う。
+/// `.` と `..` は一致させる前に解決する。一致するものがない場合は None を返す。
+pub fn map_path(path: &str, mappings: &[PathMapping]) -> Option<String> {
+  let path = Location::parse(path);
+  let (mapping, from) = mappings
+    .iter()
+    .filter_map(|mapping| {
+      let from = Location::parse(&mapping.from);
+      path.starts_with(&from).then_some((mapping, from))
+    })
+    .fold(
+      None,
+      |best: Option<(&PathMapping, Location)>, candidate| match best {
+        Some((mapping, from)) if from.components.len() >= candidate.1.components.len() => {
+          Some((mapping, from))
+        }
+        _ => Some(candidate),
+      },
+    )?;
+
+  let rest = &path.components[from.components.len()..];
+  Some(Location::parse(&mapping.to).join(rest))
+}
+
+// パスの書き方
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Style {
+  /// `/` 区切り、大文字小文字を区別する
+  Posix,
+  /// `\` または `/` 区切り、大文字小文字を区別しない
+  Windows,
+  /// `scheme://authority` に続く `/` 区切りのパス。要素はパーセントエンコードする
+  Uri,
+}
+
+// 先頭部分（POSIX のルート、ドライブ、UNC の共有、URI の scheme://authority）と要素に分けたパス
+#[derive(Debug, Clone)]
+struct Location {
+  style: Style,
+  root: String,
+  components: Vec<String>,
+}
+
+impl Location {
+  fn parse(path: &str) -> Self {
+    if let Some(scheme_end) = path.find("://") {
+      let after = &path[scheme_end + 3..];
+      let authority_end = after.find('/').unwrap_or(after.len());
+      return Self {
+        style: Style::Uri,
+        root: path[..scheme_end + 3 + authority_end].to_string(),
+        components: normalize(
+          split(&after[authority_end..], &['/']).map(windows_path::percent_decode),
+          true,
+        ),
+      };
+    }
+
+    let bytes = path.as_bytes();
+    let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+    if drive {
+      return Self {
+        style: Style::Windows,
+        root: format!("{}:", path[..1].to_ascii_uppercase()),
+        components: normalize(split(&path[2..], &['\\', '/']).map(String::from), true),
+      };
+    }
+    if path.starts_with(r"\\") {
+      // `\\server\share` までを先頭部分とする
+      let mut parts = split(path, &['\\', '/']);
+      let server = parts.next().unwrap_or_default();
+      let share = parts.next().unwrap_or_default();
+      return Self {
+        style: Style::Windows,
+        root: format!(r"\\{}\{}", server, share),
+        components: normalize(parts.map(String::from), true),
+      };
+    }
+
+    let rooted = path.starts_with('/');
+    Self {
+      style: Style::Posix,
+      root: if rooted { "/" } else { "" }.to_string(),
+      components: normalize(split(path, &['/']).map(String::from), rooted),
+    }
+  }
+
+  // `prefix` の先頭部分と要素がすべて一致するかどうか
+  fn starts_with(&self, prefix: &Location) -> bool {
+    if self.style != prefix.style || self.components.len() < prefix.components.len() {
+      return false;
+    }
+    let same = |a: &str, b: &str| match self.style {
+      Style::Windows => a.to_lowercase() == b.to_lowercase(),
+      // scheme と host は大文字小文字を区別しない
+      Style::Uri => a.eq_ignore_ascii_case(b),
+      Style::Posix => a == b,
+    };
+    let root_matches = match self.style {
+      Style::Uri | Style::Windows => same(&self.root, &prefix.root),
+      Style::Posix => self.root == prefix.root,
+    };
+    root_matches
+      && self
+        .components
+        .iter()
+        .zip(&prefix.components)
+        .all(|(a, b)| match self.style {
+          Style::Uri => a == b,
+          _ => same(a, b),
+        })
+  }
+
+  // 要素を追加したパスを、このパスの書き方で文字列にする
+  fn join(&self, rest: &[String]) -> String {
+    let components = self.components.iter().chain(rest);
+    match self.style {
+      Style::Posix => {
+        let joined = components.cloned().collect::<Vec<_>>().join("/");
+        match (self.root.as_str(), joined.is_empty()) {
+          ("/", _) => format!("/{}", joined),
+          (_, true) => ".".to_string(),
+          _ => joined,
+        }
+      }
+      Style::Windows => {
+        let joined = components.cloned().collect::<Vec<_>>().join("\\");
+        if joined.is_empty() && self.root.starts_with(r"\\") {
+          self.root.clone()
+        } else {
+          format!(r"{}\{}", self.root, joined)
+        }
+      }
+      Style::Uri => {
+        let joined = components
+          .map(|c| windows_path::percent_encode(c))
+          .collect::<Vec<_>>()
+          .join("/");
+        format!("{}/{}", self.root, joined)
+      }
+    }
+  }
+}
+
+// `.` を無視し、`..` を一つ上に戻して取り除く（`windows_path` と同じ）。先頭部分より上には戻らず、
+// 相対パスの先頭の `..` だけは残す。対応表と一致させる前に行うため、`/mnt/c/../d` は `/mnt/d` になる
+fn normalize(components: impl Iterator<Item = String>, rooted: bool) -> Vec<String> {
+  let mut normalized: Vec<String> = Vec::new();
+  for component in components {
+    if component == "." {
+      continue;
+    }
+    if component == ".." {
+      let can_pop = normalized.last().is_some_and(|last| last != "..");
+      if can_pop {
+        normalized.pop();
+      } else if !rooted {
+        normalized.push(component);
+      }
+    } else {
+      normalized.push(component);
+    }
+  }
+  normalized
+}
+
+fn split<'a>(path: &'a str, separators: &'a [char]) -> impl Iterator<Item = &'a str> + 'a {
+  path
+    .split(separators)
+    .filter(|c| !c.is_empty() && *c != ".")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn mappings(pairs: &[(&str, &str)]) -> Vec<PathMapping> {
+    pairs
+      .iter()
+      .map(|(from, to)| PathMapping::new(*from, *to))
+      .collect()
+  }
+
+  // 逆向きの対応表
+  fn reversed(table: &[PathMapping]) -> Vec<PathMapping> {
+    table
+      .iter()
+      .map(|mapping| PathMapping::new(mapping.to.clone(), mapping.from.clone()))
+      .collect()
+  }
+
+  // WSL の既定の対応表
+  #[test]
+  fn test_wsl_mappings() {
+    let table = wsl_mappings("Ubuntu");
+    let cases = [
+      ("/mnt/c/Users/me/a b.txt", r"C:\Users\me\a b.txt"),
+      ("/mnt/d", r"D:\"),
+      ("/mnt/c/", r"C:\"),
+      ("/home/me/project/src", r"\\wsl$\Ubuntu\home\me\project\src"),
+      ("/", r"\\wsl$\Ubuntu"),
+      // `/mnt/c` で始まる別のディレクトリはドライブではない
+      ("/mnt/cdrom/x", r"\\wsl$\Ubuntu\mnt\cdrom\x"),
+      ("/mnt/wsl/x", r"\\wsl$\Ubuntu\mnt\wsl\x"),
+      ("/tmp/./a", r"\\wsl$\Ubuntu\tmp\a"),
+      // `..` は対応表と一致させる前に解決する
+      ("/mnt/c/../d/x", r"D:\x"),
+      ("/mnt/c/Users/../../cdrom", r"\\wsl$\Ubuntu\mnt\cdrom"),
+      ("/../mnt/c/a", r"C:\a"),
+    ];
+    for (path, expected) in cases {
+      assert_eq!(
+        map_path(path, &table).as_deref(),
+        Some(expected),
+        "{}",
+        path
+      );
+    }
+    // 相対パスは変換しない
+    assert_eq!(map_path("relative/a", &table), None);
+  }
+
+  // 逆向き（Windows のパスから WSL のパス）と、大文字小文字・区切り文字の違い
+  #[test]
+  fn test_reverse_mappings() {
+    let table = reversed(&wsl_mappings("Ubuntu"));
+    let cases = [
+      (r"C:\Users\me\a.txt", Some("/mnt/c/Users/me/a.txt")),
+      (r"c:/users/me", Some("/mnt/c/users/me")),
+      (r"C:\Users\..\Windows", Some("/mnt/c/Windows")),
+      // 共有より上には戻らない
+      (r"\\wsl$\Ubuntu\..\etc", Some("/etc")),
+      (r"\\wsl$\Ubuntu\home\me", Some("/home/me")),
+      (r"\\WSL$\ubuntu\home\me", Some("/home/me")),
+      (r"\\wsl$\Debian\home\me", None),
+      (r"\\server\share\a", None),
+      ("/home/me", None),
+    ];
+    for (path, expected) in cases {
+      assert_eq!(map_path(path, &table).as_deref(), expected, "{}", path);
+    }
+  }
+
+  // sshfs のマウントを sftp:// の URI にする（要素はパーセントエンコード）
+  #[test]
+  fn test_uri_mappings() {
+    let table = mappings(&[
+      ("/home/me/mnt/server", "sftp://me@server.example/home/me"),
+      (
+        "/home/me/mnt/server/www",
+        "sftp://www@server.example/var/www/",
+      ),
+    ]);
+    let cases = [
+      (
+        "/home/me/mnt/server/notes/a b#1.txt",
+        Some("sftp://me@server.example/home/me/notes/a%20b%231.txt"),
+      ),
+      // 長く一致するほうを使う
+      (
+        "/home/me/mnt/server/www/index.html",
+        Some("sftp://www@server.example/var/www/index.html"),
+      ),
+      (
+        "/home/me/mnt/server",
+        Some("sftp://me@server.example/home/me"),
+      ),
+      ("/home/me/mnt/serverless/a", None),
+    ];
+    for (path, expected) in cases {
+      assert_eq!(map_path(path, &table).as_deref(), expected, "{}", path);
+    }
+
+    // URI からローカルのパスへ（パーセントエンコードを戻す）
+    let reverse = reversed(&table);
+    assert_eq!(
+      map_path("SFTP://me@server.example/home/me/a%20b.txt", &reverse).as_deref(),
+      Some("/home/me/mnt/server/a b.txt")
+    );
+    // エンコードされた `..` も解決してから一致させる
+    assert_eq!(
+      map_path(
+        "sftp://me@server.example/home/me/%2e%2e/%2e%2e/etc/passwd",
+        &reverse
+      ),
+      None
+    );
+  }
+
+  // 同じ長さで一致する場合は先に書かれたものを使う
+  #[test]
+  fn test_first_mapping_wins_on_tie() {
+    let table = mappings(&[("/data", "D:\\"), ("/data/", "E:\\")]);
+    assert_eq!(map_path("/data/a", &table).as_deref(), Some(r"D:\a"));
+    assert_eq!(map_path("/dat", &table), None);
+  }
+}
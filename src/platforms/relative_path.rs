@@ -0,0 +1,106 @@
+// 基準のディレクトリからの相対パスの計算
+//
+// Windows のパス（ドライブや UNC で始まるパス）は windows_path の規則で、
+// それ以外は POSIX のパスとして扱う。ファイルシステムにはアクセスせず、
+// 文字列だけで計算するため、どのプラットフォームでもテストできる。
+// POSIX ではマウントポイントも 1 つのツリーの中にあるため、別のマウントのパスも
+// `..` をたどる相対パスで表せる。
+
+use crate::platforms::windows_path;
+
+/// `path` を `base`（どちらも絶対パス）からの相対パスにする
+///
+/// `.` と `..` は文字列として解決するため、シンボリックリンクを含む場合は
+/// 正規化（`fs::canonicalize`）済みのパスを渡す。相対パスで表せない場合
+/// （異なるドライブや UNC の共有、絶対パスでない場合）は None を返す。
+pub fn relative_path(path: &str, base: &str) -> Option<String> {
+  if is_windows_path(path) || is_windows_path(base) {
+    return windows_path::relative(path, base);
+  }
+  if !path.starts_with('/') || !base.starts_with('/') {
+    return None;
+  }
+
+  let path = posix_components(path);
+  let base = posix_components(base);
+  let common = path.iter().zip(&base).take_while(|(a, b)| a == b).count();
+  let mut components = vec![".."; base.len() - common];
+  components.extend(&path[common..]);
+  if components.is_empty() {
+    Some(".".to_string())
+  } else {
+    Some(components.join("/"))
+  }
+}
+
+/// ドライブ（`C:`）、UNC（`\\server`）、拡張長パスのいずれかで始まるかどうか
+pub fn is_windows_path(path: &str) -> bool {
+  let bytes = path.as_bytes();
+  let drive = bytes.len() >= 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':';
+  drive || path.starts_with("\\\\")
+}
+
+// 絶対パスの要素（`.` と空の要素は無視し、`..` は一つ上に戻る。ルートより上には戻らない）
+fn posix_components(path: &str) -> Vec<&str> {
+  let mut components = Vec::new();
+  for component in path.split('/') {
+    match component {
+      "" | "." => {}
+      ".." => {
+        components.pop();
+      }
+      _ => components.push(component),
+    }
+  }
+  components
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 共通の部分より上は `..` でたどる
+  #[test]
+  fn test_relative_path() {
+    let cases = [
+      (
+        "/work/project/src/main.rs",
+        "/work/project",
+        Some("src/main.rs"),
+      ),
+      ("/work/other/a.txt", "/work/project", Some("../other/a.txt")),
+      ("/a.txt", "/work/project", Some("../../a.txt")),
+      ("/work/project", "/work/project", Some(".")),
+      ("/work/project", "/work/project/src/deep", Some("../..")),
+      // 別のマウントも同じツリーの中
+      (
+        "/mnt/usb/photo.jpg",
+        "/home/me/project",
+        Some("../../../mnt/usb/photo.jpg"),
+      ),
+      // `.` / `..` / 連続した区切り / 末尾の区切り
+      ("/work/./x/../a.txt", "/work//", Some("a.txt")),
+      ("/../../etc/hosts", "/", Some("etc/hosts")),
+      // 大文字小文字は区別する
+      ("/Work/a.txt", "/work", Some("../Work/a.txt")),
+      // 要素の途中で一致していても共通部分ではない
+      ("/work/project-2/a", "/work/project", Some("../project-2/a")),
+      // Windows のパス
+      (r"C:\work\src\a.rs", r"C:\work", Some(r"src\a.rs")),
+      (r"D:\data", r"C:\work", None),
+      (r"\\server\share\a", r"\\server\share\b", Some(r"..\a")),
+      // 絶対パスでない
+      ("relative/a.txt", "/work", None),
+      ("/work/a.txt", "work", None),
+    ];
+    for (path, base, expected) in cases {
+      assert_eq!(
+        relative_path(path, base).as_deref(),
+        expected,
+        "relative_path({:?}, {:?})",
+        path,
+        base
+      );
+    }
+  }
+}
@@ -0,0 +1,251 @@
+// 一時的な失敗に対する再試行（バックオフ付き）
+//
+// Windows の OpenClipboard は他のプロセスがクリップボードを開いている間は失敗し、
+// X11 のセレクションの取得や変換も所有者の切り替わりと重なると失敗することがある。
+// 各プラットフォームの実装は、このような一時的な失敗を `busy` で作ったエラー
+// （`ErrorKind::WouldBlock`）で返し、その操作を `retry` で囲む。
+//
+// ポリシーは全体の設定（`set_policy`）と、呼び出しごとの設定（`with_policy`）がある。
+
+use std::cell::Cell;
+use std::io::{Error, ErrorKind};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 再試行のポリシー
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+  /// 最初の試行を含めた最大の試行回数（1 なら再試行しない）
+  pub attempts: u32,
+  /// 最初の再試行までの待ち時間。再試行のたびに 2 倍になる
+  pub backoff: Duration,
+  /// 最初の試行からの合計の制限時間。これを超えて待つことはない
+  pub deadline: Duration,
+}
+
+/// デフォルトのポリシー
+pub const DEFAULT_POLICY: RetryPolicy = RetryPolicy {
+  attempts: 5,
+  backoff: Duration::from_millis(10),
+  deadline: Duration::from_secs(1),
+};
+
+static POLICY: Mutex<RetryPolicy> = Mutex::new(DEFAULT_POLICY);
+
+thread_local! {
+  // `with_policy` で指定された、このスレッドの呼び出しだけに使うポリシー
+  static CALL_POLICY: Cell<Option<RetryPolicy>> = const { Cell::new(None) };
+}
+
+/// 全体のポリシーを取得する
+pub fn global_policy() -> RetryPolicy {
+  *POLICY.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// 全体のポリシーを設定する
+pub fn set_policy(policy: RetryPolicy) {
+  *POLICY.lock().unwrap_or_else(|e| e.into_inner()) = policy;
+}
+
+/// 現在のポリシーを取得する（`with_policy` の中ではその呼び出しのポリシー）
+pub fn policy() -> RetryPolicy {
+  CALL_POLICY.with(Cell::get).unwrap_or_else(global_policy)
+}
+
+/// `f` の実行中だけ、このスレッドのポリシーを `policy` にする
+///
+/// `policy` が None の場合は全体のポリシーのまま実行する。
+pub fn with_policy<T>(policy: Option<RetryPolicy>, f: impl FnOnce() -> T) -> T {
+  // f がパニックしても元に戻す
+  struct Restore(Option<RetryPolicy>);
+  impl Drop for Restore {
+    fn drop(&mut self) {
+      CALL_POLICY.with(|p| p.set(self.0));
+    }
+  }
+
+  let Some(policy) = policy else {
+    return f();
+  };
+  let _restore = Restore(CALL_POLICY.with(|p| p.replace(Some(policy))));
+  f()
+}
+
+/// 再試行すれば成功する可能性がある失敗を表すエラーを作成する
+pub fn busy(message: impl Into<String>) -> Error {
+  Error::new(ErrorKind::WouldBlock, message.into())
+}
+
+/// 再試行の対象になるエラーかどうか
+pub fn is_transient(e: &Error) -> bool {
+  matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::Interrupted)
+}
+
+/// 現在のポリシーで `f` を実行し、一時的な失敗の場合は再試行する
+pub fn retry<T>(operation: &str, f: impl FnMut() -> Result<T, Error>) -> Result<T, Error> {
+  retry_with(&policy(), operation, f)
+}
+
+/// 指定したポリシーで `f` を実行し、一時的な失敗の場合は再試行する
+///
+/// 再試行した後に失敗した場合は、エラーの種類を保ったまま試行回数をメッセージに含める。
+pub fn retry_with<T>(
+  policy: &RetryPolicy,
+  operation: &str,
+  mut f: impl FnMut() -> Result<T, Error>,
+) -> Result<T, Error> {
+  let started = Instant::now();
+  let mut backoff = policy.backoff;
+  let mut attempts = 0;
+
+  loop {
+    attempts += 1;
+    let e = match f() {
+      Ok(value) => return Ok(value),
+      Err(e) => e,
+    };
+
+    // 回数か時間を使い切った、または再試行しても変わらない失敗
+    let give_up = !is_transient(&e)
+      || attempts >= policy.attempts.max(1)
+      || started.elapsed() + backoff > policy.deadline;
+    if give_up {
+      return Err(if attempts > 1 {
+        Error::new(
+          e.kind(),
+          format!("{} failed after {} attempts: {}", operation, attempts, e),
+        )
+      } else {
+        e
+      });
+    }
+
+    thread::sleep(backoff);
+    backoff = backoff.saturating_mul(2);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 指定した回数だけ一時的に失敗するバックエンド
+  struct MockBackend {
+    transient_failures: u32,
+    calls: u32,
+  }
+
+  impl MockBackend {
+    fn new(transient_failures: u32) -> Self {
+      Self {
+        transient_failures,
+        calls: 0,
+      }
+    }
+
+    fn open_clipboard(&mut self) -> Result<&'static str, Error> {
+      self.calls += 1;
+      if self.calls <= self.transient_failures {
+        Err(busy("Failed to open clipboard: 5"))
+      } else {
+        Ok("opened")
+      }
+    }
+  }
+
+  fn quick(attempts: u32) -> RetryPolicy {
+    RetryPolicy {
+      attempts,
+      backoff: Duration::from_millis(1),
+      deadline: Duration::from_secs(5),
+    }
+  }
+
+  // 一時的な失敗の後に成功すれば、その結果が返る
+  #[test]
+  fn test_retry_recovers_from_transient_failures() {
+    let mut backend = MockBackend::new(3);
+    let result = retry_with(&quick(5), "Opening the clipboard", || {
+      backend.open_clipboard()
+    });
+    assert_eq!(result.unwrap(), "opened");
+    assert_eq!(backend.calls, 4);
+  }
+
+  // 回数を使い切った場合は試行回数がエラーに含まれる
+  #[test]
+  fn test_retry_reports_attempts() {
+    let mut backend = MockBackend::new(u32::MAX);
+    let err = retry_with(&quick(3), "Opening the clipboard", || {
+      backend.open_clipboard()
+    })
+    .unwrap_err();
+    assert_eq!(backend.calls, 3);
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(
+      err.to_string(),
+      "Opening the clipboard failed after 3 attempts: Failed to open clipboard: 5"
+    );
+  }
+
+  // 一時的でない失敗は再試行せず、そのまま返す
+  #[test]
+  fn test_retry_stops_on_permanent_failure() {
+    let mut calls = 0;
+    let err = retry_with(&quick(5), "Reading", || -> Result<(), Error> {
+      calls += 1;
+      Err(Error::new(ErrorKind::NotFound, "No text available"))
+    })
+    .unwrap_err();
+    assert_eq!(calls, 1);
+    assert_eq!(err.kind(), ErrorKind::NotFound);
+    assert_eq!(err.to_string(), "No text available");
+  }
+
+  // 合計の制限時間を超えて待たない
+  #[test]
+  fn test_retry_respects_deadline() {
+    let policy = RetryPolicy {
+      attempts: 100,
+      backoff: Duration::from_millis(20),
+      deadline: Duration::from_millis(100),
+    };
+    let mut backend = MockBackend::new(u32::MAX);
+    let err = retry_with(&policy, "Opening the clipboard", || {
+      backend.open_clipboard()
+    })
+    .unwrap_err();
+    // 20 + 40 ms 待った後、次の 80 ms は制限時間を超えるため諦める
+    assert_eq!(backend.calls, 3);
+    assert!(err.to_string().contains("after 3 attempts"));
+  }
+
+  // 呼び出しごとのポリシーは、その呼び出しの間だけ全体のポリシーより優先される
+  #[test]
+  fn test_with_policy_overrides_for_the_call() {
+    let mut backend = MockBackend::new(u32::MAX);
+    let result = with_policy(Some(quick(2)), || {
+      assert_eq!(policy(), quick(2));
+      retry("Opening the clipboard", || backend.open_clipboard())
+    });
+    assert!(result.is_err());
+    assert_eq!(backend.calls, 2);
+    assert_eq!(policy(), global_policy());
+
+    // None の場合は全体のポリシーを使う
+    with_policy(None, || assert_eq!(policy(), global_policy()));
+  }
+
+  // 再試行しない設定では 1 回だけ試す
+  #[test]
+  fn test_single_attempt() {
+    let mut backend = MockBackend::new(1);
+    let err = retry_with(&quick(1), "Opening the clipboard", || {
+      backend.open_clipboard()
+    })
+    .unwrap_err();
+    assert_eq!(backend.calls, 1);
+    assert_eq!(err.to_string(), "Failed to open clipboard: 5");
+  }
+}
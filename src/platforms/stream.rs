@@ -0,0 +1,148 @@
+// クリップボードのデータを分割して受け渡すための共通処理
+//
+// 大きな画像や仮想ファイルを 1 つの Vec<u8> にまとめずに、
+// 受け取った順にファイルや JavaScript のストリームへ渡すために使う。
+
+use std::io::{Error, ErrorKind};
+use std::sync::{Condvar, Mutex};
+
+/// 1 回に受け渡すデータの最大バイト数
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// 分割されたデータを受け取るコールバック
+pub type Sink<'a> = dyn FnMut(&[u8]) -> Result<(), Error> + 'a;
+
+/// メモリ上にあるデータを CHUNK_SIZE ごとに渡し、合計バイト数を返す
+#[cfg_attr(target_os = "linux", allow(dead_code))]
+pub fn feed_chunks(data: &[u8], sink: &mut Sink) -> Result<u64, Error> {
+  for chunk in data.chunks(CHUNK_SIZE) {
+    sink(chunk)?;
+  }
+  Ok(data.len() as u64)
+}
+
+/// 受け取り側（JavaScript の Readable）がデータを求めているかどうか
+///
+/// 読み取りスレッドは `wait` で要求を待ってからチャンクを渡し、受け取り側のバッファが
+/// いっぱいになったら（`push()` が false を返したら）`pause` して次の要求まで止まる。
+#[derive(Debug, Default)]
+pub struct Demand {
+  state: Mutex<DemandState>,
+  changed: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct DemandState {
+  wanted: bool,
+  closed: bool,
+}
+
+impl Demand {
+  /// データを求める（Readable の read()）
+  pub fn request(&self) {
+    self.update(|state| state.wanted = true);
+  }
+
+  /// 次の要求まで止める
+  pub fn pause(&self) {
+    self.update(|state| state.wanted = false);
+  }
+
+  /// 受け取り側が閉じられた（Readable の destroy()）
+  pub fn close(&self) {
+    self.update(|state| state.closed = true);
+  }
+
+  /// 受け取り側が閉じられたかどうか
+  pub fn is_closed(&self) -> bool {
+    self.state.lock().unwrap_or_else(|e| e.into_inner()).closed
+  }
+
+  /// データが求められるまで待つ。受け取り側が閉じられた場合は `ErrorKind::BrokenPipe`
+  pub fn wait(&self) -> Result<(), Error> {
+    let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+    let state = self
+      .changed
+      .wait_while(state, |state| !state.wanted && !state.closed)
+      .unwrap_or_else(|e| e.into_inner());
+    if state.closed {
+      return Err(Error::new(ErrorKind::BrokenPipe, "Stream was destroyed"));
+    }
+    Ok(())
+  }
+
+  fn update(&self, f: impl FnOnce(&mut DemandState)) {
+    f(&mut self.state.lock().unwrap_or_else(|e| e.into_inner()));
+    self.changed.notify_all();
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // データは CHUNK_SIZE 以下に分割され、順番通りに渡される
+  #[test]
+  fn test_feed_chunks() {
+    let data: Vec<u8> = (0..CHUNK_SIZE * 2 + 10).map(|i| i as u8).collect();
+    let mut sizes = Vec::new();
+    let mut received = Vec::new();
+    let total = feed_chunks(&data, &mut |chunk| {
+      sizes.push(chunk.len());
+      received.extend_from_slice(chunk);
+      Ok(())
+    })
+    .unwrap();
+
+    assert_eq!(total, data.len() as u64);
+    assert_eq!(sizes, vec![CHUNK_SIZE, CHUNK_SIZE, 10]);
+    assert_eq!(received, data);
+  }
+
+  // コールバックのエラーで中断される
+  #[test]
+  fn test_feed_chunks_stops_on_error() {
+    let data = vec![0u8; CHUNK_SIZE * 3];
+    let mut calls = 0;
+    let result = feed_chunks(&data, &mut |_| {
+      calls += 1;
+      Err(Error::other("disk full"))
+    });
+    assert!(result.is_err());
+    assert_eq!(calls, 1);
+  }
+
+  // 要求があるまで待ち、止めた後は次の要求まで待つ
+  #[test]
+  fn test_demand_wait() {
+    use std::sync::{mpsc, Arc};
+    use std::thread;
+    use std::time::Duration;
+
+    let demand = Arc::new(Demand::default());
+    let (paused_tx, paused_rx) = mpsc::channel();
+    let waiter = {
+      let demand = Arc::clone(&demand);
+      thread::spawn(move || {
+        demand.wait().unwrap();
+        demand.pause();
+        paused_tx.send(()).unwrap();
+        demand.wait()
+      })
+    };
+    thread::sleep(Duration::from_millis(20));
+    assert!(paused_rx.try_recv().is_err());
+
+    // 要求で 1 回目の wait が終わり、pause した後の 2 回目の wait は閉じられるまで待つ
+    demand.request();
+    paused_rx.recv().unwrap();
+    thread::sleep(Duration::from_millis(20));
+    assert!(!waiter.is_finished());
+    demand.close();
+    let err = waiter.join().unwrap().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+
+    // 閉じた後は待たない
+    assert!(demand.wait().is_err());
+  }
+}
@@ -0,0 +1,625 @@
+// クリップボードのテキストの文字コードの判定とデコード
+//
+// X11 のテキストのターゲットは文字コードがそれぞれ異なる:
+//   UTF8_STRING / text/plain;charset=utf-8  UTF-8
+//   STRING                                  ISO-8859-1（Latin-1）
+//   COMPOUND_TEXT                           ISO 2022 の部分集合（ICCCM の Compound Text）
+//   TEXT                                    所有者が上のいずれかの型で返す
+// COMPOUND_TEXT の多バイト文字集合（JIS X 0208 など）は EUC の並びに直し、
+// 呼び出し側が渡す変換関数（Linux では iconv）で文字列にする。
+// 書き込むテキストの改行コードの変換もここで行う。
+// Win32 API や Xlib を使わないため、どのプラットフォームでもテストできる。
+
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+/// 読み取ったテキストと、その形式・文字コード
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodedText {
+  /// テキスト
+  pub text: String,
+  /// 読み取った形式（X11 のターゲット、Windows のクリップボード形式、macOS のペーストボードタイプ）
+  pub source_format: String,
+  /// デコードに使った文字コード（`"UTF-8"`, `"ISO-8859-1"`, `"COMPOUND_TEXT"`, `"UTF-16LE"`）
+  pub charset: String,
+  /// 変換できないバイト列を U+FFFD に置き換えたかどうか
+  pub was_lossy: bool,
+}
+
+/// X11 のテキストのターゲット（優先する順）
+pub const X11_TEXT_TARGETS: [&str; 5] = [
+  "UTF8_STRING",
+  "text/plain;charset=utf-8",
+  "STRING",
+  "COMPOUND_TEXT",
+  "TEXT",
+];
+
+/// 文字コード名とバイト列を受け取り、文字列に変換する関数（変換できない場合は None）
+pub type Converter<'a> = &'a dyn Fn(&str, &[u8]) -> Option<String>;
+
+// X11 のテキストの文字コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum X11Encoding {
+  Utf8,
+  Latin1,
+  CompoundText,
+}
+
+impl X11Encoding {
+  // ターゲット名または型名から文字コードを求める
+  fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "UTF8_STRING" => Some(X11Encoding::Utf8),
+      "STRING" => Some(X11Encoding::Latin1),
+      "COMPOUND_TEXT" => Some(X11Encoding::CompoundText),
+      _ if name.eq_ignore_ascii_case("text/plain;charset=utf-8") => Some(X11Encoding::Utf8),
+      _ => None,
+    }
+  }
+
+  // 型が分からない場合（ヘルパーで TEXT を読んだ場合など）にデータから推測する
+  fn guess(data: &[u8]) -> Self {
+    if data.iter().any(|&b| b == ESC || b == CSI) {
+      X11Encoding::CompoundText
+    } else if std::str::from_utf8(data).is_ok() {
+      X11Encoding::Utf8
+    } else {
+      X11Encoding::Latin1
+    }
+  }
+
+  fn charset(self) -> &'static str {
+    match self {
+      X11Encoding::Utf8 => "UTF-8",
+      X11Encoding::Latin1 => "ISO-8859-1",
+      X11Encoding::CompoundText => "COMPOUND_TEXT",
+    }
+  }
+}
+
+/// X11 のセレクションから読み取ったテキストをデコードする
+///
+/// `source_format` は要求したターゲット、`type_name` は所有者が返した型（分からない場合は
+/// `source_format` と同じ）。型、ターゲットの順に文字コードを決め、どちらからも
+/// 決まらない場合（TEXT など）はデータから推測する。
+pub fn decode_x11_text(
+  source_format: &str,
+  type_name: &str,
+  data: &[u8],
+  convert: Converter,
+) -> DecodedText {
+  let encoding = X11Encoding::from_name(type_name)
+    .or_else(|| X11Encoding::from_name(source_format))
+    .unwrap_or_else(|| X11Encoding::guess(data));
+
+  let (text, was_lossy) = match encoding {
+    X11Encoding::Utf8 => decode_utf8(data),
+    X11Encoding::Latin1 => (decode_latin1(data), false),
+    X11Encoding::CompoundText => decode_compound_text(data, convert),
+  };
+  DecodedText {
+    text,
+    source_format: source_format.to_string(),
+    charset: encoding.charset().to_string(),
+    was_lossy,
+  }
+}
+
+/// UTF-8 としてデコードする（不正なバイト列は U+FFFD になる）
+pub fn decode_utf8(data: &[u8]) -> (String, bool) {
+  match String::from_utf8(data.to_vec()) {
+    Ok(text) => (text, false),
+    Err(_) => (String::from_utf8_lossy(data).into_owned(), true),
+  }
+}
+
+/// ISO-8859-1 としてデコードする（すべてのバイトが同じ番号の文字になる）
+pub fn decode_latin1(data: &[u8]) -> String {
+  data.iter().map(|&b| b as char).collect()
+}
+
+/// UTF-16 としてデコードする（対になっていないサロゲートは U+FFFD になる）
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn decode_utf16(units: &[u16]) -> (String, bool) {
+  match String::from_utf16(units) {
+    Ok(text) => (text, false),
+    Err(_) => (String::from_utf16_lossy(units), true),
+  }
+}
+
+const ESC: u8 = 0x1b;
+const CSI: u8 = 0x9b;
+const STX: u8 = 0x02;
+
+// COMPOUND_TEXT の文字集合
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Charset {
+  // ASCII（GL の初期状態）
+  Ascii,
+  // JIS X 0201 のローマ字（ASCII の 0x5C が円記号、0x7E がオーバーライン）
+  JisRoman,
+  // JIS X 0201 のカタカナ（GR のバイトで格納する）
+  Katakana,
+  // ISO-8859-1 の右半分（GR の初期状態）
+  Latin1,
+  // 変換関数で文字列にする文字集合（iconv の文字コード名と、1 文字のバイト数）
+  Convert(&'static str, usize),
+  // 拡張セグメントの UTF-8
+  Utf8,
+  // 対応していない文字集合
+  Unknown,
+}
+
+impl Charset {
+  // 94 文字集合（ESC ( F / ESC ) F）
+  fn set94(final_byte: u8) -> Self {
+    match final_byte {
+      b'B' => Charset::Ascii,
+      b'J' => Charset::JisRoman,
+      b'I' => Charset::Katakana,
+      _ => Charset::Unknown,
+    }
+  }
+
+  // 96 文字集合（ESC - F）。ISO-8859 の各部の右半分
+  fn set96(final_byte: u8) -> Self {
+    let name = match final_byte {
+      b'A' => return Charset::Latin1,
+      b'B' => "ISO-8859-2",
+      b'C' => "ISO-8859-3",
+      b'D' => "ISO-8859-4",
+      b'L' => "ISO-8859-5",
+      b'G' => "ISO-8859-6",
+      b'F' => "ISO-8859-7",
+      b'H' => "ISO-8859-8",
+      b'M' => "ISO-8859-9",
+      b'b' => "ISO-8859-15",
+      _ => return Charset::Unknown,
+    };
+    Charset::Convert(name, 1)
+  }
+
+  // 94^2 文字集合（ESC $ ( F / ESC $ ) F）。EUC の並び（GR のバイト）で格納する
+  fn set94x94(final_byte: u8) -> Self {
+    match final_byte {
+      b'A' => Charset::Convert("EUC-CN", 2),
+      b'B' => Charset::Convert("EUC-JP", 2),
+      b'C' => Charset::Convert("EUC-KR", 2),
+      // JIS X 0212 は EUC-JP では SS3（0x8F）に続く 2 バイトになる
+      b'D' => Charset::Convert("EUC-JP", 3),
+      _ => Charset::Unknown,
+    }
+  }
+
+  // 拡張セグメント（ESC % / n M L 名前 STX データ）の文字集合名
+  fn extended(name: &[u8]) -> Self {
+    let name = String::from_utf8_lossy(name).to_ascii_lowercase();
+    match name.as_str() {
+      "utf-8" => Charset::Utf8,
+      "iso10646-1" => Charset::Convert("UCS-2BE", 2),
+      "big5-0" => Charset::Convert("BIG5", 1),
+      "gbk-0" => Charset::Convert("GBK", 1),
+      "gb18030-0" => Charset::Convert("GB18030", 1),
+      _ => Charset::Unknown,
+    }
+  }
+
+  // 1 文字のバイト数（GL/GR に指示したときの読み取りの単位）
+  fn width(self) -> usize {
+    match self {
+      Charset::Convert("EUC-CN" | "EUC-JP" | "EUC-KR", _) => 2,
+      _ => 1,
+    }
+  }
+}
+
+// 文字集合ごとにまとめたバイト列
+struct Runs(Vec<(Charset, Vec<u8>)>);
+
+impl Runs {
+  fn push(&mut self, charset: Charset, bytes: &[u8]) {
+    match self.0.last_mut() {
+      Some((last, run)) if *last == charset => run.extend_from_slice(bytes),
+      _ => self.0.push((charset, bytes.to_vec())),
+    }
+  }
+}
+
+/// COMPOUND_TEXT をデコードする
+///
+/// GL/GR への文字集合の指示（ESC ( ) - $）、UTF-8 の拡張セグメント（ESC % G）、
+/// 名前付きの拡張セグメント（ESC % / n）を解釈し、方向の指定（CSI ... ]）は読み飛ばす。
+/// 変換できない部分は U+FFFD になり、2 番目の値が true になる。
+pub fn decode_compound_text(data: &[u8], convert: Converter) -> (String, bool) {
+  let mut runs = Runs(Vec::new());
+  let mut gl = Charset::Ascii;
+  let mut gr = Charset::Latin1;
+  let mut lossy = false;
+  let mut i = 0;
+
+  while i < data.len() {
+    let b = data[i];
+    match b {
+      ESC => {
+        let rest = &data[i + 1..];
+        match rest {
+          [b'(', f, ..] => {
+            gl = Charset::set94(*f);
+            i += 3;
+          }
+          [b')', f, ..] => {
+            gr = Charset::set94(*f);
+            i += 3;
+          }
+          [b'-', f, ..] => {
+            gr = Charset::set96(*f);
+            i += 3;
+          }
+          [b'$', b'(', f, ..] => {
+            gl = Charset::set94x94(*f);
+            i += 4;
+          }
+          [b'$', b')', f, ..] => {
+            gr = Charset::set94x94(*f);
+            i += 4;
+          }
+          // 古い形式の GL への指示（ESC $ A / B / C）
+          [b'$', f @ (b'A' | b'B' | b'C'), ..] => {
+            gl = Charset::set94x94(*f);
+            i += 3;
+          }
+          [b'%', b'G', ..] => {
+            // ESC % @ までが UTF-8
+            let start = i + 3;
+            let end = find(&data[start..], &[ESC, b'%', b'@']).map_or(data.len(), |p| start + p);
+            runs.push(Charset::Utf8, &data[start..end]);
+            i = (end + 3).min(data.len());
+          }
+          [b'%', b'/', b'0'..=b'4', m, l, ..] if *m >= 0x80 && *l >= 0x80 => {
+            // 長さは M, L の下位 7 ビットで表され、名前と STX を含む
+            let len = (*m as usize - 0x80) * 0x80 + (*l as usize - 0x80);
+            let start = i + 6;
+            let end = (start + len).min(data.len());
+            let segment = &data[start..end];
+            match segment.iter().position(|&c| c == STX) {
+              Some(p) => runs.push(Charset::extended(&segment[..p]), &segment[p + 1..]),
+              None => runs.push(Charset::Unknown, segment),
+            }
+            i = end;
+          }
+          _ => {
+            // 解釈できないエスケープシーケンスは中間バイトと終端バイトまで読み飛ばす
+            lossy = true;
+            i += 1;
+            while i < data.len() && (0x20..=0x2f).contains(&data[i]) {
+              i += 1;
+            }
+            i += 1;
+          }
+        }
+      }
+      CSI => {
+        // 方向の指定（CSI 1 ] など）はテキストに含めない
+        i += 1;
+        while i < data.len() && !(0x40..=0x7e).contains(&data[i]) {
+          i += 1;
+        }
+        i += 1;
+      }
+      // 制御文字と空白はどの文字集合でも同じ
+      0x00..=0x20 | 0x7f => {
+        runs.push(Charset::Ascii, &[b]);
+        i += 1;
+      }
+      0x21..=0x7e => i += push_chars(&mut runs, gl, &data[i..], false),
+      0xa0..=0xff => i += push_chars(&mut runs, gr, &data[i..], true),
+      // CSI 以外の C1 制御文字は COMPOUND_TEXT では使われない
+      _ => {
+        runs.push(Charset::Unknown, &[b]);
+        i += 1;
+      }
+    }
+  }
+
+  let mut text = String::new();
+  for (charset, bytes) in runs.0 {
+    lossy |= decode_run(charset, &bytes, convert, &mut text);
+  }
+  (text, lossy)
+}
+
+// GL または GR の 1 文字を文字集合のバイト列として追加し、読み進めたバイト数を返す
+fn push_chars(runs: &mut Runs, charset: Charset, data: &[u8], is_gr: bool) -> usize {
+  let width = charset.width();
+  let Some(bytes) = data.get(..width) else {
+    runs.push(Charset::Unknown, data);
+    return data.len();
+  };
+  // GL と GR で同じ文字集合を指示できるため、格納する形式にそろえる
+  let stored: Vec<u8> = match charset {
+    Charset::Ascii | Charset::JisRoman => bytes.iter().map(|b| b & 0x7f).collect(),
+    Charset::Convert(_, 3) => std::iter::once(0x8f)
+      .chain(bytes.iter().map(|b| b | 0x80))
+      .collect(),
+    Charset::Convert(..) | Charset::Katakana => bytes.iter().map(|b| b | 0x80).collect(),
+    // 96 文字集合の右半分は GR でのみ使われる
+    _ if is_gr => bytes.to_vec(),
+    _ => {
+      runs.push(Charset::Unknown, bytes);
+      return width;
+    }
+  };
+  runs.push(charset, &stored);
+  width
+}
+
+// 文字集合ごとのバイト列を文字列にして追加し、変換できない部分があったかどうかを返す
+fn decode_run(charset: Charset, bytes: &[u8], convert: Converter, text: &mut String) -> bool {
+  match charset {
+    Charset::Ascii | Charset::Latin1 => text.extend(bytes.iter().map(|&b| b as char)),
+    Charset::JisRoman => text.extend(bytes.iter().map(|&b| match b {
+      0x5c => '\u{a5}',
+      0x7e => '\u{203e}',
+      _ => b as char,
+    })),
+    Charset::Katakana => {
+      let mut lossy = false;
+      text.extend(bytes.iter().map(|&b| match b {
+        // 0xA1..0xDF は半角カタカナ（U+FF61..U+FF9F）
+        0xa1..=0xdf => char::from_u32(0xff61 + (b - 0xa1) as u32).unwrap_or('\u{fffd}'),
+        _ => {
+          lossy = true;
+          '\u{fffd}'
+        }
+      }));
+      return lossy;
+    }
+    Charset::Utf8 => {
+      let (decoded, lossy) = decode_utf8(bytes);
+      text.push_str(&decoded);
+      return lossy;
+    }
+    Charset::Convert(name, _) => match convert(name, bytes) {
+      Some(decoded) => text.push_str(&decoded),
+      None => {
+        text.push('\u{fffd}');
+        return true;
+      }
+    },
+    Charset::Unknown => {
+      text.push('\u{fffd}');
+      return true;
+    }
+  }
+  false
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+  haystack.windows(needle.len()).position(|w| w == needle)
+}
+
+/// 書き込むテキストの改行コード
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEndings {
+  /// `\n`
+  Lf,
+  /// `\r\n`
+  CrLf,
+}
+
+impl LineEndings {
+  /// 実行中の OS の改行コード（Windows では CRLF、それ以外では LF）
+  pub fn native() -> Self {
+    if cfg!(target_os = "windows") {
+      LineEndings::CrLf
+    } else {
+      LineEndings::Lf
+    }
+  }
+
+  /// JavaScript 側から渡される名前（`"lf"`, `"crlf"`, `"native"`）から変換する
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "lf" => Some(LineEndings::Lf),
+      "crlf" => Some(LineEndings::CrLf),
+      "native" => Some(LineEndings::native()),
+      _ => None,
+    }
+  }
+
+  /// テキストの改行（`\r\n`, `\r`, `\n` が混在していてもよい）をこの改行コードにそろえる
+  pub fn apply(self, text: &str) -> String {
+    let newline = match self {
+      LineEndings::Lf => "\n",
+      LineEndings::CrLf => "\r\n",
+    };
+    let mut converted = String::with_capacity(text.len() + text.len() / 16);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+      match c {
+        '\r' => {
+          chars.next_if_eq(&'\n');
+          converted.push_str(newline);
+        }
+        '\n' => converted.push_str(newline),
+        _ => converted.push(c),
+      }
+    }
+    converted
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // テスト用の変換関数（EUC-JP の「日本」「語」と ISO-8859-7 の α だけを知っている）
+  fn convert(charset: &str, bytes: &[u8]) -> Option<String> {
+    match (charset, bytes) {
+      ("EUC-JP", b"\xc6\xfc\xcb\xdc") => Some("日本".to_string()),
+      ("EUC-JP", b"\xb8\xec") => Some("語".to_string()),
+      ("ISO-8859-7", b"\xe1") => Some("α".to_string()),
+      _ => None,
+    }
+  }
+
+  // (ターゲット, 返された型, データ, テキスト, 文字コード, 置き換えたか)
+  type Case<'a> = (&'a str, &'a str, &'a [u8], &'a str, &'a str, bool);
+
+  fn decode(target: &str, type_name: &str, data: &[u8]) -> (String, String, bool) {
+    let decoded = decode_x11_text(target, type_name, data, &convert);
+    assert_eq!(decoded.source_format, target);
+    (decoded.text, decoded.charset, decoded.was_lossy)
+  }
+
+  // ターゲットと返された型から文字コードを決める
+  #[test]
+  fn test_decode_x11_text_by_target() {
+    let cases: &[Case] = &[
+      (
+        "UTF8_STRING",
+        "UTF8_STRING",
+        "日本語".as_bytes(),
+        "日本語",
+        "UTF-8",
+        false,
+      ),
+      (
+        "UTF8_STRING",
+        "UTF8_STRING",
+        b"caf\xe9",
+        "caf\u{fffd}",
+        "UTF-8",
+        true,
+      ),
+      (
+        "text/plain;charset=utf-8",
+        "text/plain;charset=utf-8",
+        b"abc",
+        "abc",
+        "UTF-8",
+        false,
+      ),
+      ("STRING", "STRING", b"caf\xe9", "café", "ISO-8859-1", false),
+      (
+        "COMPOUND_TEXT",
+        "COMPOUND_TEXT",
+        b"\x1b$)B\xc6\xfc\xcb\xdc",
+        "日本",
+        "COMPOUND_TEXT",
+        false,
+      ),
+      // TEXT は所有者が返した型で判断する
+      ("TEXT", "STRING", b"caf\xe9", "café", "ISO-8859-1", false),
+      ("TEXT", "UTF8_STRING", "é".as_bytes(), "é", "UTF-8", false),
+      // 型が分からない場合はデータから推測する
+      ("TEXT", "TEXT", "日本".as_bytes(), "日本", "UTF-8", false),
+      ("TEXT", "TEXT", b"caf\xe9", "café", "ISO-8859-1", false),
+      (
+        "TEXT",
+        "TEXT",
+        b"\x1b$)B\xb8\xec",
+        "語",
+        "COMPOUND_TEXT",
+        false,
+      ),
+    ];
+    for (target, type_name, data, text, charset, lossy) in cases {
+      assert_eq!(
+        decode(target, type_name, data),
+        (text.to_string(), charset.to_string(), *lossy),
+        "{} / {}",
+        target,
+        type_name
+      );
+    }
+  }
+
+  // COMPOUND_TEXT の文字集合の指示と拡張セグメント
+  #[test]
+  fn test_decode_compound_text() {
+    let cases: &[(&[u8], &str, bool)] = &[
+      // 初期状態は GL が ASCII、GR が Latin-1
+      (b"abc \xe9\n", "abc é\n", false),
+      // JIS X 0208 を GR に指示し、ASCII と混在させる
+      (b"x\x1b$)B\xc6\xfc\xcb\xdcy", "x日本y", false),
+      // JIS X 0208 を GL に指示（古い形式も含む）
+      (b"\x1b$(BF|K\\\x1b(Bz", "日本z", false),
+      (b"\x1b$B8l\x1b(B", "語", false),
+      // JIS X 0201 のローマ字とカタカナ
+      (b"\x1b(J\\~\x1b(B\\", "¥‾\\", false),
+      (b"\x1b)I\xb1\xb2", "ｱｲ", false),
+      // ISO-8859-7 の右半分
+      (b"\x1b-F\xe1", "α", false),
+      // UTF-8 の拡張セグメント
+      (b"a\x1b%G\xe6\x97\xa5\x1b%@b", "a日b", false),
+      // 名前付きの拡張セグメント（長さ 8 = "utf-8" + STX + 2 バイト）
+      (b"\x1b%/1\x80\x88utf-8\x02\xc3\xa9!", "é!", false),
+      // 方向の指定は読み飛ばす
+      (b"\x9b2]ab\x9b]", "ab", false),
+      // 変換できない文字集合は U+FFFD になる
+      (b"\x1b$)C\xb0\xa1", "\u{fffd}", true),
+      (b"\x1b$)B\xc6", "\u{fffd}", true),
+      (b"\x1b(Zab", "\u{fffd}", true),
+    ];
+    for (data, text, lossy) in cases {
+      assert_eq!(
+        decode_compound_text(data, &convert),
+        (text.to_string(), *lossy),
+        "{:?}",
+        data
+      );
+    }
+  }
+
+  // 任意のバイト列でパニックしない
+  #[test]
+  fn test_decode_compound_text_arbitrary_bytes() {
+    let mut state: u32 = 0x1234_5678;
+    for len in 0..2000 {
+      let data: Vec<u8> = (0..len % 40)
+        .map(|_| {
+          state ^= state << 13;
+          state ^= state >> 17;
+          state ^= state << 5;
+          // エスケープシーケンスが現れやすいようにする
+          [ESC, CSI, b'$', b'(', b')', b'%', b'/', b'B', 0x80, 0xc6][state as usize % 10]
+            .wrapping_add((state >> 8) as u8 % 2)
+        })
+        .collect();
+      decode_compound_text(&data, &convert);
+    }
+  }
+
+  // UTF-16 の対になっていないサロゲート
+  #[test]
+  fn test_decode_utf16() {
+    assert_eq!(
+      decode_utf16(&[0x65e5, 0xd83d, 0xde00]),
+      ("日😀".to_string(), false)
+    );
+    assert_eq!(
+      decode_utf16(&[0x61, 0xd800]),
+      ("a\u{fffd}".to_string(), true)
+    );
+  }
+
+  // 混在した改行をそろえる
+  #[test]
+  fn test_line_endings() {
+    let text = "a\r\nb\nc\rd\r\n\r\n";
+    assert_eq!(LineEndings::Lf.apply(text), "a\nb\nc\nd\n\n");
+    assert_eq!(LineEndings::CrLf.apply(text), "a\r\nb\r\nc\r\nd\r\n\r\n");
+    assert_eq!(LineEndings::CrLf.apply("日本語"), "日本語");
+    // 変換済みのテキストを変換し直しても変わらない
+    let crlf = LineEndings::CrLf.apply(text);
+    assert_eq!(LineEndings::CrLf.apply(&crlf), crlf);
+
+    assert_eq!(LineEndings::from_name("crlf"), Some(LineEndings::CrLf));
+    assert_eq!(
+      LineEndings::from_name("native"),
+      Some(LineEndings::native())
+    );
+    assert_eq!(LineEndings::from_name("CRLF"), None);
+  }
+}
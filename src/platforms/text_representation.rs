@@ -0,0 +1,413 @@
+// ファイルパスと一緒に書き込むテキストの形式
+//
+// ファイルをテキストとして貼り付けたときの内容（ターミナルならクォートしたパス、
+// チャットなら素のパスなど）を、どのプラットフォームでも同じ規則で作る。
+// 文字列だけを扱うため、どのプラットフォームでもテストできる。
+
+use crate::platforms::path_mapping::{map_path, PathMapping};
+use crate::platforms::relative_path::{is_windows_path, relative_path};
+use crate::platforms::windows_path;
+
+/// ファイルパスと一緒に書き込むテキストの形式
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TextRepresentation {
+  /// テキストを書き込まない
+  None,
+  /// パスを改行区切りで並べる
+  Paths,
+  /// file URI を改行区切りで並べる
+  Uris,
+  /// シェルでクォートしたパスを空白区切りで並べる（Windows ではダブルクォート）
+  ShellQuoted,
+  /// パスごとにテンプレートを展開し、改行区切りで並べる
+  ///
+  /// `{path}`, `{uri}`, `{name}`（ファイル名）, `{quoted}`（シェルでクォートしたパス）を置き換える。
+  Custom(String),
+}
+
+/// ファイルパスと一緒に書き込むテキストの設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathText {
+  /// テキストの形式
+  pub representation: TextRepresentation,
+  /// パスをこのディレクトリ（正規化済みの絶対パス）からの相対パスで表す
+  ///
+  /// URI は常に絶対パスのまま。相対パスで表せないパス（別のドライブなど）は絶対パスになる。
+  pub relative_to: Option<String>,
+  /// 絶対パスと URI を変換する対応表（WSL のパスを Windows のパスにするなど）
+  pub mappings: Vec<PathMapping>,
+}
+
+impl PathText {
+  /// 正規化済みの絶対パスの一覧からテキストを作る（`TextRepresentation::None` の場合は None）
+  pub fn render(&self, paths: &[String]) -> Option<String> {
+    let entries: Vec<Entry> = paths.iter().map(|path| self.entry(path)).collect();
+    self.representation.render(&entries)
+  }
+
+  // パスをテキストに使う形にする
+  fn entry(&self, path: &str) -> Entry {
+    let mapped = map_path(path, &self.mappings);
+    let relative = self
+      .relative_to
+      .as_ref()
+      .and_then(|base| relative_path(path, base));
+    Entry {
+      shown: relative
+        .or_else(|| mapped.clone())
+        .unwrap_or_else(|| path.to_string()),
+      uri: location_uri(mapped.as_deref().unwrap_or(path)),
+      name: file_name(path).to_string(),
+    }
+  }
+}
+
+impl From<TextRepresentation> for PathText {
+  fn from(representation: TextRepresentation) -> Self {
+    Self {
+      representation,
+      relative_to: None,
+      mappings: Vec::new(),
+    }
+  }
+}
+
+// テキストに使う 1 つのパスの表し方
+struct Entry {
+  // 表示するパス（相対パスや変換したパス）
+  shown: String,
+  // URI（変換したパスから作る）
+  uri: String,
+  // ファイル名
+  name: String,
+}
+
+impl TextRepresentation {
+  /// JavaScript 側から渡される名前（`"none"`, `"paths"`, `"uris"`, `"shell-quoted"`）から変換する
+  ///
+  /// `"custom"` はテンプレートが必要なため、ここでは扱わない。
+  pub fn from_name(name: &str) -> Option<Self> {
+    match name {
+      "none" => Some(TextRepresentation::None),
+      "paths" => Some(TextRepresentation::Paths),
+      "uris" => Some(TextRepresentation::Uris),
+      "shell-quoted" => Some(TextRepresentation::ShellQuoted),
+      _ => None,
+    }
+  }
+
+  // 各パスの表し方からテキストを作る
+  fn render(&self, entries: &[Entry]) -> Option<String> {
+    let text = match self {
+      TextRepresentation::None => return None,
+      TextRepresentation::Paths => entries
+        .iter()
+        .map(|entry| entry.shown.as_str())
+        .collect::<Vec<_>>()
+        .join("\n"),
+      TextRepresentation::Uris => entries
+        .iter()
+        .map(|entry| entry.uri.as_str())
+        .collect::<Vec<_>>()
+        .join("\n"),
+      TextRepresentation::ShellQuoted => entries
+        .iter()
+        .map(|entry| shell_quote(&entry.shown))
+        .collect::<Vec<_>>()
+        .join(" "),
+      TextRepresentation::Custom(template) => entries
+        .iter()
+        .map(|entry| expand_template(template, entry))
+        .collect::<Vec<_>>()
+        .join("\n"),
+    };
+    Some(text)
+  }
+}
+
+/// 絶対パス、または対応表で変換した先（URI の場合もある）を URI にする
+pub fn location_uri(location: &str) -> String {
+  if location.contains("://") {
+    location.to_string()
+  } else {
+    file_uri(location)
+  }
+}
+
+/// 絶対パスを file URI にする
+///
+/// Windows のドライブや UNC のパスは `file:///C:/...` / `file://server/share/...`、
+/// それ以外は POSIX のパスとして `file:///...` にする。
+pub fn file_uri(path: &str) -> String {
+  windows_path::to_file_uri(path).unwrap_or_else(|| {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+      let unreserved = byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte);
+      if unreserved {
+        uri.push(byte as char);
+      } else {
+        uri.push_str(&format!("%{:02X}", byte));
+      }
+    }
+    uri
+  })
+}
+
+/// シェルに合わせてパスをクォートする
+///
+/// Windows のパス（変換した `C:\...` など）と Windows で実行している場合は
+/// cmd.exe / PowerShell 向け、それ以外は POSIX シェル向けにする。
+pub fn shell_quote(path: &str) -> String {
+  if cfg!(target_os = "windows") || is_windows_path(path) {
+    windows_quote(path)
+  } else {
+    posix_quote(path)
+  }
+}
+
+// POSIX シェル: 特殊な文字を含む場合はシングルクォートで囲み、`'` は `'\''` にする
+fn posix_quote(path: &str) -> String {
+  let safe = !path.is_empty()
+    && path
+      .bytes()
+      .all(|b| b.is_ascii_alphanumeric() || b"_@%+=:,./-".contains(&b));
+  if safe {
+    path.to_string()
+  } else {
+    format!("'{}'", path.replace('\'', r"'\''"))
+  }
+}
+
+// cmd.exe / PowerShell: 空白や特殊な文字を含む場合はダブルクォートで囲む
+// （Windows のパスには `"` を含められない）
+fn windows_quote(path: &str) -> String {
+  let needs_quotes = path.is_empty()
+    || path
+      .chars()
+      .any(|c| c.is_whitespace() || "&|<>^()%!,;=`'$@{}[]#".contains(c));
+  if needs_quotes {
+    format!("\"{}\"", path)
+  } else {
+    path.to_string()
+  }
+}
+
+// テンプレートの `{...}` を置き換える（置き換えた値の中の `{...}` は展開しない）
+fn expand_template(template: &str, entry: &Entry) -> String {
+  let mut expanded = String::with_capacity(template.len() + entry.shown.len());
+  let mut rest = template;
+  while let Some(start) = rest.find('{') {
+    expanded.push_str(&rest[..start]);
+    let after = &rest[start..];
+    let value = after.find('}').and_then(|end| {
+      let value = match &after[1..end] {
+        "path" => entry.shown.clone(),
+        "uri" => entry.uri.clone(),
+        "name" => entry.name.clone(),
+        "quoted" => shell_quote(&entry.shown),
+        _ => return None,
+      };
+      Some((value, end))
+    });
+    match value {
+      Some((value, end)) => {
+        expanded.push_str(&value);
+        rest = &after[end + 1..];
+      }
+      // 未知の名前はそのまま残す
+      None => {
+        expanded.push('{');
+        rest = &after[1..];
+      }
+    }
+  }
+  expanded.push_str(rest);
+  expanded
+}
+
+// パスの最後の要素（`/` と `\` のどちらの区切りでもよい）
+fn file_name(path: &str) -> &str {
+  let trimmed = path.trim_end_matches(['/', '\\']);
+  trimmed
+    .rsplit(['/', '\\'])
+    .next()
+    .filter(|name| !name.is_empty())
+    .unwrap_or(path)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::platforms::path_mapping::wsl_mappings;
+
+  fn paths(items: &[&str]) -> Vec<String> {
+    items.iter().map(|s| s.to_string()).collect()
+  }
+
+  fn render(representation: TextRepresentation, paths: &[String]) -> Option<String> {
+    PathText::from(representation).render(paths)
+  }
+
+  // 各形式のテキスト
+  #[test]
+  fn test_render() {
+    let list = paths(&["/tmp/a b.txt", "/tmp/it's"]);
+    assert_eq!(render(TextRepresentation::None, &list), None);
+    assert_eq!(
+      render(TextRepresentation::Paths, &list).unwrap(),
+      "/tmp/a b.txt\n/tmp/it's"
+    );
+    assert_eq!(
+      render(TextRepresentation::Uris, &list).unwrap(),
+      "file:///tmp/a%20b.txt\nfile:///tmp/it's"
+    );
+    assert_eq!(
+      render(
+        TextRepresentation::Custom("- [{name}]({uri})".to_string()),
+        &list
+      )
+      .unwrap(),
+      "- [a b.txt](file:///tmp/a%20b.txt)\n- [it's](file:///tmp/it's)"
+    );
+    assert_eq!(
+      TextRepresentation::from_name("shell-quoted"),
+      Some(TextRepresentation::ShellQuoted)
+    );
+    assert_eq!(TextRepresentation::from_name("custom"), None);
+  }
+
+  // 基準のディレクトリからの相対パスで表し、URI は絶対パスのまま
+  #[test]
+  fn test_render_relative() {
+    let list = paths(&["/work/project/src/a b.rs", "/work/other/c.rs"]);
+    let text = |representation| {
+      PathText {
+        representation,
+        relative_to: Some("/work/project".to_string()),
+        mappings: Vec::new(),
+      }
+      .render(&list)
+      .unwrap()
+    };
+    assert_eq!(text(TextRepresentation::Paths), "src/a b.rs\n../other/c.rs");
+    assert_eq!(
+      text(TextRepresentation::ShellQuoted),
+      "'src/a b.rs' ../other/c.rs"
+    );
+    assert_eq!(
+      text(TextRepresentation::Uris),
+      "file:///work/project/src/a%20b.rs\nfile:///work/other/c.rs"
+    );
+    assert_eq!(
+      text(TextRepresentation::Custom("{path} {uri}".to_string())),
+      "src/a b.rs file:///work/project/src/a%20b.rs\n../other/c.rs file:///work/other/c.rs"
+    );
+
+    // 相対パスで表せないパスは絶対パスのまま
+    let windows = PathText {
+      representation: TextRepresentation::Paths,
+      relative_to: Some(r"C:\work".to_string()),
+      mappings: Vec::new(),
+    };
+    assert_eq!(
+      windows
+        .render(&paths(&[r"C:\work\a.txt", r"D:\b.txt"]))
+        .unwrap(),
+      "a.txt\nD:\\b.txt"
+    );
+  }
+
+  // 対応表で変換したパスと URI（sftp:// などはそのまま使う）
+  #[test]
+  fn test_render_mapped() {
+    let mut mappings = wsl_mappings("Ubuntu");
+    mappings.insert(
+      0,
+      PathMapping::new("/home/me/mnt/server", "sftp://me@server/srv"),
+    );
+    let text = |representation| {
+      PathText {
+        representation,
+        relative_to: None,
+        mappings: mappings.clone(),
+      }
+      .render(&paths(&[
+        "/mnt/c/Users/me/a b.txt",
+        "/home/me/notes.md",
+        "/home/me/mnt/server/x y",
+      ]))
+      .unwrap()
+    };
+    assert_eq!(
+      text(TextRepresentation::Paths),
+      "C:\\Users\\me\\a b.txt\n\\\\wsl$\\Ubuntu\\home\\me\\notes.md\nsftp://me@server/srv/x%20y"
+    );
+    assert_eq!(
+      text(TextRepresentation::Uris),
+      "file:///C:/Users/me/a%20b.txt\nfile://wsl$/Ubuntu/home/me/notes.md\nsftp://me@server/srv/x%20y"
+    );
+    // 変換した Windows のパスは Windows のシェル向けにクォートする
+    assert_eq!(
+      text(TextRepresentation::Custom("{quoted}".to_string())),
+      "\"C:\\Users\\me\\a b.txt\"\n\"\\\\wsl$\\Ubuntu\\home\\me\\notes.md\"\nsftp://me@server/srv/x%20y"
+    );
+  }
+
+  // file URI（Windows のパスと POSIX のパス）
+  #[test]
+  fn test_file_uri() {
+    let cases = [
+      (
+        "/home/me/データ.txt",
+        "file:///home/me/%E3%83%87%E3%83%BC%E3%82%BF.txt",
+      ),
+      ("/tmp/100%", "file:///tmp/100%25"),
+      ("/tmp/a#b?c", "file:///tmp/a%23b%3Fc"),
+      (r"C:\a b\c.txt", "file:///C:/a%20b/c.txt"),
+      (r"\\server\share\c.txt", "file://server/share/c.txt"),
+    ];
+    for (path, expected) in cases {
+      assert_eq!(file_uri(path), expected, "{}", path);
+    }
+  }
+
+  // シェルのクォート
+  #[test]
+  fn test_shell_quote() {
+    let posix = [
+      ("/usr/bin/env", "/usr/bin/env"),
+      ("/tmp/a b", "'/tmp/a b'"),
+      ("/tmp/it's", r"'/tmp/it'\''s'"),
+      ("/tmp/$HOME", "'/tmp/$HOME'"),
+      ("/tmp/日本", "'/tmp/日本'"),
+    ];
+    for (path, expected) in posix {
+      assert_eq!(posix_quote(path), expected, "{}", path);
+    }
+
+    let windows = [
+      (r"C:\Windows\notepad.exe", r"C:\Windows\notepad.exe"),
+      (r"C:\Program Files\a.txt", r#""C:\Program Files\a.txt""#),
+      (r"C:\a&b", r#""C:\a&b""#),
+    ];
+    for (path, expected) in windows {
+      assert_eq!(windows_quote(path), expected, "{}", path);
+    }
+  }
+
+  // テンプレートの展開
+  #[test]
+  fn test_expand_template() {
+    let expand = |template, path| {
+      expand_template(
+        template,
+        &PathText::from(TextRepresentation::Paths).entry(path),
+      )
+    };
+    assert_eq!(expand("{path}", "/tmp/{uri}"), "/tmp/{uri}");
+    assert_eq!(expand("{unknown} {name", "/tmp/a"), "{unknown} {name");
+    assert_eq!(expand("{name}", r"C:\dir\file.txt"), "file.txt");
+    assert_eq!(expand("{name}", "/tmp/dir/"), "dir");
+    assert_eq!(expand("{{path}}", "/a"), "{/a}");
+  }
+}
@@ -0,0 +1,85 @@
+// クリップボード操作のタイムアウト設定
+//
+// セレクションの所有者が応答しない場合などに呼び出し元のスレッドが
+// 固まらないよう、外部コマンドやネイティブ API の呼び出しはこの時間で打ち切る。
+
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+/// デフォルトのタイムアウト
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+static TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_TIMEOUT.as_millis() as u64);
+
+/// 現在のタイムアウトを取得する
+pub fn timeout() -> Duration {
+  Duration::from_millis(TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+/// タイムアウトを設定する
+pub fn set_timeout(timeout: Duration) {
+  TIMEOUT_MS.store(timeout.as_millis() as u64, Ordering::Relaxed);
+}
+
+/// タイムアウトを表すエラーを作成する
+pub fn timed_out(operation: &str, timeout: Duration) -> Error {
+  Error::new(
+    ErrorKind::TimedOut,
+    format!("{} timed out after {} ms", operation, timeout.as_millis()),
+  )
+}
+
+/// 別スレッドで処理を実行し、タイムアウトまでに終わらなければエラーを返す
+///
+/// 打ち切られた処理のスレッドはバックグラウンドで終了を待つことになるため、
+/// 外部コマンドのように中断できる処理には使わないこと。
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub fn run_with_timeout<T, F>(operation: &str, timeout: Duration, f: F) -> Result<T, Error>
+where
+  T: Send + 'static,
+  F: FnOnce() -> Result<T, Error> + Send + 'static,
+{
+  let (tx, rx) = mpsc::channel();
+  thread::spawn(move || {
+    let _ = tx.send(f());
+  });
+
+  match rx.recv_timeout(timeout) {
+    Ok(result) => result,
+    Err(mpsc::RecvTimeoutError::Timeout) => Err(timed_out(operation, timeout)),
+    Err(mpsc::RecvTimeoutError::Disconnected) => Err(Error::other(format!(
+      "{} terminated unexpectedly",
+      operation
+    ))),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::time::Instant;
+
+  // 時間内に終わる処理は結果がそのまま返る
+  #[test]
+  fn test_run_with_timeout_completes() {
+    let result = run_with_timeout("quick", Duration::from_secs(5), || Ok(42));
+    assert_eq!(result.unwrap(), 42);
+  }
+
+  // 時間内に終わらない処理は TimedOut エラーになる
+  #[test]
+  fn test_run_with_timeout_times_out() {
+    let started = Instant::now();
+    let result: Result<(), Error> = run_with_timeout("slow", Duration::from_millis(100), || {
+      thread::sleep(Duration::from_secs(2));
+      Ok(())
+    });
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+    assert!(err.to_string().contains("slow timed out after 100 ms"));
+    assert!(started.elapsed() < Duration::from_secs(1));
+  }
+}
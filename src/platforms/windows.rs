@@ -1,40 +1,91 @@
 #![cfg(target_os = "windows")]
 
+use crate::platforms::dropfiles::{self, DropFiles};
+use crate::platforms::file_entry::FileEntry;
+use crate::platforms::lazy::Provider;
+use crate::platforms::owner_info::{self, OwnerInfo, SOURCE_MARKER_FORMAT};
+use crate::platforms::ownership::SequenceOwnership;
+use crate::platforms::retry;
+use crate::platforms::stream::{self, Sink};
+use crate::platforms::text_encoding::{self, DecodedText};
+use crate::platforms::text_representation::{PathText, TextRepresentation};
+use crate::platforms::windows_path;
+use crate::platforms::Selection;
+use std::cell::RefCell;
 use std::ffi::c_void;
 use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::io::{Error, ErrorKind};
 use std::iter::once;
-use std::mem::{size_of, zeroed};
+use std::mem::zeroed;
 use std::os::windows::ffi::OsStrExt;
 use std::os::windows::ffi::OsStringExt;
-use std::path::Path;
 use std::ptr;
+use std::sync::{mpsc, Arc, Once};
 
 use windows_sys::Win32::{
-  Foundation::{GetLastError, HWND},
+  Foundation::{CloseHandle, GetLastError, BOOL, HWND, LPARAM, LRESULT, WPARAM},
+  Globalization::{MultiByteToWideChar, CP_ACP},
   System::{
     DataExchange::{
-      CloseClipboard, EmptyClipboard, GetClipboardData, IsClipboardFormatAvailable, OpenClipboard,
-      SetClipboardData,
+      CloseClipboard, EmptyClipboard, GetClipboardData, GetClipboardOwner,
+      GetClipboardSequenceNumber, IsClipboardFormatAvailable, OpenClipboard,
+      RegisterClipboardFormatW, SetClipboardData,
     },
     Memory::{GlobalAlloc, GlobalLock, GlobalSize, GlobalUnlock, GMEM_MOVEABLE},
+    Threading::{
+      OpenProcess, QueryFullProcessImageNameW, PROCESS_NAME_WIN32,
+      PROCESS_QUERY_LIMITED_INFORMATION,
+    },
+  },
+  UI::WindowsAndMessaging::{
+    CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+    GetWindowThreadProcessId, PostQuitMessage, RegisterClassW, HWND_MESSAGE, MSG,
+    WM_DESTROYCLIPBOARD, WM_RENDERALLFORMATS, WM_RENDERFORMAT, WNDCLASSW,
   },
-  UI::Shell::DragQueryFileW,
 };
 
 // シェルフォーマットの定数
 const CF_HDROP: u32 = 15;
 const CF_UNICODETEXT: u32 = 13; // Unicode テキスト形式
 
-// Shell.h から DROPFILES 構造体を定義
-#[repr(C)]
-#[allow(non_snake_case)]
-struct DROPFILES {
-  pFiles: u32,
-  pt: windows_sys::Win32::Foundation::POINT,
-  fNC: windows_sys::Win32::Foundation::BOOL,
-  fWide: windows_sys::Win32::Foundation::BOOL,
+// CF_HDROP に渡すパスを作る
+// 存在するパスは正規化（シンボリックリンクの解決）した上で拡張長パスの接頭辞を取り除く
+fn hdrop_path(path: &str, cwd: &str) -> String {
+  match std::fs::canonicalize(path) {
+    Ok(canonical) => windows_path::normalize(&canonical.to_string_lossy()),
+    Err(_) => windows_path::absolutize(path, cwd),
+  }
+}
+
+// ANSI（システムのコードページ）の文字列を変換する
+fn ansi_to_string(bytes: &[u8]) -> String {
+  if bytes.is_empty() {
+    return String::new();
+  }
+  unsafe {
+    let len = MultiByteToWideChar(
+      CP_ACP,
+      0,
+      bytes.as_ptr(),
+      bytes.len() as i32,
+      ptr::null_mut(),
+      0,
+    );
+    if len <= 0 {
+      return String::from_utf8_lossy(bytes).into_owned();
+    }
+    let mut wide = vec![0u16; len as usize];
+    MultiByteToWideChar(
+      CP_ACP,
+      0,
+      bytes.as_ptr(),
+      bytes.len() as i32,
+      wide.as_mut_ptr(),
+      len,
+    );
+    String::from_utf16_lossy(&wide)
+  }
 }
 
 // ワイド文字列（UTF-16）に変換し、NULL終端を追加するヘルパー関数
@@ -48,351 +99,656 @@ extern "system" {
   fn GlobalFree(hMem: *mut c_void) -> *mut c_void;
 }
 
-/// ファイルパスをクリップボードにコピーする
-pub fn write_clipboard_file_paths(paths: &[String]) -> Result<(), Error> {
-  unsafe {
-    // クリップボードを開く (所有者を指定しない場合は NULL)
-    if OpenClipboard(0) == 0 {
-      let err = GetLastError();
+// クリップボードを開いている間だけ存在し、drop で閉じるガード
+// エラーで早期に return してもクリップボードが開いたままにならない
+struct ClipboardGuard {
+  _private: (),
+}
+
+impl ClipboardGuard {
+  // クリップボードを開く（`owner` が 0 の場合は所有者を指定しない）
+  // 他のプロセスが開いている間は失敗するため、再試行のポリシーに従って開き直す
+  fn open(owner: HWND) -> Result<Self, Error> {
+    Self::open_with(owner, |owner| unsafe { OpenClipboard(owner) })
+  }
+
+  // `open` の OpenClipboard を差し替えられるようにしたもの（テストで一時的な失敗を起こす）
+  fn open_with(owner: HWND, mut open: impl FnMut(HWND) -> BOOL) -> Result<Self, Error> {
+    retry::retry("Opening the clipboard", || {
+      if open(owner) == 0 {
+        return Err(retry::busy(format!(
+          "Failed to open clipboard: {}",
+          unsafe { GetLastError() }
+        )));
+      }
+      Ok(Self { _private: () })
+    })
+  }
+
+  // クリップボードを空にして、このプロセス（`open` に渡したウィンドウ）を所有者にする
+  fn empty(&self) -> Result<(), Error> {
+    if unsafe { EmptyClipboard() } == 0 {
       return Err(Error::new(
         ErrorKind::Other,
-        format!("Failed to open clipboard: {}", err),
+        format!("Failed to empty clipboard: {}", unsafe { GetLastError() }),
       ));
     }
+    Ok(())
+  }
 
-    // クリップボードを空にする
-    if EmptyClipboard() == 0 {
-      let err = GetLastError();
-      CloseClipboard();
+  // 指定した形式のデータのハンドルを取得する（メモリはクリップボードが所有する）
+  fn data(&self, format_id: u32) -> Result<isize, Error> {
+    let handle = unsafe { GetClipboardData(format_id) };
+    if handle == 0 {
       return Err(Error::new(
         ErrorKind::Other,
-        format!("Failed to empty clipboard: {}", err),
+        format!("Failed to get clipboard data: {:?}", unsafe {
+          GetLastError()
+        }),
       ));
     }
+    Ok(handle)
+  }
+
+  // クリップボードを閉じ、成功したかどうかを返す
+  fn close(self) -> bool {
+    let closed = unsafe { CloseClipboard() } != 0;
+    std::mem::forget(self);
+    closed
+  }
+}
+
+impl Drop for ClipboardGuard {
+  fn drop(&mut self) {
+    unsafe { CloseClipboard() };
+  }
+}
+
+// GlobalAlloc で確保したメモリ
+// SetClipboardData が成功するまで所有し、それまでに drop された場合は解放する
+struct GlobalMemory {
+  handle: *mut c_void,
+}
 
-    // 空の配列の場合はクリップボードをクリアして終了
-    if paths.is_empty() {
-      CloseClipboard();
-      println!("Cleared clipboard data (empty file list)");
-      return Ok(());
+impl GlobalMemory {
+  // GMEM_MOVEABLE のメモリを確保して `data` を書き込む（CF_HDROP などはこの形式が必要）
+  fn new(data: &[u8]) -> Result<Self, Error> {
+    unsafe {
+      let handle = GlobalAlloc(GMEM_MOVEABLE, data.len().max(1));
+      if handle.is_null() {
+        return Err(Error::new(
+          ErrorKind::Other,
+          format!("Failed to allocate global memory: {}", GetLastError()),
+        ));
+      }
+      // 以降のエラーでは drop でメモリが解放される
+      let memory = Self { handle };
+
+      let buffer_ptr = GlobalLock(handle) as *mut u8;
+      if buffer_ptr.is_null() {
+        return Err(Error::new(
+          ErrorKind::Other,
+          format!("Failed to lock global memory: {}", GetLastError()),
+        ));
+      }
+      ptr::copy_nonoverlapping(data.as_ptr(), buffer_ptr, data.len());
+      GlobalUnlock(handle);
+      Ok(memory)
     }
+  }
 
-    // 1. パスリストをワイド文字列（UTF-16）に変換し、ダブルNULL終端形式にする
-    let mut wide_paths: Vec<u16> = paths
-      .iter()
-      .map(|p| to_wide_null(p)) // 各パスを NULL 終端 UTF-16 に変換
-      .flatten() // 平坦化して連結
-      .collect();
-    wide_paths.push(0); // リストの最後に NULL を追加してダブルNULL終端にする
-
-    // 2. DROPFILES 構造体とパスリストを格納するためのメモリサイズを計算
-    let dropfiles_size = std::mem::size_of::<DROPFILES>();
-    let paths_size = wide_paths.len() * std::mem::size_of::<u16>();
-    let total_size = dropfiles_size + paths_size;
-
-    // 3. グローバルメモリを確保
-    // CF_HDROP は GMEM_MOVEABLE である必要がある
-    let h_global = GlobalAlloc(GMEM_MOVEABLE, total_size);
-    if h_global == ptr::null_mut() {
-      let err = GetLastError();
-      CloseClipboard();
+  // クリップボードにデータとして設定する（クリップボードを開いている間に呼び出す）
+  // 成功するとメモリの所有権は OS に移るため、GlobalFree を呼んではいけない
+  fn set_clipboard_data(self, format_id: u32) -> Result<(), Error> {
+    if unsafe { SetClipboardData(format_id, self.handle as isize) } == 0 {
+      // self の drop でメモリが解放される
       return Err(Error::new(
         ErrorKind::Other,
-        format!("Failed to allocate global memory: {}", err),
+        format!("Failed to set clipboard data: {}", unsafe {
+          GetLastError()
+        }),
       ));
     }
+    std::mem::forget(self);
+    Ok(())
+  }
+}
+
+impl Drop for GlobalMemory {
+  fn drop(&mut self) {
+    unsafe { GlobalFree(self.handle) };
+  }
+}
+
+// クリップボードが所有するメモリをロックし、その内容を `read` に渡す
+// `read` がパニックしてもアンロックされるように、アンロックはガードの drop で行う
+fn read_global<R>(handle: isize, read: impl FnOnce(&[u8]) -> R) -> Result<R, Error> {
+  struct Unlock(*mut c_void);
+  impl Drop for Unlock {
+    fn drop(&mut self) {
+      unsafe { GlobalUnlock(self.0) };
+    }
+  }
 
-    // 4. メモリをロックしてポインタを取得
-    let buffer_ptr = GlobalLock(h_global) as *mut u8;
-    if buffer_ptr.is_null() {
-      let err = GetLastError();
-      // GlobalFree(h_global); // windows-sys 0.52 では GlobalFree が直接使えないので代わりに Windows API を直接呼び出す
-      CloseClipboard();
+  let handle = handle as *mut c_void;
+  unsafe {
+    let ptr = GlobalLock(handle);
+    if ptr.is_null() {
       return Err(Error::new(
         ErrorKind::Other,
-        format!("Failed to lock global memory: {}", err),
+        format!("Failed to lock memory: {:?}", GetLastError()),
       ));
     }
+    let _unlock = Unlock(handle);
+    let size = GlobalSize(handle);
+    Ok(read(std::slice::from_raw_parts(ptr as *const u8, size)))
+  }
+}
 
-    // 5. DROPFILES 構造体をメモリに書き込む
-    let dropfiles_ptr = buffer_ptr as *mut DROPFILES;
-    *dropfiles_ptr = DROPFILES {
-      pFiles: dropfiles_size as u32, // パスリストへのオフセット
-      pt: zeroed(),                  // 使わないのでゼロ初期化
-      fNC: 0,                        // 非クライアント領域座標ではない
-      fWide: 1,                      // ワイド文字（UTF-16）を使用
-    };
+/// ファイルパスをクリップボードにコピーする
+///
+/// `source` を指定した場合は、書き込み元を示す印も登録済みの形式として設定する。
+/// `text` はパスと一緒に設定するテキストの形式で、None の場合は file URI の一覧になる。
+pub fn write_clipboard_file_paths(
+  paths: &[String],
+  source: Option<&str>,
+  text: Option<&PathText>,
+) -> Result<(), Error> {
+  // CF_HDROP には拡張長パスの接頭辞（`\\?\`）のない絶対パスを渡す
+  let cwd = std::env::current_dir()
+    .map(|d| d.to_string_lossy().into_owned())
+    .unwrap_or_default();
+  let paths: Vec<String> = paths.iter().map(|p| hdrop_path(p, &cwd)).collect();
+
+  // クリップボードを開いて空にする（エラーの場合もガードの drop で閉じる）
+  let clipboard = ClipboardGuard::open(0)?;
+  clipboard.empty()?;
+
+  // 空の配列の場合はクリップボードをクリアして終了
+  if paths.is_empty() {
+    println!("Cleared clipboard data (empty file list)");
+    return Ok(());
+  }
 
-    // 6. パスリスト（ダブルNULL終端）を DROPFILES 構造体の直後に書き込む
-    let paths_ptr = buffer_ptr.add(dropfiles_size);
-    ptr::copy_nonoverlapping(wide_paths.as_ptr() as *const u8, paths_ptr, paths_size);
+  // 1. DROPFILES 構造体とパスリスト（ダブルNULL終端）のバイト列を作る
+  let hdrop = dropfiles::encode(&DropFiles::new(paths.clone()))?;
 
-    // 7. メモリをアンロック
-    GlobalUnlock(h_global);
+  // 2. グローバルメモリを確保してバイト列を書き込む
+  let memory = GlobalMemory::new(&hdrop)?;
 
-    // 8. CF_HDROP 形式でデータをクリップボードに設定
-    // SetClipboardData が成功すると、OS がメモリの所有権を持つため、GlobalFree を呼んではいけない
-    if SetClipboardData(CF_HDROP, h_global as isize) == 0 {
-      let err = GetLastError();
-      CloseClipboard();
-      // GlobalFree(h_global); // GlobalFree が直接使えない
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to set clipboard data: {}", err),
-      ));
-    }
+  // 3. CF_HDROP 形式でデータをクリップボードに設定
+  // 失敗した場合、メモリは GlobalMemory の drop で解放される
+  memory.set_clipboard_data(CF_HDROP)?;
 
-    // 9. ファイルパスをURLとしてもクリップボードに設定する (CF_UNICODETEXT形式)
-    // ファイルパスをURLに変換して連結
-    let url_text = paths
+  // 4. ファイルパスのテキストもクリップボードに設定する (CF_UNICODETEXT形式)
+  // 形式の指定がなければ file URI（ドライブのパスは file:///C:/...、UNC パスは file://server/share/...）
+  let default_text = PathText::from(TextRepresentation::Uris);
+  if let Some(text) = text.unwrap_or(&default_text).render(&paths) {
+    // テキストをUTF-16（NULL終端）のバイト列に変換
+    let wide_text: Vec<u8> = to_wide_null(&text)
       .iter()
-      .map(|path| {
-        // 絶対パスに変換
-        let path = Path::new(path);
-        let abs_path = if path.is_absolute() {
-          path.to_path_buf()
-        } else {
-          std::env::current_dir().unwrap_or_default().join(path)
-        };
+      .flat_map(|unit| unit.to_le_bytes())
+      .collect();
 
-        // パスをURLに変換 (file:///C:/path/to/file.txt 形式)
-        let path_str = abs_path.to_string_lossy();
-        let file_url = format!("file:///{}", path_str.replace('\\', "/"));
-        file_url
-      })
-      .collect::<Vec<String>>()
-      .join("\n");
-
-    // URLテキストをUTF-16に変換
-    let wide_url = to_wide_null(&url_text);
-    let url_size = wide_url.len() * std::mem::size_of::<u16>();
-
-    // テキスト用にグローバルメモリを確保
-    let h_text = GlobalAlloc(GMEM_MOVEABLE, url_size);
-    if h_text == ptr::null_mut() {
-      // テキスト設定に失敗してもファイルパスは設定できているので、エラーにはしない
-      eprintln!("Warning: Failed to allocate memory for text format");
-    } else {
-      // メモリをロックしてポインタを取得
-      let text_ptr = GlobalLock(h_text) as *mut u8;
-      if !text_ptr.is_null() {
-        // UTF-16テキストを書き込む
-        ptr::copy_nonoverlapping(wide_url.as_ptr() as *const u8, text_ptr, url_size);
-        GlobalUnlock(h_text);
-
-        // CF_UNICODETEXT形式でデータをクリップボードに設定
-        if SetClipboardData(CF_UNICODETEXT, h_text as isize) == 0 {
-          // テキスト設定に失敗した場合は警告を出すだけ
-          eprintln!(
-            "Warning: Failed to set text clipboard data: {}",
-            GetLastError()
-          );
-        }
-      } else {
-        eprintln!("Warning: Failed to lock memory for text format");
-      }
+    // テキスト設定に失敗してもファイルパスは設定できているので、エラーにはしない
+    if let Err(e) =
+      GlobalMemory::new(&wide_text).and_then(|memory| memory.set_clipboard_data(CF_UNICODETEXT))
+    {
+      eprintln!("Warning: Failed to set text clipboard data: {}", e);
     }
+  }
 
-    // 10. クリップボードを閉じる
-    if CloseClipboard() == 0 {
-      // この時点ではデータは設定されているが、閉じるのに失敗した
-      // エラーとして報告するべきか？ 일단 ここでは警告としておく
-      eprintln!("Warning: Failed to close clipboard: {}", GetLastError());
-    }
+  // 5. 書き込み元を示す印を設定する（UTF-8、NULL 終端）
+  if let Some(source) = source {
+    set_source_marker(source);
+  }
 
-    println!("Copied {} files to clipboard on Windows", paths.len());
-    Ok(())
-  } // unsafe ブロック終了
-}
+  // 6. クリップボードを閉じる
+  if !clipboard.close() {
+    // この時点ではデータは設定されているので、警告だけにする
+    eprintln!("Warning: Failed to close clipboard: {}", unsafe {
+      GetLastError()
+    });
+  }
 
-// クリップボードからテキストを読み取る
-pub fn read_clipboard_text() -> Result<String, Error> {
-  unsafe {
-    // クリップボードを開く
-    if OpenClipboard(0) == 0 {
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to open clipboard: {:?}", GetLastError()),
-      ));
-    }
+  println!("Copied {} files to clipboard on Windows", paths.len());
+  Ok(())
+}
 
-    // CF_UNICODETEXTフォーマットが利用可能か確認
-    if IsClipboardFormatAvailable(13) == 0 {
-      // CF_UNICODETEXT = 13
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        "No text available in clipboard",
-      ));
+/// テキストをクリップボードにコピーする（CF_UNICODETEXT）
+///
+/// `paths` が空でなければ、その file URI の一覧を登録済みの形式 `text/uri-list`
+/// （UTF-8、CRLF 区切り）としても設定する。
+pub fn write_clipboard_text(text: &str, paths: &[String]) -> Result<(), Error> {
+  // テキストをUTF-16（NULL終端）のバイト列に変換
+  let wide_text: Vec<u8> = to_wide_null(text)
+    .iter()
+    .flat_map(|unit| unit.to_le_bytes())
+    .collect();
+
+  // クリップボードを開いて空にする（エラーの場合もガードの drop で閉じる）
+  let clipboard = ClipboardGuard::open(0)?;
+  clipboard.empty()?;
+  GlobalMemory::new(&wide_text)?.set_clipboard_data(CF_UNICODETEXT)?;
+
+  // URI の一覧の設定に失敗してもテキストは設定できているので、エラーにはしない
+  if !paths.is_empty() {
+    let mut uri_list = paths
+      .iter()
+      .map(|path| windows_path::to_file_uri(path).unwrap_or_else(|| path.clone()))
+      .collect::<Vec<String>>()
+      .join("\r\n")
+      .into_bytes();
+    uri_list.push(0);
+    let format_id = clipboard_format_id("text/uri-list");
+    if let Err(e) =
+      GlobalMemory::new(&uri_list).and_then(|memory| memory.set_clipboard_data(format_id))
+    {
+      eprintln!("Warning: Failed to set text/uri-list clipboard data: {}", e);
     }
+  }
 
-    // クリップボードからデータを取得
-    let handle = GetClipboardData(13); // CF_UNICODETEXT = 13
-    if handle == 0 {
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to get clipboard data: {:?}", GetLastError()),
-      ));
-    }
+  if !clipboard.close() {
+    // この時点ではデータは設定されているので、警告だけにする
+    eprintln!("Warning: Failed to close clipboard: {}", unsafe {
+      GetLastError()
+    });
+  }
 
-    // メモリをロック
-    let ptr = GlobalLock(handle as *mut c_void) as *const u16;
-    if ptr.is_null() {
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to lock memory: {:?}", GetLastError()),
-      ));
-    }
+  println!("Copied text to clipboard on Windows");
+  Ok(())
+}
 
-    // ワイド文字列をRust文字列に変換
-    // ヌル終端の文字列の長さを計算
-    let mut len = 0;
-    while *ptr.add(len) != 0 {
-      len += 1;
-    }
+// 書き込み元を示す印を設定する（クリップボードを開いている間に呼び出す）
+// 印の設定に失敗してもファイルパスは設定できているので、エラーにはしない
+fn set_source_marker(source: &str) {
+  let format_id = clipboard_format_id(SOURCE_MARKER_FORMAT);
+  let mut data = source.as_bytes().to_vec();
+  data.push(0);
 
-    let wstr = std::slice::from_raw_parts(ptr, len);
-    let text = String::from_utf16_lossy(wstr);
+  if let Err(e) = GlobalMemory::new(&data).and_then(|memory| memory.set_clipboard_data(format_id)) {
+    eprintln!("Warning: Failed to set source marker: {}", e);
+  }
+}
 
-    // メモリをアンロック
-    GlobalUnlock(handle as *mut c_void);
+// クリップボードからテキストを読み取る
+pub fn read_clipboard_text() -> Result<String, Error> {
+  read_clipboard_text_decoded().map(|decoded| decoded.text)
+}
 
-    // クリップボードを閉じる
-    CloseClipboard();
+// クリップボードのテキストを、形式と文字コードとともに読み取る（CF_UNICODETEXT は UTF-16LE）
+pub fn read_clipboard_text_decoded() -> Result<DecodedText, Error> {
+  let clipboard = ClipboardGuard::open(0)?;
 
-    Ok(text)
+  // CF_UNICODETEXTフォーマットが利用可能か確認
+  if unsafe { IsClipboardFormatAvailable(CF_UNICODETEXT) } == 0 {
+    return Err(Error::new(
+      ErrorKind::Other,
+      "No text available in clipboard",
+    ));
   }
+
+  // ワイド文字列をRust文字列に変換（NULL終端またはメモリの末尾まで）
+  let handle = clipboard.data(CF_UNICODETEXT)?;
+  let (text, was_lossy) = read_global(handle, |data| {
+    let units: Vec<u16> = data
+      .chunks_exact(2)
+      .map(|c| u16::from_le_bytes([c[0], c[1]]))
+      .take_while(|&unit| unit != 0)
+      .collect();
+    text_encoding::decode_utf16(&units)
+  })?;
+
+  Ok(DecodedText {
+    text,
+    source_format: "CF_UNICODETEXT".to_string(),
+    charset: "UTF-16LE".to_string(),
+    was_lossy,
+  })
 }
 
 // クリップボードからRAWデータを読み取る
 pub fn read_clipboard_raw() -> Result<Vec<u8>, Error> {
-  unsafe {
-    // クリップボードを開く
-    if OpenClipboard(0) == 0 {
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to open clipboard: {:?}", GetLastError()),
-      ));
-    }
-
-    // 利用可能なフォーマットを調べる - 最初の利用可能なフォーマットを使用
-    let format_id = 13; // CF_UNICODETEXT = 13
+  let clipboard = ClipboardGuard::open(0)?;
 
-    // クリップボードからデータを取得
-    let handle = GetClipboardData(format_id);
-    if handle == 0 {
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to get clipboard data: {:?}", GetLastError()),
-      ));
-    }
+  // 利用可能なフォーマットを調べる - 最初の利用可能なフォーマットを使用
+  let handle = clipboard.data(CF_UNICODETEXT)?;
 
-    // メモリをロック
-    let ptr = GlobalLock(handle as *mut c_void);
-    if ptr.is_null() {
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to lock memory: {:?}", GetLastError()),
-      ));
-    }
+  // バイトデータをコピー
+  let data = read_global(handle, |data| data.to_vec())?;
 
-    // グローバルメモリのサイズを取得
-    let size = GlobalSize(handle as *mut c_void);
+  if data.is_empty() {
+    Err(Error::new(
+      ErrorKind::Other,
+      "No data available in clipboard",
+    ))
+  } else {
+    Ok(data)
+  }
+}
 
-    // バイトデータをコピー
-    let data = if size > 0 {
-      let slice = std::slice::from_raw_parts(ptr as *const u8, size);
-      slice.to_vec()
-    } else {
-      Vec::new()
-    };
+// クリップボードの形式名から形式 ID を取得する
+// 数値（`"13"` など）はそのまま ID として扱い、それ以外は登録済みの形式名（`"PNG"` など）として扱う
+fn clipboard_format_id(format: &str) -> u32 {
+  format
+    .parse()
+    .unwrap_or_else(|_| unsafe { RegisterClipboardFormatW(to_wide_null(format).as_ptr()) })
+}
 
-    // メモリをアンロック
-    GlobalUnlock(handle as *mut c_void);
+// 指定した形式のデータを分割して読み取る
+pub fn read_clipboard_format(format: &str, sink: &mut Sink) -> Result<u64, Error> {
+  let format_id = clipboard_format_id(format);
+  if format_id == 0 {
+    return Err(Error::new(
+      ErrorKind::InvalidInput,
+      format!("Invalid clipboard format: {}", format),
+    ));
+  }
 
-    // クリップボードを閉じる
-    CloseClipboard();
+  let clipboard = ClipboardGuard::open(0)?;
 
-    if data.is_empty() {
-      Err(Error::new(
-        ErrorKind::Other,
-        "No data available in clipboard",
-      ))
-    } else {
-      Ok(data)
-    }
+  if unsafe { IsClipboardFormatAvailable(format_id) } == 0 {
+    return Err(Error::new(
+      ErrorKind::NotFound,
+      format!("Format {} is not available in clipboard", format),
+    ));
   }
+
+  // ロックしたメモリから直接分割して渡す（全体をコピーしない）
+  let handle = clipboard.data(format_id)?;
+  read_global(handle, |data| stream::feed_chunks(data, sink))?
 }
 
 // クリップボードからファイルパスを読み取る
 pub fn read_clipboard_file_paths() -> Result<Vec<String>, Error> {
-  unsafe {
-    // クリップボードを開く
-    if OpenClipboard(0) == 0 {
-      return Err(Error::new(
-        ErrorKind::Other,
-        format!("Failed to open clipboard: {:?}", GetLastError()),
-      ));
-    }
+  let clipboard = ClipboardGuard::open(0)?;
+
+  // CF_HDROPフォーマットが利用可能か確認
+  if unsafe { IsClipboardFormatAvailable(CF_HDROP) } == 0 {
+    return Err(Error::new(
+      ErrorKind::Other,
+      "No file paths available in clipboard",
+    ));
+  }
 
-    // CF_HDROPフォーマットが利用可能か確認
-    if IsClipboardFormatAvailable(CF_HDROP) == 0 {
-      CloseClipboard();
-      return Err(Error::new(
-        ErrorKind::Other,
-        "No file paths available in clipboard",
-      ));
-    }
+  // DROPFILES のバイト列を読み取る
+  let hdrop = clipboard.data(CF_HDROP)?;
+  let paths = read_global(hdrop, |data| dropfiles::decode_with(data, ansi_to_string))??.paths;
 
-    // クリップボードからデータを取得
-    let hdrop = GetClipboardData(CF_HDROP);
-    if hdrop == 0 {
-      CloseClipboard();
-      return Err(Error::new(ErrorKind::Other, "Failed to get clipboard data"));
-    }
+  // クリップボードを閉じる
+  drop(clipboard);
 
-    // ファイル数を取得 (0xFFFFFFFFを指定すると、ファイル数が返る)
-    let file_count = DragQueryFileW(hdrop as isize, 0xFFFFFFFF, ptr::null_mut(), 0);
+  if paths.is_empty() {
+    Err(Error::new(
+      ErrorKind::Other,
+      "No valid file paths found in clipboard",
+    ))
+  } else {
+    Ok(paths)
+  }
+}
 
-    let mut paths = Vec::new();
+// クリップボードのファイルを URI とパスの組として読み取る（CF_HDROP はローカルのパスだけ）
+pub fn read_clipboard_file_entries() -> Result<Vec<FileEntry>, Error> {
+  Ok(
+    read_clipboard_file_paths()?
+      .iter()
+      .map(|path| FileEntry::from_path(path))
+      .collect(),
+  )
+}
 
-    // 各ファイルパスを取得
-    for i in 0..file_count {
-      // 必要なバッファサイズを取得
-      let size = DragQueryFileW(hdrop as isize, i, ptr::null_mut(), 0) + 1;
+// 遅延レンダリングでクリップボードを所有するウィンドウのクラス名
+const LAZY_WINDOW_CLASS: &str = "ClipFilepathsLazyRenderer";
 
-      // バッファを確保
-      let mut buffer = vec![0u16; size as usize];
+// 遅延レンダリングのフォーマット（ID と名前）と provider
+struct LazyRenderer {
+  formats: Vec<(u32, String)>,
+  provider: Provider,
+}
+
+thread_local! {
+  // ウィンドウのスレッドでのみ使う
+  static LAZY_RENDERER: RefCell<Option<LazyRenderer>> = RefCell::new(None);
+}
+
+/// 要求されたときに `provider` でデータを生成するようにクリップボードに書き込む
+///
+/// メッセージ専用ウィンドウをクリップボードの所有者にして、データなしでフォーマットだけを
+/// 登録する（遅延レンダリング）。貼り付け先が要求すると WM_RENDERFORMAT が届く。
+/// ウィンドウは別のアプリがクリップボードを書き換えるまで専用のスレッドで動き続ける。
+pub fn write_clipboard_lazy(formats: &[String], provider: Provider) -> Result<(), Error> {
+  let formats: Vec<(u32, String)> = formats
+    .iter()
+    .map(|format| (clipboard_format_id(format), format.clone()))
+    .collect();
+  if let Some((_, format)) = formats.iter().find(|(id, _)| *id == 0) {
+    return Err(Error::new(
+      ErrorKind::InvalidInput,
+      format!("Failed to register clipboard format: {}", format),
+    ));
+  }
+  let count = formats.len();
+
+  let (ready_tx, ready_rx) = mpsc::channel();
+  std::thread::spawn(move || unsafe {
+    let hwnd = match create_lazy_window() {
+      Ok(hwnd) => hwnd,
+      Err(e) => {
+        let _ = ready_tx.send(Err(e));
+        return;
+      }
+    };
+    let registered = register_lazy_formats(hwnd, &formats);
+    let owned = registered.is_ok();
+    LAZY_RENDERER.with(|r| *r.borrow_mut() = Some(LazyRenderer { formats, provider }));
+    let _ = ready_tx.send(registered);
+
+    if owned {
+      // WM_DESTROYCLIPBOARD で PostQuitMessage されるまでメッセージを処理する
+      let mut msg: MSG = zeroed();
+      while GetMessageW(&mut msg, 0, 0, 0) > 0 {
+        DispatchMessageW(&msg);
+      }
+    }
+    DestroyWindow(hwnd);
+    LAZY_RENDERER.with(|r| r.borrow_mut().take());
+  });
+
+  ready_rx.recv().unwrap_or_else(|_| {
+    Err(Error::other(
+      "Clipboard renderer thread exited unexpectedly",
+    ))
+  })?;
+  println!("Registered {} lazy formats to clipboard on Windows", count);
+  Ok(())
+}
 
-      // ファイル名を取得
-      let length = DragQueryFileW(hdrop as isize, i, buffer.as_mut_ptr(), size);
+// 遅延レンダリング用のメッセージ専用ウィンドウを作成する
+unsafe fn create_lazy_window() -> Result<HWND, Error> {
+  let class_name = to_wide_null(LAZY_WINDOW_CLASS);
+  static REGISTER: Once = Once::new();
+  REGISTER.call_once(|| {
+    let mut class: WNDCLASSW = zeroed();
+    class.lpfnWndProc = Some(lazy_window_proc);
+    class.lpszClassName = class_name.as_ptr();
+    RegisterClassW(&class);
+  });
+
+  let hwnd = CreateWindowExW(
+    0,
+    class_name.as_ptr(),
+    ptr::null(),
+    0,
+    0,
+    0,
+    0,
+    0,
+    HWND_MESSAGE,
+    0,
+    0,
+    ptr::null(),
+  );
+  if hwnd == 0 {
+    return Err(Error::new(
+      ErrorKind::Other,
+      format!("Failed to create clipboard window: {}", GetLastError()),
+    ));
+  }
+  Ok(hwnd)
+}
 
-      if length > 0 {
-        // 実際の長さに合わせてバッファをトリミング
-        buffer.truncate(length as usize);
+// ウィンドウをクリップボードの所有者にして、データなしでフォーマットを登録する
+fn register_lazy_formats(hwnd: HWND, formats: &[(u32, String)]) -> Result<(), Error> {
+  let clipboard = ClipboardGuard::open(hwnd)?;
+  clipboard.empty()?;
+  // データのハンドルに NULL を渡すと遅延レンダリングになる
+  for (format_id, _) in formats {
+    unsafe { SetClipboardData(*format_id, 0) };
+  }
+  Ok(())
+}
 
-        // UTF-16文字列をRust文字列に変換
-        let path = String::from_utf16_lossy(&buffer);
-        paths.push(path);
+unsafe extern "system" fn lazy_window_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  match msg {
+    // クリップボードはすでに開かれているので、データを設定するだけでよい
+    WM_RENDERFORMAT => {
+      render_lazy_format(wparam as u32);
+      0
+    }
+    // ウィンドウの破棄前にすべてのフォーマットを生成する
+    WM_RENDERALLFORMATS => {
+      if let Ok(_clipboard) = ClipboardGuard::open(hwnd) {
+        if GetClipboardOwner() == hwnd {
+          let format_ids: Vec<u32> = LAZY_RENDERER.with(|r| {
+            r.borrow()
+              .as_ref()
+              .map(|r| r.formats.iter().map(|(id, _)| *id).collect())
+              .unwrap_or_default()
+          });
+          for format_id in format_ids {
+            render_lazy_format(format_id);
+          }
+        }
       }
+      0
+    }
+    // 別のアプリがクリップボードを書き換えた
+    WM_DESTROYCLIPBOARD => {
+      PostQuitMessage(0);
+      0
     }
+    _ => DefWindowProcW(hwnd, msg, wparam, lparam),
+  }
+}
 
-    // クリップボードを閉じる
-    CloseClipboard();
+// provider でデータを生成してクリップボードに設定する
+fn render_lazy_format(format_id: u32) {
+  // provider の実行中に RefCell を借用したままにしない
+  let Some((format, provider)) = LAZY_RENDERER.with(|r| {
+    let renderer = r.borrow();
+    let renderer = renderer.as_ref()?;
+    let (_, format) = renderer.formats.iter().find(|(id, _)| *id == format_id)?;
+    Some((format.clone(), Arc::clone(&renderer.provider)))
+  }) else {
+    return;
+  };
+
+  // 生成できなかった場合はデータを設定せず、貼り付け先には提供しないフォーマットになる
+  // （WM_RENDERFORMAT にはエラーを返す方法がない）
+  let Ok(Some(data)) = provider(&format) else {
+    return;
+  };
+
+  // 成功した場合はメモリの所有権が OS に移り、失敗した場合は解放される
+  if let Ok(memory) = GlobalMemory::new(&data) {
+    let _ = memory.set_clipboard_data(format_id);
+  }
+}
 
-    if paths.is_empty() {
-      Err(Error::new(
-        ErrorKind::Other,
-        "No valid file paths found in clipboard",
-      ))
-    } else {
-      Ok(paths)
+// クリップボードの所有者のウィンドウ・プロセスと、書き込み元の印を取得する
+// GetClipboardOwner は所有者のウィンドウを返すため、そこからプロセスを辿る
+pub fn clipboard_owner_info() -> Result<OwnerInfo, Error> {
+  let mut info = OwnerInfo::default();
+
+  let hwnd = unsafe { GetClipboardOwner() };
+  if hwnd != 0 {
+    info.window = Some(hwnd as u64);
+    let mut pid: u32 = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, &mut pid) };
+    if pid != 0 {
+      info.pid = Some(pid);
+      info.executable = process_executable(pid);
     }
   }
+
+  let mut marker = Vec::new();
+  match read_clipboard_format(SOURCE_MARKER_FORMAT, &mut |chunk| {
+    marker.extend_from_slice(chunk);
+    Ok(())
+  }) {
+    Ok(_) => info.source = owner_info::parse_source_marker(&marker),
+    Err(e) if e.kind() == ErrorKind::NotFound => {}
+    Err(e) => return Err(e),
+  }
+  Ok(info)
+}
+
+// プロセスの実行ファイルのパスを取得する
+// 権限のないプロセスなどで取得できない場合は None を返す
+fn process_executable(pid: u32) -> Option<String> {
+  unsafe {
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+    if process == 0 {
+      return None;
+    }
+    let mut buffer = [0u16; 1024];
+    let mut len = buffer.len() as u32;
+    let ok = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32, buffer.as_mut_ptr(), &mut len);
+    CloseHandle(process);
+    (ok != 0).then(|| String::from_utf16_lossy(&buffer[..len as usize]))
+  }
+}
+
+// クリップボードの所有期間を識別する値を取得する
+// クリップボードの内容が変わるたびに増えるシーケンス番号を使う
+pub fn clipboard_epoch() -> Result<u64, Error> {
+  let sequence = unsafe { GetClipboardSequenceNumber() };
+  Ok(sequence as u64)
+}
+
+/// 書き込んだ内容の所有状態（シーケンス番号で判定する）
+pub type Ownership = SequenceOwnership;
+
+// 書き込みを実行し、その内容の所有状態を返す
+pub fn track_ownership(
+  selection: Selection,
+  write: impl FnOnce() -> Result<(), Error>,
+) -> Result<Ownership, Error> {
+  SequenceOwnership::track(selection, clipboard_epoch, write)
+}
+
+// クリップボードを空にし、空になった状態の所有状態を返す
+pub fn clear_clipboard(selection: Selection) -> Result<Ownership, Error> {
+  SequenceOwnership::track(selection, clipboard_epoch, || {
+    ClipboardGuard::open(0)?.empty()
+  })
+}
+
+// クリップボードが変更されるたびに増加する値を取得する
+pub fn clipboard_change_count(selection: Selection) -> Result<u64, Error> {
+  if selection != Selection::Clipboard {
+    return Err(Error::new(
+      ErrorKind::Unsupported,
+      "Only the clipboard selection is available on Windows",
+    ));
+  }
+  clipboard_epoch()
 }
 
 #[cfg(test)]
@@ -464,19 +820,42 @@ mod tests {
     let path_str = test_file_path.to_string_lossy().to_string();
 
     // クリップボードにコピー
-    let result = write_clipboard_file_paths(&[path_str]);
+    let result = write_clipboard_file_paths(&[path_str], None, None);
     assert!(result.is_ok(), "Failed to copy files: {:?}", result);
 
     // テスト後にファイルを削除
     let _ = std::fs::remove_file(test_file_path);
   }
 
+  // 他のプロセスがクリップボードを開いている間の失敗は、開けるまで再試行する
+  #[test]
+  fn test_open_clipboard_retries_transient_failures() {
+    let policy = retry::RetryPolicy {
+      attempts: 5,
+      backoff: std::time::Duration::from_millis(1),
+      deadline: std::time::Duration::from_secs(1),
+    };
+    let mut calls = 0;
+    let guard = retry::with_policy(Some(policy), || {
+      ClipboardGuard::open_with(0, |owner| {
+        calls += 1;
+        if calls <= 2 {
+          0
+        } else {
+          unsafe { OpenClipboard(owner) }
+        }
+      })
+    });
+    assert!(guard.is_ok(), "{:?}", guard.err());
+    assert_eq!(calls, 3);
+  }
+
   // 空のパスリストのテスト
   // Windowsの実装では空のリストでもメモリ確保などは行うが、
   // エラーにはならない。ただし実用上は空リスト前にチェックする方が良い
   #[test]
   fn test_empty_paths() {
-    let result = write_clipboard_file_paths(&[]);
+    let result = write_clipboard_file_paths(&[], None, None);
     // この実装では空リストでもエラーにはならない
     // 注: lib.rs側で空チェックを行っているため、通常は到達しない
     assert!(result.is_ok());
@@ -0,0 +1,470 @@
+// Windows のパスの正規化
+//
+// `fs::canonicalize` は `\\?\C:\...` や `\\?\UNC\server\share\...` のような
+// 拡張長パス（verbatim）を返すが、CF_HDROP を受け取るアプリケーションの多くは
+// この形式を扱えない。また UNC パスの file URI は `file://server/share/...` になる。
+// OS の API に依存せず文字列だけで処理するため、どのプラットフォームでもテストできる。
+
+#![cfg_attr(not(target_os = "windows"), allow(dead_code))]
+
+// パスの先頭部分
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Root {
+  /// `foo\bar`
+  Relative,
+  /// `\foo`（現在のドライブのルートから）
+  CurrentDrive,
+  /// `C:foo`（ドライブ C の現在のディレクトリから）
+  DriveRelative(char),
+  /// `C:\foo`
+  Drive(char),
+  /// `\\server\share\foo`
+  Unc { server: String, share: String },
+  /// ドライブや UNC で表せない拡張長パス（`\\?\Volume{...}` など）。先頭部分をそのまま保持する
+  Verbatim(String),
+}
+
+impl Root {
+  fn is_absolute(&self) -> bool {
+    matches!(self, Root::Drive(_) | Root::Unc { .. } | Root::Verbatim(_))
+  }
+
+  // `..` でこれより上に戻れないかどうか
+  fn is_rooted(&self) -> bool {
+    !matches!(self, Root::Relative | Root::DriveRelative(_))
+  }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WindowsPath {
+  root: Root,
+  components: Vec<String>,
+}
+
+impl WindowsPath {
+  fn parse(path: &str) -> Self {
+    let path = path.replace('/', "\\");
+    let (root, rest) = parse_root(&path);
+    let mut parsed = Self {
+      root,
+      components: Vec::new(),
+    };
+    parsed.push_all(rest);
+    parsed
+  }
+
+  // `\` 区切りの要素を追加する。`.` と空の要素は無視し、`..` は一つ上に戻る
+  fn push_all(&mut self, rest: &str) {
+    for component in rest.split('\\') {
+      match component {
+        "" | "." => {}
+        ".." => {
+          let can_pop = self.components.last().is_some_and(|last| last != "..");
+          if can_pop {
+            self.components.pop();
+          } else if !self.root.is_rooted() {
+            // 相対パスの先頭の `..` は残す
+            self.components.push("..".to_string());
+          }
+        }
+        _ => self.components.push(component.to_string()),
+      }
+    }
+  }
+
+  fn to_path_string(&self) -> String {
+    let components = self.components.join("\\");
+    match &self.root {
+      Root::Relative if components.is_empty() => ".".to_string(),
+      Root::Relative => components,
+      Root::CurrentDrive => format!("\\{}", components),
+      Root::DriveRelative(drive) => format!("{}:{}", drive, components),
+      Root::Drive(drive) => format!("{}:\\{}", drive, components),
+      Root::Unc { server, share } => join_root(&format!("\\\\{}\\{}", server, share), &components),
+      Root::Verbatim(prefix) => join_root(prefix, &components),
+    }
+  }
+}
+
+fn join_root(root: &str, components: &str) -> String {
+  if components.is_empty() {
+    root.to_string()
+  } else {
+    format!("{}\\{}", root, components)
+  }
+}
+
+// パスの先頭部分と、残りの部分に分ける
+fn parse_root(path: &str) -> (Root, &str) {
+  // 拡張長パス（`\\?\`）とデバイスパス（`\\.\`）の接頭辞を取り除く
+  for prefix in ["\\\\?\\", "\\\\.\\"] {
+    let Some(rest) = path.strip_prefix(prefix) else {
+      continue;
+    };
+    if let Some(unc) = strip_prefix_ignore_case(rest, "UNC\\") {
+      return parse_unc(unc);
+    }
+    if let Some((drive, rest)) = parse_drive(rest) {
+      if rest.is_empty() || rest.starts_with('\\') {
+        return (Root::Drive(drive), rest);
+      }
+    }
+    // `\\?\Volume{...}\` などはそのまま保持する
+    let (device, rest) = rest.split_once('\\').unwrap_or((rest, ""));
+    return (Root::Verbatim(format!("{}{}", prefix, device)), rest);
+  }
+
+  if let Some(unc) = path.strip_prefix("\\\\") {
+    return parse_unc(unc);
+  }
+  if let Some((drive, rest)) = parse_drive(path) {
+    return match rest.strip_prefix('\\') {
+      Some(rest) => (Root::Drive(drive), rest),
+      None => (Root::DriveRelative(drive), rest),
+    };
+  }
+  match path.strip_prefix('\\') {
+    Some(rest) => (Root::CurrentDrive, rest),
+    None => (Root::Relative, path),
+  }
+}
+
+fn parse_unc(path: &str) -> (Root, &str) {
+  let mut parts = path.splitn(3, '\\');
+  let server = parts.next().unwrap_or_default().to_string();
+  let share = parts.next().unwrap_or_default().to_string();
+  (
+    Root::Unc { server, share },
+    parts.next().unwrap_or_default(),
+  )
+}
+
+// `C:` で始まる場合はドライブ文字（大文字）と残りを返す
+fn parse_drive(path: &str) -> Option<(char, &str)> {
+  let mut chars = path.chars();
+  let drive = chars.next().filter(char::is_ascii_alphabetic)?;
+  let rest = chars.as_str().strip_prefix(':')?;
+  Some((drive.to_ascii_uppercase(), rest))
+}
+
+fn strip_prefix_ignore_case<'a>(s: &'a str, prefix: &str) -> Option<&'a str> {
+  let head = s.get(..prefix.len())?;
+  head
+    .eq_ignore_ascii_case(prefix)
+    .then(|| &s[prefix.len()..])
+}
+
+/// パスを CF_HDROP などに渡せる形に正規化する
+///
+/// 拡張長パスの接頭辞（`\\?\`, `\\?\UNC\`）を取り除き、区切り文字を `\` にそろえ、
+/// `.` と `..` を解決する。ドライブ文字は大文字になる。
+pub fn normalize(path: &str) -> String {
+  WindowsPath::parse(path).to_path_string()
+}
+
+/// パスを `cwd`（絶対パス）を基準にした絶対パスにする
+///
+/// `C:foo` のようなドライブ相対パスは、`cwd` が同じドライブならそのディレクトリを、
+/// 別のドライブならドライブのルートを基準にする（ドライブごとの現在のディレクトリは
+/// プロセスの状態のため扱わない）。`\foo` は `cwd` のドライブまたは UNC 共有のルートを基準にする。
+pub fn absolutize(path: &str, cwd: &str) -> String {
+  let path = WindowsPath::parse(path);
+  if path.root.is_absolute() {
+    return path.to_path_string();
+  }
+  let base = WindowsPath::parse(cwd);
+  if !base.root.is_absolute() {
+    return path.to_path_string();
+  }
+
+  let mut joined = match &path.root {
+    Root::CurrentDrive => WindowsPath {
+      root: base.root,
+      components: Vec::new(),
+    },
+    Root::DriveRelative(drive) if base.root != Root::Drive(*drive) => WindowsPath {
+      root: Root::Drive(*drive),
+      components: Vec::new(),
+    },
+    _ => base,
+  };
+  joined.push_all(&path.components.join("\\"));
+  joined.to_path_string()
+}
+
+/// `path` を `base`（どちらも絶対パス）からの相対パスにする
+///
+/// ドライブや UNC の共有が異なる場合は相対パスで表せないため None を返す。
+/// ドライブ文字・サーバー名・各要素は大文字小文字を区別せずに比べる。
+pub fn relative(path: &str, base: &str) -> Option<String> {
+  let path = WindowsPath::parse(path);
+  let base = WindowsPath::parse(base);
+  if !path.root.is_absolute() || !same_root(&path.root, &base.root) {
+    return None;
+  }
+
+  let common = path
+    .components
+    .iter()
+    .zip(&base.components)
+    .take_while(|(a, b)| a.to_lowercase() == b.to_lowercase())
+    .count();
+  let mut components = vec![".."; base.components.len() - common];
+  components.extend(path.components[common..].iter().map(String::as_str));
+  if components.is_empty() {
+    Some(".".to_string())
+  } else {
+    Some(components.join("\\"))
+  }
+}
+
+fn same_root(a: &Root, b: &Root) -> bool {
+  match (a, b) {
+    (Root::Drive(a), Root::Drive(b)) => a == b,
+    (
+      Root::Unc { server, share },
+      Root::Unc {
+        server: other_server,
+        share: other_share,
+      },
+    ) => {
+      server.eq_ignore_ascii_case(other_server)
+        && share.to_lowercase() == other_share.to_lowercase()
+    }
+    (Root::Verbatim(a), Root::Verbatim(b)) => a.eq_ignore_ascii_case(b),
+    _ => false,
+  }
+}
+
+/// 絶対パスを file URI に変換する
+///
+/// `C:\a b\c.txt` は `file:///C:/a%20b/c.txt`、`\\server\share\c.txt` は
+/// `file://server/share/c.txt` になる。ドライブや UNC で表せないパスは None。
+pub fn to_file_uri(path: &str) -> Option<String> {
+  let path = WindowsPath::parse(path);
+  let components: Vec<String> = path.components.iter().map(|c| percent_encode(c)).collect();
+  let components = components.join("/");
+
+  match &path.root {
+    Root::Drive(drive) => Some(format!("file:///{}:/{}", drive, components)),
+    Root::Unc { server, share } if !server.is_empty() && !share.is_empty() => {
+      let root = format!("file://{}/{}", server, percent_encode(share));
+      if components.is_empty() {
+        Some(root)
+      } else {
+        Some(format!("{}/{}", root, components))
+      }
+    }
+    _ => None,
+  }
+}
+
+/// URI のパスの 1 要素として使えない文字を %XX にする（UTF-8 のバイト単位）
+pub fn percent_encode(component: &str) -> String {
+  let mut encoded = String::with_capacity(component.len());
+  for byte in component.bytes() {
+    let unreserved = byte.is_ascii_alphanumeric() || b"-._~!$&'()*+,;=@".contains(&byte);
+    if unreserved {
+      encoded.push(byte as char);
+    } else {
+      encoded.push_str(&format!("%{:02X}", byte));
+    }
+  }
+  encoded
+}
+
+/// %XX をバイトに戻す（不正な並びはそのまま残す）
+pub fn percent_decode(component: &str) -> String {
+  let bytes = component.as_bytes();
+  let mut decoded = Vec::with_capacity(bytes.len());
+  let mut i = 0;
+  while i < bytes.len() {
+    let hex = bytes
+      .get(i + 1..i + 3)
+      .and_then(|h| std::str::from_utf8(h).ok())
+      .and_then(|h| u8::from_str_radix(h, 16).ok());
+    match (bytes[i], hex) {
+      (b'%', Some(byte)) => {
+        decoded.push(byte);
+        i += 3;
+      }
+      (byte, _) => {
+        decoded.push(byte);
+        i += 1;
+      }
+    }
+  }
+  String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  // 拡張長パスの接頭辞を取り除き、区切り文字と `.` / `..` をそろえる
+  #[test]
+  fn test_normalize() {
+    let cases = [
+      // 拡張長パス
+      (r"\\?\C:\Users\me\file.txt", r"C:\Users\me\file.txt"),
+      (r"\\?\c:\", r"C:\"),
+      (
+        r"\\?\UNC\server\share\dir\file.txt",
+        r"\\server\share\dir\file.txt",
+      ),
+      (r"\\?\unc\server\share", r"\\server\share"),
+      (r"\\.\C:\dev", r"C:\dev"),
+      (
+        r"\\?\Volume{0b1c2d3e-0000-0000-0000-000000000000}\dir",
+        r"\\?\Volume{0b1c2d3e-0000-0000-0000-000000000000}\dir",
+      ),
+      // 区切り文字と `.` / `..`
+      ("C:/Users/me/../you/./file.txt", r"C:\Users\you\file.txt"),
+      (r"C:\..\file.txt", r"C:\file.txt"),
+      (r"C:\dir\\file.txt", r"C:\dir\file.txt"),
+      (r"\\server\share\..\x", r"\\server\share\x"),
+      // 相対パス
+      (r"dir\..\..\file.txt", r"..\file.txt"),
+      (r"dir\..", "."),
+      (r"\dir\file.txt", r"\dir\file.txt"),
+      (r"d:dir\file.txt", r"D:dir\file.txt"),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(normalize(input), expected, "normalize({:?})", input);
+    }
+  }
+
+  // 相対パス・ドライブ相対パス・ルートからのパスを絶対パスにする
+  #[test]
+  fn test_absolutize() {
+    let cwd = r"C:\work\project";
+    let cases = [
+      (r"src\main.rs", cwd, r"C:\work\project\src\main.rs"),
+      (r"..\other", cwd, r"C:\work\other"),
+      (r"\tmp\file.txt", cwd, r"C:\tmp\file.txt"),
+      // ドライブ相対パス
+      (r"c:notes.txt", cwd, r"C:\work\project\notes.txt"),
+      (r"D:notes.txt", cwd, r"D:\notes.txt"),
+      // すでに絶対パスの場合はそのまま
+      (r"E:\data", cwd, r"E:\data"),
+      (r"\\?\C:\data", cwd, r"C:\data"),
+      (r"\\server\share\data", cwd, r"\\server\share\data"),
+      // UNC 共有の中での相対パス
+      (
+        r"file.txt",
+        r"\\server\share\dir",
+        r"\\server\share\dir\file.txt",
+      ),
+      (
+        r"\file.txt",
+        r"\\server\share\dir",
+        r"\\server\share\file.txt",
+      ),
+      (
+        r"..\..\..\file.txt",
+        r"\\server\share\dir",
+        r"\\server\share\file.txt",
+      ),
+      // 基準が絶対パスでない場合は正規化だけを行う
+      (r"a\..\b", r"relative", "b"),
+    ];
+    for (input, cwd, expected) in cases {
+      assert_eq!(
+        absolutize(input, cwd),
+        expected,
+        "absolutize({:?}, {:?})",
+        input,
+        cwd
+      );
+    }
+  }
+
+  // 同じドライブ・共有の中では `..` を使った相対パスになり、異なる場合は None
+  #[test]
+  fn test_relative() {
+    let cases = [
+      (
+        r"C:\work\project\src\main.rs",
+        r"C:\work\project",
+        Some(r"src\main.rs"),
+      ),
+      (
+        r"C:\work\other\a.txt",
+        r"C:\work\project",
+        Some(r"..\other\a.txt"),
+      ),
+      (r"C:\a.txt", r"C:\work\project", Some(r"..\..\a.txt")),
+      (r"C:\work\project", r"C:\work\project", Some(".")),
+      (r"C:\work", r"C:\work\project\src", Some(r"..\..")),
+      // 大文字小文字と拡張長パスの違いは無視する
+      (
+        r"\\?\c:\Work\Project\a.txt",
+        r"C:\work\project",
+        Some("a.txt"),
+      ),
+      (
+        r"\\Server\Share\dir\a.txt",
+        r"\\server\share\other",
+        Some(r"..\dir\a.txt"),
+      ),
+      (r"C:\work\.\x\..\a.txt", r"C:\work\", Some("a.txt")),
+      // 異なるドライブ・共有、または絶対パスでない
+      (r"D:\data\a.txt", r"C:\work", None),
+      (r"\\server\other\a.txt", r"\\server\share", None),
+      (r"\\server\share\a.txt", r"C:\work", None),
+      (r"relative\a.txt", r"C:\work", None),
+    ];
+    for (path, base, expected) in cases {
+      assert_eq!(
+        relative(path, base).as_deref(),
+        expected,
+        "relative({:?}, {:?})",
+        path,
+        base
+      );
+    }
+  }
+
+  // ドライブは file:///C:/...、UNC は file://server/share/... になる
+  #[test]
+  fn test_to_file_uri() {
+    let cases = [
+      (
+        r"C:\Users\me\file.txt",
+        Some("file:///C:/Users/me/file.txt"),
+      ),
+      (r"\\?\C:\a b\#1%.txt", Some("file:///C:/a%20b/%231%25.txt")),
+      (r"C:\", Some("file:///C:/")),
+      (
+        r"C:\データ\ü.txt",
+        Some("file:///C:/%E3%83%87%E3%83%BC%E3%82%BF/%C3%BC.txt"),
+      ),
+      (
+        r"\\server\share\dir\file.txt",
+        Some("file://server/share/dir/file.txt"),
+      ),
+      (
+        r"\\?\UNC\server\share\file.txt",
+        Some("file://server/share/file.txt"),
+      ),
+      (r"\\server\my share", Some("file://server/my%20share")),
+      // 絶対パスでない、または表せないパス
+      (r"relative\file.txt", None),
+      (r"\rooted\file.txt", None),
+      (r"C:file.txt", None),
+      (r"\\server", None),
+      (
+        r"\\?\Volume{0b1c2d3e-0000-0000-0000-000000000000}\dir",
+        None,
+      ),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(
+        to_file_uri(input).as_deref(),
+        expected,
+        "to_file_uri({:?})",
+        input
+      );
+    }
+  }
+}
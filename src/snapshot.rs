@@ -0,0 +1,135 @@
+// クリップボードの一貫したスナップショットを取得する実装
+//
+// 複数の形式を別々に読み取ると、その間にクリップボードが書き換わる可能性がある。
+// 読み取りの前後で所有期間（X11 のセレクションのタイムスタンプ、Windows のシーケンス番号、
+// macOS の changeCount）を比較し、変わっていれば読み直す。
+
+use std::io::{Error, ErrorKind};
+
+/// デフォルトの最大試行回数
+pub const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+
+/// スナップショットの取得結果
+#[derive(Debug)]
+pub struct Snapshot<T> {
+  pub value: T,
+  /// 読み取りを行った回数
+  pub attempts: u32,
+  /// 読み取りの前後で所有期間が変わっていないことを確認できたかどうか
+  pub verified: bool,
+}
+
+/// 所有期間が変わらない間に `read` を実行する
+///
+/// 所有期間が取得できない環境では 1 回だけ読み取り、`verified` を false にして返す。
+/// `max_attempts` 回とも読み取り中に所有者が変わった場合は `ErrorKind::Interrupted` を返す。
+pub fn read_consistent<T>(
+  max_attempts: u32,
+  mut epoch: impl FnMut() -> Result<u64, Error>,
+  mut read: impl FnMut() -> T,
+) -> Result<Snapshot<T>, Error> {
+  let max_attempts = max_attempts.max(1);
+
+  for attempts in 1..=max_attempts {
+    let Ok(before) = epoch() else {
+      return Ok(Snapshot {
+        value: read(),
+        attempts,
+        verified: false,
+      });
+    };
+
+    let value = read();
+
+    match epoch() {
+      Ok(after) if after == before => {
+        return Ok(Snapshot {
+          value,
+          attempts,
+          verified: true,
+        })
+      }
+      Ok(_) => continue,
+      Err(_) => {
+        return Ok(Snapshot {
+          value,
+          attempts,
+          verified: false,
+        })
+      }
+    }
+  }
+
+  Err(Error::new(
+    ErrorKind::Interrupted,
+    format!(
+      "Clipboard ownership changed during each of {} read attempts",
+      max_attempts
+    ),
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cell::Cell;
+
+  // 所有期間が変わらなければ 1 回で確定する
+  #[test]
+  fn test_read_consistent_stable() {
+    let snapshot = read_consistent(3, || Ok(7), || "data").unwrap();
+    assert_eq!(snapshot.value, "data");
+    assert_eq!(snapshot.attempts, 1);
+    assert!(snapshot.verified);
+  }
+
+  // 読み取り中に所有者が変わった場合は読み直す
+  #[test]
+  fn test_read_consistent_retries_on_change() {
+    // 1 回目の読み取り中に 1 → 2 に変わり、2 回目は 2 のまま
+    let epochs = [1, 2, 2, 2];
+    let calls = Cell::new(0);
+    let reads = Cell::new(0);
+    let snapshot = read_consistent(
+      3,
+      || {
+        let i = calls.get();
+        calls.set(i + 1);
+        Ok(epochs[i])
+      },
+      || {
+        reads.set(reads.get() + 1);
+        reads.get()
+      },
+    )
+    .unwrap();
+    assert_eq!(snapshot.value, 2);
+    assert_eq!(snapshot.attempts, 2);
+    assert!(snapshot.verified);
+  }
+
+  // 毎回変わり続ける場合はエラーになる
+  #[test]
+  fn test_read_consistent_gives_up() {
+    let counter = Cell::new(0u64);
+    let result = read_consistent(
+      3,
+      || {
+        counter.set(counter.get() + 1);
+        Ok(counter.get())
+      },
+      || (),
+    );
+    let err = result.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Interrupted);
+    assert!(err.to_string().contains("3 read attempts"));
+  }
+
+  // 所有期間を取得できない環境では確認なしで 1 回だけ読む
+  #[test]
+  fn test_read_consistent_without_epoch() {
+    let snapshot = read_consistent(3, || Err(Error::other("no X server")), || 1).unwrap();
+    assert_eq!(snapshot.attempts, 1);
+    assert!(!snapshot.verified);
+  }
+}
@@ -0,0 +1,297 @@
+// クリップボードのテキストからファイルパスを抽出する実装
+//
+// ターミナルからコピーされたパスはファイル形式ではなくテキストとしてのみ
+// クリップボードに載るため、テキストを解析してパスとして扱えるようにする。
+
+use std::path::Path;
+use url::Url;
+
+// POSIX シェルでバックスラッシュによりエスケープされうる文字
+// バックスラッシュ自体は含めない（Windows の UNC パス `\\server\share` を壊さないため）
+const SHELL_ESCAPABLE: &[char] = &[
+  ' ', '\t', '\'', '"', '$', '`', '(', ')', '&', ';', '|', '<', '>', '*', '?', '[', ']', '!', '#',
+  '{', '}', '=', ',',
+];
+
+/// テキストからファイルパスを抽出する
+///
+/// 以下の形式を認識する:
+/// * POSIX の絶対パス (`/usr/bin/env`)
+/// * ホームディレクトリからのパス (`~/Documents`)
+/// * `file://` URI
+/// * シェルでクォート・エスケープされたパス (`'/a b'`, `"/a b"`, `/a\ b`)
+/// * Windows のドライブパス (`C:\Users`) と UNC パス (`\\server\share`)
+///
+/// `exists` はパスが存在するかどうかの判定で、存在するものだけが返される。
+pub fn extract_paths(text: &str, exists: impl Fn(&str) -> bool) -> Vec<String> {
+  let mut paths = Vec::new();
+
+  for line in text.lines() {
+    let line = line.trim();
+    if line.is_empty() {
+      continue;
+    }
+
+    // 空白を含むパスがクォートされずに 1 行でコピーされたケースを優先する
+    if line.contains(char::is_whitespace)
+      && (!line.contains(['\'', '"', '\\']) || is_windows_path(line))
+    {
+      if let Some(path) = normalize_candidate(line) {
+        if exists(&path) {
+          paths.push(path);
+          continue;
+        }
+      }
+    }
+
+    for token in split_shell_words(line) {
+      if let Some(path) = normalize_candidate(&token) {
+        if exists(&path) {
+          paths.push(path);
+        }
+      }
+    }
+  }
+
+  dedup_preserving_order(paths)
+}
+
+/// テキストからファイルパスを抽出し、実際に存在するものだけを返す
+pub fn parse_text_as_paths(text: &str) -> Vec<String> {
+  extract_paths(text, |p| Path::new(p).exists())
+}
+
+// トークンがパスとして認識できれば、展開済みのパス文字列を返す
+fn normalize_candidate(token: &str) -> Option<String> {
+  if token.starts_with("file://") {
+    let url = Url::parse(token).ok()?;
+    let path = url.to_file_path().ok()?;
+    return Some(path.to_string_lossy().into_owned());
+  }
+
+  if let Some(rest) = token.strip_prefix("~/") {
+    let home = home_dir()?;
+    return Some(Path::new(&home).join(rest).to_string_lossy().into_owned());
+  }
+
+  if token.starts_with('/') || is_windows_path(token) {
+    return Some(token.to_string());
+  }
+
+  None
+}
+
+// Windows のドライブパス (`C:\`, `C:/`) または UNC パス (`\\server\share`) かどうか
+fn is_windows_path(s: &str) -> bool {
+  let bytes = s.as_bytes();
+  let is_drive = bytes.len() >= 3
+    && bytes[0].is_ascii_alphabetic()
+    && bytes[1] == b':'
+    && (bytes[2] == b'\\' || bytes[2] == b'/');
+  let is_unc = s.starts_with("\\\\")
+    && s[2..]
+      .split('\\')
+      .next()
+      .is_some_and(|server| !server.is_empty());
+  is_drive || is_unc
+}
+
+// ホームディレクトリを取得する
+fn home_dir() -> Option<String> {
+  std::env::var("HOME")
+    .or_else(|_| std::env::var("USERPROFILE"))
+    .ok()
+    .filter(|h| !h.is_empty())
+}
+
+// シェルの単語分割に近いルールで行をトークンに分割する
+// シングルクォート内はすべてリテラル、ダブルクォート内は `\"` のみエスケープとして扱う
+fn split_shell_words(line: &str) -> Vec<String> {
+  let mut words = Vec::new();
+  let mut current = String::new();
+  let mut in_word = false;
+  let mut chars = line.chars().peekable();
+
+  while let Some(c) = chars.next() {
+    match c {
+      '\'' => {
+        in_word = true;
+        for q in chars.by_ref() {
+          if q == '\'' {
+            break;
+          }
+          current.push(q);
+        }
+      }
+      '"' => {
+        in_word = true;
+        while let Some(q) = chars.next() {
+          match q {
+            '"' => break,
+            '\\' if chars.peek() == Some(&'"') => {
+              current.push('"');
+              chars.next();
+            }
+            _ => current.push(q),
+          }
+        }
+      }
+      '\\' => {
+        in_word = true;
+        match chars.peek() {
+          Some(next) if SHELL_ESCAPABLE.contains(next) => {
+            current.push(*next);
+            chars.next();
+          }
+          _ => current.push('\\'),
+        }
+      }
+      c if c.is_whitespace() => {
+        if in_word {
+          words.push(std::mem::take(&mut current));
+          in_word = false;
+        }
+      }
+      _ => {
+        in_word = true;
+        current.push(c);
+      }
+    }
+  }
+
+  if in_word {
+    words.push(current);
+  }
+
+  words
+}
+
+fn dedup_preserving_order(items: Vec<String>) -> Vec<String> {
+  let mut result: Vec<String> = Vec::with_capacity(items.len());
+  for item in items {
+    if !result.contains(&item) {
+      result.push(item);
+    }
+  }
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::env::temp_dir;
+  use std::fs::File;
+
+  // 存在確認を行わずに抽出する
+  fn extract_path_candidates(text: &str) -> Vec<String> {
+    extract_paths(text, |_| true)
+  }
+
+  // POSIX の絶対パスの抽出
+  #[test]
+  fn test_extract_posix_paths() {
+    let text = "/usr/bin/env\n/tmp/a.txt /tmp/b.txt\n";
+    assert_eq!(
+      extract_paths(text, |p| !p.contains(' ')),
+      vec!["/usr/bin/env", "/tmp/a.txt", "/tmp/b.txt"]
+    );
+  }
+
+  // パスではないテキストは無視される
+  #[test]
+  fn test_extract_ignores_plain_text() {
+    let text = "hello world\nrelative/path.txt\n# comment";
+    assert!(extract_path_candidates(text).is_empty());
+  }
+
+  // クォート・エスケープされたパス
+  #[test]
+  fn test_extract_quoted_and_escaped_paths() {
+    let cases = [
+      ("'/tmp/my file.txt'", "/tmp/my file.txt"),
+      ("\"/tmp/my file.txt\"", "/tmp/my file.txt"),
+      ("/tmp/my\\ file.txt", "/tmp/my file.txt"),
+      ("\"/tmp/say \\\"hi\\\".txt\"", "/tmp/say \"hi\".txt"),
+      ("/tmp/it\\'s.txt", "/tmp/it's.txt"),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(extract_path_candidates(input), vec![expected], "{}", input);
+    }
+  }
+
+  // 空白を含むパスが 1 行でそのまま書かれている場合は、存在すれば行全体を優先する
+  #[test]
+  fn test_extract_unquoted_line_with_spaces() {
+    let line = "  /home/user/My Documents/a.txt  ";
+    assert_eq!(
+      extract_paths(line, |p| p == "/home/user/My Documents/a.txt"),
+      vec!["/home/user/My Documents/a.txt"]
+    );
+    assert_eq!(
+      extract_paths(line, |p| p == "/home/user/My"),
+      vec!["/home/user/My"]
+    );
+  }
+
+  // file:// URI はデコードされたパスになる
+  #[test]
+  fn test_extract_file_uri() {
+    assert_eq!(
+      extract_path_candidates("file:///tmp/my%20file.txt"),
+      vec!["/tmp/my file.txt"]
+    );
+  }
+
+  // ~/ はホームディレクトリに展開される
+  #[test]
+  fn test_extract_home_relative_path() {
+    let Some(home) = home_dir() else {
+      return;
+    };
+    let expected = Path::new(&home)
+      .join("notes.txt")
+      .to_string_lossy()
+      .into_owned();
+    assert_eq!(extract_path_candidates("~/notes.txt"), vec![expected]);
+  }
+
+  // Windows のドライブパスと UNC パス
+  #[test]
+  fn test_extract_windows_paths() {
+    let cases = [
+      ("C:\\Users\\me\\a.txt", "C:\\Users\\me\\a.txt"),
+      ("d:/data/b.txt", "d:/data/b.txt"),
+      ("\\\\server\\share\\c.txt", "\\\\server\\share\\c.txt"),
+      (
+        "\"C:\\Program Files\\app.exe\"",
+        "C:\\Program Files\\app.exe",
+      ),
+      ("C:\\Program Files\\app.exe", "C:\\Program Files\\app.exe"),
+    ];
+    for (input, expected) in cases {
+      assert_eq!(extract_path_candidates(input), vec![expected], "{}", input);
+    }
+  }
+
+  // 重複したパスは 1 つにまとめられる
+  #[test]
+  fn test_extract_dedup() {
+    assert_eq!(
+      extract_path_candidates("/tmp/a\n/tmp/a\nfile:///tmp/a"),
+      vec!["/tmp/a"]
+    );
+  }
+
+  // 存在するパスだけが返される
+  #[test]
+  fn test_parse_text_as_paths_checks_existence() {
+    let existing = temp_dir().join("electron_pan_clip_text_paths.txt");
+    File::create(&existing).expect("Failed to create test file");
+    let existing_str = existing.to_string_lossy().to_string();
+
+    let text = format!("{}\n/path/does/not/exist/text_paths.txt", existing_str);
+    assert_eq!(parse_text_as_paths(&text), vec![existing_str]);
+
+    let _ = std::fs::remove_file(existing);
+  }
+}